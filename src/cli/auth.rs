@@ -1,11 +1,13 @@
 use crate::cli::config::read_config;
 use crate::cli::config::write_config;
 use anyhow::Context as _;
+use fs_err::tokio as fs;
 use keyring::Entry;
 use pesde::GixUrl;
 use reqwest::header::AUTHORIZATION;
 use serde::Deserialize;
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 use tokio::task::spawn_blocking;
 use tracing::instrument;
 
@@ -41,6 +43,58 @@ pub async fn get_tokens() -> anyhow::Result<Tokens> {
 	Ok(Tokens::default())
 }
 
+/// Reads tokens from the files referenced by the `PESDE_TOKENS_FILE` and `PESDE_TOKEN_FILE` environment variables,
+/// which is how CI systems commonly provide secrets
+#[instrument(level = "trace")]
+pub async fn get_env_tokens() -> anyhow::Result<Tokens> {
+	let token_file = match std::env::var_os("PESDE_TOKEN_FILE") {
+		Some(path) => Some((
+			PathBuf::from(path),
+			read_config().await?.resolved_default_index()?,
+		)),
+		None => None,
+	};
+
+	read_token_files(
+		std::env::var_os("PESDE_TOKENS_FILE").map(PathBuf::from),
+		token_file,
+	)
+	.await
+}
+
+/// Reads a JSON map of index URLs to tokens from `tokens_file`, and the token for the default index from `token_file`,
+/// which takes precedence
+async fn read_token_files(
+	tokens_file: Option<PathBuf>,
+	token_file: Option<(PathBuf, GixUrl)>,
+) -> anyhow::Result<Tokens> {
+	let mut tokens = Tokens::default();
+
+	if let Some(path) = tokens_file {
+		let contents = fs::read_to_string(path)
+			.await
+			.context("failed to read PESDE_TOKENS_FILE")?;
+		let file_tokens = serde_json::from_str::<Tokens>(&contents)
+			.context("failed to parse PESDE_TOKENS_FILE")?;
+
+		tokens.extend(
+			file_tokens
+				.into_iter()
+				.map(|(index, token)| (index, token.trim().to_string())),
+		);
+	}
+
+	if let Some((path, default_index)) = token_file {
+		let token = fs::read_to_string(path)
+			.await
+			.context("failed to read PESDE_TOKEN_FILE")?;
+
+		tokens.insert(default_index, token.trim().to_string());
+	}
+
+	Ok(tokens)
+}
+
 #[instrument(level = "trace")]
 pub async fn set_tokens(tokens: Tokens) -> anyhow::Result<()> {
 	let json = serde_json::to_string(&tokens).context("failed to serialize tokens")?;
@@ -84,11 +138,17 @@ struct UserResponse {
 	login: String,
 }
 
+pub struct TokenInfo {
+	pub login: String,
+	/// The OAuth scopes of the token, if GitHub reports them (only for classic tokens)
+	pub scopes: Option<Vec<String>>,
+}
+
 #[instrument(level = "trace")]
-pub async fn get_token_login(
+pub async fn get_token_info(
 	reqwest: &reqwest::Client,
 	access_token: &str,
-) -> anyhow::Result<String> {
+) -> anyhow::Result<TokenInfo> {
 	let response = reqwest
 		.get("https://api.github.com/user")
 		.header(AUTHORIZATION, access_token)
@@ -96,10 +156,80 @@ pub async fn get_token_login(
 		.await
 		.context("failed to send user request")?
 		.error_for_status()
-		.context("failed to get user")?
+		.context("failed to get user")?;
+
+	let scopes = response
+		.headers()
+		.get("x-oauth-scopes")
+		.and_then(|scopes| scopes.to_str().ok())
+		.map(|scopes| {
+			scopes
+				.split(',')
+				.map(str::trim)
+				.filter(|scope| !scope.is_empty())
+				.map(ToString::to_string)
+				.collect()
+		});
+
+	let response = response
 		.json::<UserResponse>()
 		.await
 		.context("failed to parse user response")?;
 
-	Ok(response.login)
+	Ok(TokenInfo {
+		login: response.login,
+		scopes,
+	})
+}
+
+pub async fn get_token_login(
+	reqwest: &reqwest::Client,
+	access_token: &str,
+) -> anyhow::Result<String> {
+	get_token_info(reqwest, access_token)
+		.await
+		.map(|info| info.login)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn token_files() {
+		let dir = tempfile::tempdir().unwrap();
+		let default_index = "https://github.com/acme/index".parse::<GixUrl>().unwrap();
+		let other_index = "https://github.com/acme/other".parse::<GixUrl>().unwrap();
+
+		let tokens_file = dir.path().join("tokens.json");
+		fs::write(
+			&tokens_file,
+			format!(r#"{{"{default_index}":"Bearer old","{other_index}":" Bearer other\n"}}"#),
+		)
+		.await
+		.unwrap();
+		let token_file = dir.path().join("token");
+		fs::write(&token_file, "Bearer new\n").await.unwrap();
+
+		assert!(read_token_files(None, None).await.unwrap().is_empty());
+
+		let tokens = read_token_files(Some(tokens_file.clone()), None)
+			.await
+			.unwrap();
+		assert_eq!(tokens[&default_index], "Bearer old");
+		assert_eq!(tokens[&other_index], "Bearer other");
+
+		// the token file overrides the default index's token from the tokens file
+		let tokens = read_token_files(Some(tokens_file), Some((token_file, default_index.clone())))
+			.await
+			.unwrap();
+		assert_eq!(tokens[&default_index], "Bearer new");
+		assert_eq!(tokens[&other_index], "Bearer other");
+
+		assert!(
+			read_token_files(Some(dir.path().join("missing.json")), None)
+				.await
+				.is_err()
+		);
+	}
 }
@@ -6,15 +6,21 @@ use pesde::source::Realm;
 use pesde::source::ResolveResult;
 
 use crate::cli::AnyPackageIdentifier;
+use crate::cli::VersionedPackageName;
 use crate::cli::dep_type_to_key;
 use pesde::DEFAULT_INDEX_NAME;
+use pesde::GixUrl;
 use pesde::RefreshedSources;
 use pesde::Subproject;
 use pesde::manifest::Alias;
 use pesde::manifest::DependencyType;
+use pesde::manifest::ManifestIndices;
 use pesde::source::DependencySpecifiers;
 use pesde::source::PackageSource as _;
 use pesde::source::path::RelativeOrAbsolutePath;
+#[expect(deprecated)]
+use pesde::source::pesde::target::TargetKind;
+use semver::Version;
 
 #[derive(Debug, Args)]
 pub struct AddCommand {
@@ -41,31 +47,57 @@ pub struct AddCommand {
 	/// Whether to add the package as a dev dependency
 	#[arg(short, long, conflicts_with = "peer")]
 	dev: bool,
+
+	/// Whether to allow prerelease versions when no version is specified
+	#[arg(long)]
+	pre: bool,
+
+	/// Whether to pin the exact resolved version instead of a caret range
+	#[arg(long)]
+	save_exact: bool,
+}
+
+/// Finds the URL of the index with the given name, resolving the default index of pesde
+/// dependencies the same way the resolver does
+#[expect(deprecated)]
+fn index_url(
+	indices: &ManifestIndices,
+	name: &str,
+	target: Option<TargetKind>,
+) -> anyhow::Result<GixUrl> {
+	let (indices, name) = match target {
+		Some(target) => (&indices.pesde, indices.pesde_index_name(name, target)),
+		None => (&indices.wally, name),
+	};
+
+	indices
+		.get(name)
+		.cloned()
+		.with_context(|| format!("index `{name}` not found"))
+}
+
+fn version_requirement(version: &Version, exact: bool) -> String {
+	if exact {
+		format!("={version}")
+	} else {
+		format!("^{version}")
+	}
 }
 
 impl AddCommand {
 	pub async fn run(self, subproject: Subproject) -> anyhow::Result<()> {
 		let (source, specifier) = self
 			.package
-			.source_and_specifier(self.realm, async |pesde| {
+			.source_and_specifier(self.realm, async |target| {
 				let manifest = subproject
 					.deser_manifest()
 					.await
 					.context("failed to read manifest")?;
 
-				let indices = if pesde {
-					&manifest.indices.pesde
-				} else {
-					&manifest.indices.wally
-				};
-
 				let name = self.index.as_deref().unwrap_or(DEFAULT_INDEX_NAME);
 				Ok((
 					name.to_string(),
-					indices
-						.get(name)
-						.with_context(|| format!("index `{name}` not found"))?
-						.clone(),
+					index_url(&manifest.indices, name, target)?,
 				))
 			})
 			.await
@@ -78,12 +110,24 @@ impl AddCommand {
 			.await
 			.context("failed to refresh package source")?;
 
-		let ResolveResult { mut versions, .. } = source
+		let ResolveResult { versions, .. } = source
 			.resolve(&subproject, &specifier, &refreshed_sources)
 			.await
 			.context("failed to resolve package")?;
 
-		let Some((version, _)) = versions.pop_last() else {
+		// prereleases are only picked if explicitly requested, either by version or by flag
+		let allow_pre = self.pre
+			|| !matches!(
+				&self.package,
+				AnyPackageIdentifier::PesdePackageName(VersionedPackageName(_, None))
+					| AnyPackageIdentifier::WallyPackageName(VersionedPackageName(_, None))
+			);
+
+		let Some(version) = versions
+			.into_keys()
+			.rev()
+			.find(|version| allow_pre || version.pre.is_empty())
+		else {
 			anyhow::bail!("no matching versions found for package");
 		};
 
@@ -135,7 +179,7 @@ impl AddCommand {
 			#[expect(deprecated)]
 			DependencySpecifiers::Pesde(spec) => {
 				field["name"] = toml_edit::value(spec.name.to_string());
-				field["version"] = toml_edit::value(format!("^{version}"));
+				field["version"] = toml_edit::value(version_requirement(&version, self.save_exact));
 
 				field["target"] = toml_edit::value(spec.target.to_string());
 
@@ -152,7 +196,7 @@ impl AddCommand {
 				let name_str = spec.name.to_string();
 				let name_str = name_str.trim_start_matches("wally#");
 				field["wally"] = toml_edit::value(name_str);
-				field["version"] = toml_edit::value(format!("^{version}"));
+				field["version"] = toml_edit::value(version_requirement(&version, self.save_exact));
 
 				if spec.index != DEFAULT_INDEX_NAME {
 					field["index"] = toml_edit::value(spec.index);
@@ -181,3 +225,130 @@ impl AddCommand {
 		Ok(())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::local_index::LocalIndex;
+	use clap::Parser as _;
+	use fs_err::tokio as fs;
+	use pesde::AuthConfig;
+	use pesde::Importer;
+	use pesde::MANIFEST_FILE_NAME;
+	use pesde::Project;
+	use semver::VersionReq;
+
+	#[derive(clap::Parser)]
+	struct Cli {
+		#[command(flatten)]
+		add: AddCommand,
+	}
+
+	#[test]
+	fn save_exact_pins_version() {
+		let version = Version::parse("1.2.3").unwrap();
+
+		let exact = version_requirement(&version, true);
+		assert_eq!(exact, "=1.2.3");
+		let req = VersionReq::parse(&exact).unwrap();
+		assert!(req.matches(&version));
+		assert!(!req.matches(&Version::parse("1.2.4").unwrap()));
+
+		assert_eq!(version_requirement(&version, false), "^1.2.3");
+	}
+
+	#[test]
+	#[expect(deprecated)]
+	fn index_url_uses_target_defaults() {
+		let indices = toml::from_str::<ManifestIndices>(
+			r#"
+			[indices]
+			default = "https://github.com/pesde-pkg/index"
+			roblox = "https://github.com/pesde-pkg/roblox-index"
+
+			[wally_indices]
+			default = "https://github.com/UpliftGames/wally-index"
+
+			[target_default_indices]
+			roblox = "roblox"
+			"#,
+		)
+		.unwrap();
+
+		let url = |name, target| index_url(&indices, name, target).map(|url| url.to_string());
+
+		assert_eq!(
+			url(DEFAULT_INDEX_NAME, Some(TargetKind::Roblox)).unwrap(),
+			"https://github.com/pesde-pkg/roblox-index"
+		);
+		assert_eq!(
+			url(DEFAULT_INDEX_NAME, Some(TargetKind::Luau)).unwrap(),
+			"https://github.com/pesde-pkg/index"
+		);
+		assert_eq!(
+			url("roblox", Some(TargetKind::Luau)).unwrap(),
+			"https://github.com/pesde-pkg/roblox-index"
+		);
+		assert_eq!(
+			url(DEFAULT_INDEX_NAME, None).unwrap(),
+			"https://github.com/UpliftGames/wally-index"
+		);
+		assert!(url("missing", Some(TargetKind::Luau)).is_err());
+	}
+
+	#[tokio::test]
+	async fn prereleases_need_opting_into() {
+		let dir = tempfile::tempdir().unwrap();
+		let mut index = LocalIndex::new(&dir.path().join("index"));
+		for version in ["1.0.0", "2.0.0-rc.1"] {
+			index
+				.publish(
+					"acme/foo",
+					version,
+					"{ environment = \"luau\", lib = \"init.luau\" }",
+					&[("init.luau", "return {}")],
+				)
+				.await;
+		}
+
+		let project_dir = dir.path().join("project");
+		fs::create_dir_all(&project_dir).await.unwrap();
+		fs::write(
+			project_dir.join(MANIFEST_FILE_NAME),
+			format!("[indices]\ndefault = \"{}\"\n", index.url()),
+		)
+		.await
+		.unwrap();
+		let project = Project::new(
+			&project_dir,
+			dir.path().join("data"),
+			dir.path().join("cas"),
+			AuthConfig::new(),
+			reqwest::Client::new(),
+		);
+
+		for args in [
+			&["acme/foo", "--alias", "stable"][..],
+			&["acme/foo", "--alias", "pre", "--pre"],
+			&["acme/foo@^2.0.0-rc.1", "--alias", "requested"],
+		] {
+			Cli::try_parse_from(std::iter::once("add").chain(args.iter().copied()))
+				.unwrap()
+				.add
+				.run(project.clone().subproject(Importer::root()))
+				.await
+				.unwrap();
+		}
+
+		let manifest = fs::read_to_string(project_dir.join(MANIFEST_FILE_NAME))
+			.await
+			.unwrap();
+		let dependencies =
+			toml::from_str::<toml::Table>(&manifest).unwrap()["dependencies"].clone();
+		let version = |alias: &str| dependencies[alias]["version"].as_str().unwrap().to_string();
+
+		assert_eq!(version("stable"), "^1.0.0");
+		assert_eq!(version("pre"), "^2.0.0-rc.1");
+		assert_eq!(version("requested"), "^2.0.0-rc.1");
+	}
+}
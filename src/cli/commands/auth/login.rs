@@ -21,9 +21,13 @@ use pesde::source::pesde::PesdePackageSource;
 
 #[derive(Debug, Args)]
 pub struct LoginCommand {
-	/// The token to use for authentication, skipping login
+	/// The token to use for authentication, skipping login. Fine-grained tokens can be used this way
 	#[arg(short, long)]
 	token: Option<String>,
+
+	/// The GitHub OAuth scopes to request. By default no scopes are requested, which only grants access to public information
+	#[arg(long, value_delimiter = ',', conflicts_with = "token")]
+	scope: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -53,11 +57,12 @@ enum AccessTokenResponse {
 }
 
 impl LoginCommand {
+	/// Returns `None` if the index is not configured for GitHub OAuth
 	pub async fn authenticate_device_flow(
 		&self,
 		index_url: GixUrl,
 		subproject: &Subproject,
-	) -> anyhow::Result<String> {
+	) -> anyhow::Result<Option<String>> {
 		println!("logging in into {index_url}");
 
 		let source = PesdePackageSource::from_url(index_url);
@@ -72,15 +77,22 @@ impl LoginCommand {
 			.await
 			.context("failed to read index config")?;
 		let Some(client_id) = config.github_oauth_client_id else {
-			anyhow::bail!("index not configured for Github oauth.");
+			return Ok(None);
 		};
 
+		let scope = self.scope.join(" ");
+		if scope.is_empty() {
+			println!("requesting no scopes, only granting access to public information");
+		} else {
+			println!("requesting scopes: {}", self.scope.join(", "));
+		}
+
 		let response = subproject
 			.project()
 			.reqwest()
 			.post(Url::parse_with_params(
 				"https://github.com/login/device/code",
-				&[("client_id", &client_id)],
+				&[("client_id", &client_id), ("scope", &scope)],
 			)?)
 			.header(ACCEPT, "application/json")
 			.send()
@@ -144,7 +156,7 @@ impl LoginCommand {
 
 			match response {
 				AccessTokenResponse::Success { access_token } => {
-					return Ok(access_token);
+					return Ok(Some(access_token));
 				}
 				AccessTokenResponse::Error(e) => match e {
 					AccessTokenError::AuthorizationPending => {}
@@ -166,15 +178,39 @@ impl LoginCommand {
 		anyhow::bail!("code expired, please re-run the login command");
 	}
 
+	/// Returns the token to use and whether it was given rather than obtained through OAuth,
+	/// calling `prompt` for it if the index is not configured for GitHub OAuth
+	async fn get_token(
+		&self,
+		index_url: &GixUrl,
+		subproject: &Subproject,
+		prompt: impl FnOnce() -> anyhow::Result<String>,
+	) -> anyhow::Result<(String, bool)> {
+		if let Some(token) = &self.token {
+			return Ok((token.clone(), true));
+		}
+
+		if let Some(token) = self
+			.authenticate_device_flow(index_url.clone(), subproject)
+			.await?
+		{
+			return Ok((token, false));
+		}
+
+		println!("{index_url} is not configured for GitHub OAuth, enter a token for it instead");
+
+		Ok((prompt()?, true))
+	}
+
 	pub async fn run(self, index_url: GixUrl, subproject: Subproject) -> anyhow::Result<()> {
-		let token_given = self.token.is_some();
-		let token = match self.token {
-			Some(token) => token,
-			None => {
-				self.authenticate_device_flow(index_url.clone(), &subproject)
-					.await?
-			}
-		};
+		let (token, token_given) = self
+			.get_token(&index_url, &subproject, || {
+				inquire::Password::new("token:")
+					.without_confirmation()
+					.prompt()
+					.context("failed to read token")
+			})
+			.await?;
 
 		let token = if token_given {
 			println!("set token for {index_url}");
@@ -202,3 +238,48 @@ impl LoginCommand {
 		Ok(())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::local_index::LocalIndex;
+	use pesde::AuthConfig;
+	use pesde::Importer;
+	use pesde::Project;
+
+	#[tokio::test]
+	async fn prompts_for_token_without_oauth() {
+		let dir = tempfile::tempdir().unwrap();
+		let index = LocalIndex::new(&dir.path().join("index"));
+		let index_url = index.url().parse::<GixUrl>().unwrap();
+		let subproject = Project::new(
+			dir.path().join("project"),
+			dir.path().join("data"),
+			dir.path().join("cas"),
+			AuthConfig::new(),
+			reqwest::Client::new(),
+		)
+		.subproject(Importer::root());
+
+		let command = |token: Option<&str>| LoginCommand {
+			token: token.map(str::to_string),
+			scope: vec![],
+		};
+
+		assert_eq!(
+			command(None)
+				.get_token(&index_url, &subproject, || Ok("github_pat_abc".to_string()))
+				.await
+				.unwrap(),
+			("github_pat_abc".to_string(), true)
+		);
+		// a token given as an argument is used without prompting
+		assert_eq!(
+			command(Some("given"))
+				.get_token(&index_url, &subproject, || unreachable!())
+				.await
+				.unwrap(),
+			("given".to_string(), true)
+		);
+	}
+}
@@ -1,25 +1,55 @@
-use crate::cli::auth::get_token_login;
+use crate::cli::auth::get_token_info;
 use crate::cli::auth::get_tokens;
+use anyhow::Context as _;
 use clap::Args;
 use console::style;
+use itertools::Itertools as _;
 use pesde::GixUrl;
 
 #[derive(Debug, Args)]
-pub struct WhoAmICommand;
+pub struct WhoAmICommand {
+	/// Whether to print the result as JSON
+	#[arg(long)]
+	json: bool,
+}
 
 impl WhoAmICommand {
 	pub async fn run(self, index_url: GixUrl, reqwest: &reqwest::Client) -> anyhow::Result<()> {
 		let tokens = get_tokens().await?;
-		let Some(token) = tokens.get(&index_url) else {
+		let info = match tokens.get(&index_url) {
+			Some(token) => Some(get_token_info(reqwest, token).await?),
+			None => None,
+		};
+
+		if self.json {
+			let json = serde_json::json!({
+				"index": index_url.to_string(),
+				"username": info.as_ref().map(|info| &info.login),
+				"scopes": info.as_ref().and_then(|info| info.scopes.as_ref()),
+			});
+
+			println!(
+				"{}",
+				serde_json::to_string_pretty(&json).context("failed to serialize output")?
+			);
+
+			return Ok(());
+		}
+
+		let Some(info) = info else {
 			println!("not logged in into {index_url}");
 			return Ok(());
 		};
 
 		println!(
 			"logged in as {} into {index_url}",
-			style(get_token_login(reqwest, token).await?).bold()
+			style(&info.login).bold()
 		);
 
+		if let Some(scopes) = info.scopes {
+			println!("token scopes: {}", scopes.iter().format(", "));
+		}
+
 		Ok(())
 	}
 }
@@ -0,0 +1,67 @@
+use crate::cli::style::SUCCESS_STYLE;
+use anyhow::Context as _;
+use async_compression::tokio::write::GzipEncoder;
+use clap::Args;
+use fs_err::tokio as fs;
+use pesde::Subproject;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt as _;
+
+#[derive(Debug, Args)]
+pub struct ExportCommand {
+	/// The path to write the archive to
+	#[arg(index = 1)]
+	output: PathBuf,
+}
+
+impl ExportCommand {
+	pub async fn run(self, subproject: Subproject) -> anyhow::Result<()> {
+		let cas_dir = subproject.project().cas_dir();
+
+		let file = fs::File::create(&self.output)
+			.await
+			.context("failed to create archive")?;
+		let mut archive = async_tar::Builder::new(GzipEncoder::new(file));
+
+		let mut read_dir = match fs::read_dir(cas_dir).await {
+			Ok(read_dir) => Some(read_dir),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+			Err(e) => return Err(e).context("failed to read CAS directory"),
+		};
+
+		if let Some(read_dir) = &mut read_dir {
+			while let Some(entry) = read_dir
+				.next_entry()
+				.await
+				.context("failed to read CAS directory entry")?
+			{
+				let name = entry.file_name();
+				// temporary files of in-progress writes
+				if name == ".tmp" {
+					continue;
+				}
+
+				archive
+					.append_dir_all(&name, entry.path())
+					.await
+					.with_context(|| format!("failed to archive {}", entry.path().display()))?;
+			}
+		}
+
+		let mut encoder = archive
+			.into_inner()
+			.await
+			.context("failed to finish archive")?;
+		encoder
+			.shutdown()
+			.await
+			.context("failed to finish archive")?;
+
+		println!(
+			"{}",
+			SUCCESS_STYLE.apply_to(format!("exported CAS to {}", self.output.display()))
+		);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,134 @@
+use crate::cli::style::SUCCESS_STYLE;
+use anyhow::Context as _;
+use async_compression::tokio::bufread::GzipDecoder;
+use clap::Args;
+use fs_err::tokio as fs;
+use futures::StreamExt as _;
+use pesde::Subproject;
+use pesde::hash::Hash;
+use pesde::hash::HashAlgorithm;
+use pesde::source::fs::PackageFs;
+use pesde::source::fs::cas_path;
+use pesde::source::fs::store_in_cas;
+use relative_path::Component;
+use relative_path::RelativePathBuf;
+use std::path::PathBuf;
+use tokio::io::AsyncReadExt as _;
+use tokio::io::BufReader;
+
+#[derive(Debug, Args)]
+pub struct ImportCommand {
+	/// The path of the archive created by `cas export`
+	#[arg(index = 1)]
+	input: PathBuf,
+}
+
+impl ImportCommand {
+	pub async fn run(self, subproject: Subproject) -> anyhow::Result<()> {
+		let cas_dir = subproject.project().cas_dir();
+
+		let file = fs::File::open(&self.input)
+			.await
+			.context("failed to open archive")?;
+		let archive = async_tar::Archive::new(GzipDecoder::new(BufReader::new(file)));
+		let mut entries = archive.entries().context("failed to read archive")?;
+
+		let mut imported = 0usize;
+		let mut skipped = 0usize;
+
+		while let Some(entry) = entries.next().await {
+			let mut entry = entry.context("failed to read archive entry")?;
+			if entry.header().entry_type().is_dir() {
+				continue;
+			}
+
+			let path = entry
+				.path()
+				.context("failed to read archive entry path")?
+				.to_str()
+				.and_then(|path| RelativePathBuf::from_path(path).ok())
+				.context("archive entry has an invalid path")?
+				.normalize();
+			let components = path
+				.components()
+				.map(|component| match component {
+					Component::Normal(name) => Ok(name),
+					_ => anyhow::bail!("archive entry {path} has an invalid path"),
+				})
+				.collect::<anyhow::Result<Vec<_>>>()?;
+
+			let mut contents = vec![];
+			entry
+				.read_to_end(&mut contents)
+				.await
+				.with_context(|| format!("failed to read archive entry {path}"))?;
+
+			if components.first() == Some(&"index") {
+				let PackageFs::Cached(entries) = serde_json::from_slice::<PackageFs>(&contents)
+					.with_context(|| format!("archive entry {path} is not a valid index file"))?
+				else {
+					anyhow::bail!("archive entry {path} is not a valid index file");
+				};
+				anyhow::ensure!(
+					entries.keys().all(|entry| entry
+						.components()
+						.all(|component| matches!(component, Component::Normal(_)))),
+					"index file {path} contains unsafe paths"
+				);
+
+				let dest = path.to_path(cas_dir);
+				if fs::metadata(&dest).await.is_ok() {
+					skipped += 1;
+					continue;
+				}
+
+				if let Some(parent) = dest.parent() {
+					fs::create_dir_all(parent)
+						.await
+						.context("failed to create index directory")?;
+				}
+				fs::write(&dest, &contents)
+					.await
+					.context("failed to write index file")?;
+				imported += 1;
+				continue;
+			}
+
+			let [algorithm, prefix, rest] = components[..] else {
+				anyhow::bail!("archive entry {path} is not a CAS file");
+			};
+			let algorithm = algorithm
+				.parse::<HashAlgorithm>()
+				.with_context(|| format!("archive entry {path} is not a CAS file"))?;
+			let expected = Hash::new(
+				algorithm,
+				hex::decode(format!("{prefix}{rest}"))
+					.with_context(|| format!("archive entry {path} is not a CAS file"))?,
+			);
+
+			if fs::metadata(cas_path(&expected, cas_dir)).await.is_ok() {
+				skipped += 1;
+				continue;
+			}
+
+			let hash = Hash::from_bytes(algorithm, &contents);
+			if hash != expected {
+				anyhow::bail!("archive entry {path} has hash {hash}, expected {expected}");
+			}
+
+			store_in_cas(cas_dir, contents.as_slice())
+				.await
+				.context("failed to store file in CAS")?;
+			imported += 1;
+		}
+
+		println!(
+			"{}",
+			SUCCESS_STYLE.apply_to(format!(
+				"imported {imported} files into the CAS, skipped {skipped} already present"
+			))
+		);
+
+		Ok(())
+	}
+}
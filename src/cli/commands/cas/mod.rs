@@ -1,6 +1,8 @@
 use clap::Subcommand;
 use pesde::Subproject;
 
+mod export;
+mod import;
 mod path;
 mod prune;
 
@@ -11,6 +13,12 @@ pub enum CasCommands {
 
 	/// Removes unused files from the CAS
 	Prune(prune::PruneCommand),
+
+	/// Bundles the CAS into an archive
+	Export(export::ExportCommand),
+
+	/// Merges an archive created by `cas export` into the CAS
+	Import(import::ImportCommand),
 }
 
 impl CasCommands {
@@ -21,6 +29,97 @@ impl CasCommands {
 				Ok(())
 			}
 			CasCommands::Prune(prune) => prune.run(subproject).await,
+			CasCommands::Export(export) => export.run(subproject).await,
+			CasCommands::Import(import) => import.run(subproject).await,
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use clap::Parser;
+	use fs_err::tokio as fs;
+	use pesde::AuthConfig;
+	use pesde::Importer;
+	use pesde::Project;
+	use pesde::source::fs::PackageFs;
+	use pesde::source::fs::cas_path;
+	use pesde::source::fs::store_in_cas;
+	use relative_path::RelativePathBuf;
+	use std::collections::BTreeMap;
+	use std::path::Path;
+
+	#[derive(Parser)]
+	struct Cli {
+		#[command(subcommand)]
+		cas: CasCommands,
+	}
+
+	async fn run(dir: &Path, args: &[&str]) {
+		let project = Project::new(
+			dir,
+			dir.join("data"),
+			dir.join("cas"),
+			AuthConfig::new(),
+			reqwest::Client::new(),
+		);
+
+		Cli::try_parse_from(std::iter::once("cas").chain(args.iter().copied()))
+			.unwrap()
+			.cas
+			.run(project.subproject(Importer::root()))
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	async fn export_import_round_trip() {
+		let source = tempfile::tempdir().unwrap();
+		let target = tempfile::tempdir().unwrap();
+		let archive = source.path().join("cas.tar.gz");
+
+		let cas_dir = source.path().join("cas");
+		let (_, hash) = store_in_cas(&cas_dir, b"print('hello')".as_slice())
+			.await
+			.unwrap();
+		let index_file = RelativePathBuf::from("index/pesde/hello/1.0.0");
+		let index = serde_json::to_vec(&PackageFs::Cached(BTreeMap::from([(
+			RelativePathBuf::from("init.luau"),
+			Some(hash.clone()),
+		)])))
+		.unwrap();
+		fs::create_dir_all(index_file.parent().unwrap().to_path(&cas_dir))
+			.await
+			.unwrap();
+		fs::write(index_file.to_path(&cas_dir), &index)
+			.await
+			.unwrap();
+		// in-progress writes aren't exported
+		fs::create_dir_all(cas_dir.join(".tmp")).await.unwrap();
+		fs::write(cas_dir.join(".tmp").join("partial"), "")
+			.await
+			.unwrap();
+
+		run(source.path(), &["export", archive.to_str().unwrap()]).await;
+		run(target.path(), &["import", archive.to_str().unwrap()]).await;
+
+		let target_cas = target.path().join("cas");
+		assert_eq!(
+			fs::read(cas_path(&hash, &target_cas)).await.unwrap(),
+			b"print('hello')"
+		);
+		assert_eq!(
+			fs::read(index_file.to_path(&target_cas)).await.unwrap(),
+			index
+		);
+		assert!(
+			fs::metadata(target_cas.join(".tmp").join("partial"))
+				.await
+				.is_err()
+		);
+
+		// importing again skips the files which are already present
+		run(target.path(), &["import", archive.to_str().unwrap()]).await;
+	}
+}
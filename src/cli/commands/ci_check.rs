@@ -0,0 +1,182 @@
+use crate::cli::commands::doctor::Checks;
+use crate::cli::commands::doctor::check_sources;
+use crate::cli::install::missing_peers;
+use crate::cli::style::INFO_STYLE;
+use anyhow::Context as _;
+use clap::Args;
+use pesde::RefreshedSources;
+use pesde::Subproject;
+use pesde::graph::DependencyGraph;
+use pesde::graph::GraphDiff;
+
+#[derive(Debug, Args)]
+pub struct CiCheckCommand;
+
+impl CiCheckCommand {
+	pub async fn run(self, subproject: Subproject) -> anyhow::Result<()> {
+		let mut checks = Checks { failed: 0 };
+
+		let manifest = subproject
+			.deser_manifest()
+			.await
+			.context("failed to read manifest")?;
+
+		println!("{}", INFO_STYLE.apply_to("sources"));
+		check_sources(&mut checks, &subproject, &manifest).await;
+
+		println!("{}", INFO_STYLE.apply_to("lockfile"));
+		let graph = match up_to_date_graph(&subproject).await {
+			Ok(graph) => {
+				checks.report("lockfile is up to date", Ok(()), String::new);
+				Some(graph)
+			}
+			Err(e) => {
+				checks.report("lockfile is up to date", Err(e), || {
+					format!(
+						"run `{} install` and commit the lockfile",
+						env!("CARGO_BIN_NAME")
+					)
+				});
+				None
+			}
+		};
+
+		println!("{}", INFO_STYLE.apply_to("peers"));
+		if let Some(graph) = graph {
+			for (importer, data) in &graph.importers {
+				let missing = missing_peers(&graph.nodes, &data.dependencies);
+				let result = if missing.is_empty() {
+					Ok(())
+				} else {
+					Err(anyhow::anyhow!("missing {}", missing.join(", ")))
+				};
+
+				checks.report(
+					&format!("peer dependencies of `{importer}` are satisfied"),
+					result,
+					|| "add the missing peer dependencies to the manifest".to_string(),
+				);
+			}
+		} else {
+			println!("  skipped, as the lockfile is out of date");
+		}
+
+		if checks.failed > 0 {
+			anyhow::bail!("{} checks failed", checks.failed);
+		}
+
+		Ok(())
+	}
+}
+
+/// Resolves the graph and checks it against the lockfile. Local dependencies are always
+/// resolved again, so the resolved graph is compared instead of relying on whether it was updated
+async fn up_to_date_graph(subproject: &Subproject) -> anyhow::Result<DependencyGraph> {
+	let project = subproject.project();
+
+	let old_graph = project
+		.graph_from_lockfile()
+		.await
+		.context("failed to read lockfile")?
+		.context("no lockfile found")?;
+
+	let (graph, _) = project
+		.dependency_graph(Some(&old_graph), &RefreshedSources::new(), false, &())
+		.await
+		.context("failed to build dependency graph")?;
+
+	let diff = graph.diff(&old_graph);
+	if diff != GraphDiff::default() || graph.overrides != old_graph.overrides {
+		let importers = diff
+			.importers
+			.keys()
+			.map(ToString::to_string)
+			.collect::<Vec<_>>();
+		anyhow::bail!(
+			"lockfile is out of sync ({} packages added, {} removed, changed dependencies in importers: {})",
+			diff.added.len(),
+			diff.removed.len(),
+			if importers.is_empty() {
+				"none".to_string()
+			} else {
+				importers.join(", ")
+			}
+		);
+	}
+
+	Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::local_index::LocalIndex;
+	use fs_err::tokio as fs;
+	use pesde::AuthConfig;
+	use pesde::Importer;
+	use pesde::MANIFEST_FILE_NAME;
+	use pesde::Project;
+
+	#[tokio::test]
+	async fn checks_sources_and_lockfile() {
+		let dir = tempfile::tempdir().unwrap();
+		let index = LocalIndex::new(&dir.path().join("index"));
+		let project_dir = dir.path().join("project");
+		fs::create_dir_all(&project_dir).await.unwrap();
+		for package in ["foo", "bar"] {
+			fs::create_dir_all(dir.path().join(package)).await.unwrap();
+			fs::write(dir.path().join(package).join(MANIFEST_FILE_NAME), "")
+				.await
+				.unwrap();
+		}
+
+		let project = Project::new(
+			&project_dir,
+			dir.path().join("data"),
+			dir.path().join("cas"),
+			AuthConfig::new(),
+			reqwest::Client::new(),
+		);
+		let subproject = project.clone().subproject(Importer::root());
+		let write_manifest = async |indices: &str, dependencies: &str| {
+			fs::write(
+				project_dir.join(MANIFEST_FILE_NAME),
+				format!("[indices]\n{indices}\n[dependencies]\n{dependencies}"),
+			)
+			.await
+			.unwrap();
+			project.clear_manifest_cache().await;
+		};
+		let default_index = format!("default = \"{}\"\n", index.url());
+		let foo = "foo = { path = \"../foo\" }\n";
+
+		write_manifest(&default_index, foo).await;
+		// no lockfile
+		assert!(CiCheckCommand.run(subproject.clone()).await.is_err());
+
+		project
+			.install(pesde::download_and_link::InstallOptions::<()>::new())
+			.await
+			.unwrap();
+		CiCheckCommand.run(subproject.clone()).await.unwrap();
+
+		// an unreachable index
+		let missing_index = dir.path().join("missing").display().to_string();
+		write_manifest(
+			&format!("{default_index}missing = \"file://{missing_index}\"\n"),
+			foo,
+		)
+		.await;
+		assert!(CiCheckCommand.run(subproject.clone()).await.is_err());
+
+		// a dependency missing from the lockfile
+		write_manifest(
+			&default_index,
+			&format!("{foo}bar = {{ path = \"../bar\" }}\n"),
+		)
+		.await;
+		let err = up_to_date_graph(&subproject).await.unwrap_err();
+		assert!(err.to_string().contains("out of sync"), "{err}");
+		assert!(CiCheckCommand.run(subproject).await.is_err());
+	}
+}
@@ -0,0 +1,158 @@
+use crate::cli::config::CliConfig;
+use crate::cli::config::read_config;
+use crate::cli::config::write_config;
+use clap::Args;
+use clap::Subcommand;
+use pesde::GixUrl;
+
+#[derive(Debug, Subcommand)]
+pub enum IndicesCommands {
+	/// Lists the named indices
+	List,
+
+	/// Adds a named index, replacing any index with the same name
+	Add(AddCommand),
+
+	/// Removes a named index
+	Remove(RemoveCommand),
+}
+
+#[derive(Debug, Args)]
+pub struct AddCommand {
+	/// The name of the index
+	#[arg(index = 1)]
+	name: String,
+
+	/// The URL of the index
+	#[arg(index = 2)]
+	url: GixUrl,
+}
+
+#[derive(Debug, Args)]
+pub struct RemoveCommand {
+	/// The name of the index
+	#[arg(index = 1)]
+	name: String,
+}
+
+impl IndicesCommands {
+	pub async fn run(self) -> anyhow::Result<()> {
+		let mut config = read_config().await?;
+
+		if self.apply(&mut config)? {
+			write_config(&config).await?;
+		}
+
+		Ok(())
+	}
+
+	/// Applies the command to the config, returning whether it was changed
+	fn apply(self, config: &mut CliConfig) -> anyhow::Result<bool> {
+		match self {
+			IndicesCommands::List => {
+				if config.indices.is_empty() {
+					println!("no named indices");
+				}
+
+				for (name, url) in &config.indices {
+					println!("{name}: {url}");
+				}
+
+				Ok(false)
+			}
+			IndicesCommands::Add(AddCommand { name, url }) => {
+				println!("index {name} set to: {url}");
+				config.indices.insert(name, url);
+
+				Ok(true)
+			}
+			IndicesCommands::Remove(RemoveCommand { name }) => {
+				if config.indices.remove(&name).is_none() {
+					anyhow::bail!("index {name} not found");
+				}
+
+				println!("index {name} removed");
+
+				Ok(true)
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::cli::lookup_index;
+	use std::collections::BTreeMap;
+
+	fn add(name: &str, url: &str) -> IndicesCommands {
+		IndicesCommands::Add(AddCommand {
+			name: name.to_string(),
+			url: url.parse().unwrap(),
+		})
+	}
+
+	fn remove(name: &str) -> IndicesCommands {
+		IndicesCommands::Remove(RemoveCommand {
+			name: name.to_string(),
+		})
+	}
+
+	#[test]
+	fn add_and_remove() {
+		let mut config = CliConfig::default();
+		let acme = "https://github.com/acme/index".parse::<GixUrl>().unwrap();
+
+		assert!(
+			add("acme", "https://github.com/acme/old")
+				.apply(&mut config)
+				.unwrap()
+		);
+		// adding an existing name replaces its URL
+		assert!(add("acme", &acme.to_string()).apply(&mut config).unwrap());
+		assert_eq!(config.indices, BTreeMap::from([("acme".to_string(), acme)]));
+		assert!(!IndicesCommands::List.apply(&mut config).unwrap());
+
+		assert!(remove("acme").apply(&mut config).unwrap());
+		assert!(config.indices.is_empty());
+		assert!(remove("acme").apply(&mut config).is_err());
+	}
+
+	#[test]
+	fn named_index_lookup() {
+		let mut config = CliConfig::default();
+		add("acme", "https://github.com/acme/index")
+			.apply(&mut config)
+			.unwrap();
+		add("shared", "https://github.com/acme/config")
+			.apply(&mut config)
+			.unwrap();
+		let manifest_indices = BTreeMap::from([(
+			"shared".to_string(),
+			"https://github.com/acme/manifest".parse().unwrap(),
+		)]);
+
+		let lookup = |manifest_indices, index| {
+			lookup_index(manifest_indices, &config, index)
+				.unwrap()
+				.to_string()
+		};
+
+		assert_eq!(lookup(None, "acme"), "https://github.com/acme/index");
+		assert_eq!(
+			lookup(Some(&manifest_indices), "acme"),
+			"https://github.com/acme/index"
+		);
+		// the manifest's indices take precedence over the config's
+		assert_eq!(
+			lookup(Some(&manifest_indices), "shared"),
+			"https://github.com/acme/manifest"
+		);
+		assert_eq!(lookup(None, "shared"), "https://github.com/acme/config");
+		// anything else is used as a URL
+		assert_eq!(
+			lookup(None, "https://github.com/acme/other"),
+			"https://github.com/acme/other"
+		);
+	}
+}
@@ -1,17 +1,23 @@
 use clap::Subcommand;
 
 mod default_index;
+mod indices;
 
 #[derive(Debug, Subcommand)]
 pub enum ConfigCommands {
 	/// Configuration for the default index
 	DefaultIndex(default_index::DefaultIndexCommand),
+
+	/// Named indices, which can be used instead of URLs
+	#[command(subcommand)]
+	Indices(indices::IndicesCommands),
 }
 
 impl ConfigCommands {
 	pub async fn run(self) -> anyhow::Result<()> {
 		match self {
 			ConfigCommands::DefaultIndex(default_index) => default_index.run().await,
+			ConfigCommands::Indices(indices) => indices.run().await,
 		}
 	}
 }
@@ -0,0 +1,247 @@
+use crate::cli::config::read_config;
+use crate::cli::config_path;
+use crate::cli::style::ERROR_STYLE;
+use crate::cli::style::INFO_STYLE;
+use crate::cli::style::SUCCESS_STYLE;
+use anyhow::Context as _;
+use clap::Args;
+use fs_err::tokio as fs;
+use pesde::MANIFEST_FILE_NAME;
+use pesde::RefreshedSources;
+use pesde::Subproject;
+use pesde::errors::ManifestReadErrorKind;
+use pesde::manifest::Manifest;
+use pesde::source::PackageSources;
+#[expect(deprecated)]
+use pesde::source::pesde::PesdePackageSource;
+use pesde::source::wally::WallyPackageSource;
+use tempfile::NamedTempFile;
+use tokio::task::spawn_blocking;
+
+#[derive(Debug, Args)]
+pub struct DoctorCommand;
+
+pub(super) struct Checks {
+	pub(super) failed: usize,
+}
+
+impl Checks {
+	pub(super) fn report(
+		&mut self,
+		name: &str,
+		result: anyhow::Result<()>,
+		hint: impl FnOnce() -> String,
+	) {
+		let err = match result {
+			Ok(()) => {
+				println!("{} {name}", SUCCESS_STYLE.apply_to("✓"));
+				return;
+			}
+			Err(err) => err,
+		};
+
+		self.failed += 1;
+
+		println!("{} {name}: {err:#}", ERROR_STYLE.apply_to("✗"));
+		println!("  {}: {}", INFO_STYLE.apply_to("hint"), hint());
+	}
+}
+
+impl DoctorCommand {
+	pub async fn run(self, subproject: Subproject) -> anyhow::Result<()> {
+		let mut checks = Checks { failed: 0 };
+
+		checks.report(
+			"config is valid",
+			read_config().await.map(|_| ()),
+			|| match config_path() {
+				Ok(path) => format!("fix or remove the config file at {}", path.display()),
+				Err(_) => "make sure your home directory can be found".to_string(),
+			},
+		);
+
+		check_project(&mut checks, &subproject).await;
+
+		if checks.failed > 0 {
+			anyhow::bail!("{} checks failed", checks.failed);
+		}
+
+		Ok(())
+	}
+}
+
+/// Checks the CAS directory, the manifest, the lockfile and the indices of the project
+async fn check_project(checks: &mut Checks, subproject: &Subproject) {
+	let project = subproject.project();
+
+	let cas_dir = project.cas_dir().to_path_buf();
+	checks.report(
+		"CAS directory is writable",
+		async {
+			let tmp_dir = cas_dir.join(".tmp");
+			fs::create_dir_all(&tmp_dir)
+				.await
+				.context("failed to create CAS directory")?;
+
+			spawn_blocking(move || NamedTempFile::new_in(tmp_dir))
+				.await
+				.unwrap()
+				.context("failed to create file in CAS directory")?;
+
+			Ok(())
+		}
+		.await,
+		|| {
+			format!(
+				"make sure you have permission to write to {}",
+				cas_dir.display()
+			)
+		},
+	);
+
+	let manifest = match subproject.deser_manifest().await {
+		Ok(manifest) => {
+			checks.report("manifest is valid", Ok(()), String::new);
+			Some(manifest)
+		}
+		Err(e) => {
+			let not_found = matches!(
+				e.inner(),
+				ManifestReadErrorKind::Io(e) if e.kind() == std::io::ErrorKind::NotFound
+			);
+
+			checks.report("manifest is valid", Err(e.into()), || {
+				if not_found {
+					format!("run `{} init` to create a manifest", env!("CARGO_BIN_NAME"))
+				} else {
+					format!("fix the errors in {MANIFEST_FILE_NAME}")
+				}
+			});
+			None
+		}
+	};
+
+	checks.report(
+		"lockfile is valid",
+		project
+			.graph_from_lockfile()
+			.await
+			.map(|_| ())
+			.map_err(Into::into),
+		|| {
+			format!(
+				"run `{} update` to regenerate the lockfile",
+				env!("CARGO_BIN_NAME")
+			)
+		},
+	);
+
+	if let Some(manifest) = manifest {
+		check_sources(checks, subproject, &manifest).await;
+	}
+}
+
+/// Checks that every index of the manifest can be refreshed
+pub(super) async fn check_sources(
+	checks: &mut Checks,
+	subproject: &Subproject,
+	manifest: &Manifest,
+) {
+	let refreshed_sources = RefreshedSources::new();
+
+	#[expect(deprecated)]
+	let sources = manifest
+		.indices
+		.pesde
+		.iter()
+		.map(|(name, url)| {
+			(
+				name,
+				url,
+				PackageSources::Pesde(PesdePackageSource::from_url(url.clone())),
+			)
+		})
+		.chain(manifest.indices.wally.iter().map(|(name, url)| {
+			(
+				name,
+				url,
+				PackageSources::Wally(WallyPackageSource::from_url(url.clone())),
+			)
+		}))
+		.collect::<Vec<_>>();
+
+	for (name, url, source) in sources {
+		checks.report(
+			&format!("index `{name}` ({url}) is reachable"),
+			refreshed_sources
+				.refresh(&source, subproject.project())
+				.await
+				.map_err(Into::into),
+			|| "check your network connection and that the index URL is correct".to_string(),
+		);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pesde::AuthConfig;
+	use pesde::Importer;
+	use pesde::LOCKFILE_FILE_NAME;
+	use pesde::Project;
+	use std::path::Path;
+
+	async fn failed_checks(dir: &Path, cas_dir: &Path) -> usize {
+		let project = Project::new(
+			dir.join("project"),
+			dir.join("data"),
+			cas_dir,
+			AuthConfig::new(),
+			reqwest::Client::new(),
+		);
+		let mut checks = Checks { failed: 0 };
+		check_project(&mut checks, &project.subproject(Importer::root())).await;
+
+		checks.failed
+	}
+
+	#[tokio::test]
+	async fn reports_each_failed_check() {
+		let dir = tempfile::tempdir().unwrap();
+		let project_dir = dir.path().join("project");
+		let cas_dir = dir.path().join("cas");
+		fs::create_dir_all(&project_dir).await.unwrap();
+
+		// no manifest
+		assert_eq!(failed_checks(dir.path(), &cas_dir).await, 1);
+
+		fs::write(project_dir.join(MANIFEST_FILE_NAME), "")
+			.await
+			.unwrap();
+		assert_eq!(failed_checks(dir.path(), &cas_dir).await, 0);
+
+		fs::write(
+			project_dir.join(MANIFEST_FILE_NAME),
+			format!(
+				"[indices]\nmissing = \"file://{}\"\n",
+				dir.path().join("missing").display()
+			),
+		)
+		.await
+		.unwrap();
+		assert_eq!(failed_checks(dir.path(), &cas_dir).await, 1);
+
+		fs::write(project_dir.join(MANIFEST_FILE_NAME), "dependencies = 1")
+			.await
+			.unwrap();
+		fs::write(project_dir.join(LOCKFILE_FILE_NAME), "not a lockfile")
+			.await
+			.unwrap();
+		assert_eq!(failed_checks(dir.path(), &cas_dir).await, 2);
+
+		// the CAS directory can't be created where a file is
+		let cas_file = dir.path().join("cas_file");
+		fs::write(&cas_file, "").await.unwrap();
+		assert_eq!(failed_checks(dir.path(), &cas_file).await, 3);
+	}
+}
@@ -1,24 +1,33 @@
 use crate::cli::AnyPackageIdentifier;
 use crate::cli::config::read_config;
+use crate::cli::dep_type_to_key;
 use crate::cli::reporters;
 use crate::cli::reporters::CliReporter;
+use crate::cli::with_manifest_dependencies_dir;
 use anyhow::Context as _;
 use clap::Args;
 use console::style;
+use fs_err::tokio as fs;
 use indicatif::MultiProgress;
 use pesde::Importer;
+use pesde::MANIFEST_FILE_NAME;
 use pesde::Project;
 use pesde::RefreshedSources;
 use pesde::Subproject;
 use pesde::download_and_link::DownloadAndLinkOptions;
 use pesde::download_and_link::InstallDependenciesMode;
+use pesde::find_roots;
+use pesde::manifest::DependencyType;
 use pesde::scripts::execute_script;
+use pesde::source::PackageRefs;
 use pesde::source::PackageSource as _;
 use pesde::source::ResolveResult;
 use pesde::source::ResolvedPackage;
 use pesde::source::ids::PackageId;
 use std::ffi::OsString;
 use std::io::Stderr;
+use std::path::Path;
+use std::str::FromStr as _;
 use tempfile::TempDir;
 use tokio::task::spawn_blocking;
 
@@ -56,7 +65,7 @@ impl ExecuteCommand {
 				let (source, specifier) = self
 					.package
 					.source_and_specifier(None, async |_| {
-						let index = read_config().await?.default_index;
+						let index = read_config().await?.resolved_default_index()?;
 						Ok((index.to_string(), index))
 					})
 					.await
@@ -124,6 +133,15 @@ impl ExecuteCommand {
 					.await
 					.context("failed to write package contents")?;
 
+				if let PackageRefs::Path(pkg_ref) = package.id.pkg_ref() {
+					let package_dir = subproject.project().dir().join(&pkg_ref.path);
+					let (workspace_dir, _) = find_roots(package_dir.clone())
+						.await
+						.context("failed to find the workspace of the package")?;
+					absolutize_path_dependencies(tempdir.path(), &package_dir, &workspace_dir)
+						.await?;
+				}
+
 				let exports = package
 					.id
 					.source()
@@ -131,16 +149,26 @@ impl ExecuteCommand {
 					.await
 					.context("failed to get package exports")?;
 
-				let project = Project::new(
+				anyhow::ensure!(
+					exports.bin_platforms.is_current_supported(),
+					"package's binary does not support the current platform ({} {}), supported platforms are {}",
+					std::env::consts::OS,
+					std::env::consts::ARCH,
+					exports.bin_platforms
+				);
+
+				// the bin requires its dependencies from where the package's manifest says they're installed
+				let project = with_manifest_dependencies_dir(Project::new(
 					tempdir.path(),
 					subproject.project().data_dir(),
 					subproject.project().cas_dir(),
 					subproject.project().auth_config().clone(),
 					subproject.project().reqwest().clone(),
-				);
+				))
+				.await?;
 
 				let graph = project
-					.dependency_graph(None, &refreshed_sources, true)
+					.dependency_graph(None, &refreshed_sources, true, &())
 					.await
 					.context("failed to build dependency graph")?
 					.0;
@@ -182,3 +210,134 @@ impl ExecuteCommand {
 		std::process::exit(code);
 	}
 }
+
+/// Makes the relative path dependencies of a local package copied to `dir` point to the same
+/// directories as they do from the package's original directory, or for those relative to the
+/// workspace root, from `workspace_dir`
+async fn absolutize_path_dependencies(
+	dir: &Path,
+	package_dir: &Path,
+	workspace_dir: &Path,
+) -> anyhow::Result<()> {
+	let manifest_path = dir.join(MANIFEST_FILE_NAME);
+	let mut manifest = toml_edit::DocumentMut::from_str(
+		&fs::read_to_string(&manifest_path)
+			.await
+			.context("failed to read manifest")?,
+	)
+	.context("failed to parse manifest")?;
+
+	for ty in [
+		DependencyType::Standard,
+		DependencyType::Peer,
+		DependencyType::Dev,
+	] {
+		let Some(dependencies) = manifest
+			.get_mut(dep_type_to_key(ty))
+			.and_then(|dependencies| dependencies.as_table_like_mut())
+		else {
+			continue;
+		};
+
+		for (_, dependency) in dependencies.iter_mut() {
+			let Some(dependency) = dependency.as_table_like_mut() else {
+				continue;
+			};
+
+			let Some(path) = dependency.get("path").and_then(|path| path.as_str()) else {
+				continue;
+			};
+			if Path::new(path).is_absolute() {
+				continue;
+			}
+
+			let base = if dependency
+				.get("workspace")
+				.and_then(toml_edit::Item::as_bool)
+				.unwrap_or(false)
+			{
+				workspace_dir
+			} else {
+				package_dir
+			};
+			let path = base.join(path);
+			let path = path
+				.to_str()
+				.with_context(|| format!("path {} is not valid UTF-8", path.display()))?;
+			dependency.insert("path", toml_edit::value(path));
+		}
+	}
+
+	// the copied manifest is a symlink to the original, which must not be changed
+	fs::remove_file(&manifest_path)
+		.await
+		.context("failed to remove copied manifest")?;
+	fs::write(&manifest_path, manifest.to_string())
+		.await
+		.context("failed to write manifest")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::path::PathBuf;
+
+	#[tokio::test]
+	async fn absolutize_path_dependencies_keeps_targets() {
+		let dir = tempfile::tempdir().unwrap();
+		let workspace_dir = dir.path().join("workspace");
+		let package_dir = workspace_dir.join("package");
+		let copy_dir = dir.path().join("copy");
+		fs::create_dir_all(&package_dir).await.unwrap();
+		fs::create_dir_all(&copy_dir).await.unwrap();
+
+		let absolute = dir.path().join("absolute");
+		fs::write(
+			copy_dir.join(MANIFEST_FILE_NAME),
+			format!(
+				r#"[dependencies]
+foo = {{ path = "../foo" }}
+bar = {{ path = {absolute:?} }}
+baz = {{ workspace = true, path = "baz" }}
+
+[peer_dependencies]
+qux = {{ path = "qux" }}
+
+[dev_dependencies]
+git = {{ repo = "https://github.com/pesde-pkg/pesde", rev = "main" }}
+"#
+			),
+		)
+		.await
+		.unwrap();
+
+		absolutize_path_dependencies(&copy_dir, &package_dir, &workspace_dir)
+			.await
+			.unwrap();
+
+		let manifest = toml::from_str::<toml::Table>(
+			&fs::read_to_string(copy_dir.join(MANIFEST_FILE_NAME))
+				.await
+				.unwrap(),
+		)
+		.unwrap();
+		let path = |ty: &str, alias: &str| {
+			manifest[ty][alias]
+				.get("path")
+				.and_then(toml::Value::as_str)
+				.map(PathBuf::from)
+		};
+
+		assert_eq!(
+			path("dependencies", "foo"),
+			Some(package_dir.join("../foo"))
+		);
+		assert_eq!(path("dependencies", "bar"), Some(absolute));
+		assert_eq!(path("dependencies", "baz"), Some(workspace_dir.join("baz")));
+		assert_eq!(
+			path("peer_dependencies", "qux"),
+			Some(package_dir.join("qux"))
+		);
+		assert_eq!(path("dev_dependencies", "git"), None);
+	}
+}
@@ -0,0 +1,62 @@
+use crate::cli::install::missing_peers;
+use crate::cli::style::SUCCESS_STYLE;
+use crate::cli::style::WARN_PREFIX;
+use anyhow::Context as _;
+use clap::Args;
+use console::style;
+use pesde::Subproject;
+
+#[derive(Debug, Args)]
+pub struct CheckPeersCommand {
+	/// Whether to exit with an error if any peer dependency is unsatisfied
+	#[arg(long)]
+	strict: bool,
+}
+
+impl CheckPeersCommand {
+	pub async fn run(self, subproject: Subproject) -> anyhow::Result<()> {
+		let graph = subproject
+			.project()
+			.graph_from_lockfile()
+			.await
+			.context("failed to read lockfile")?
+			.with_context(|| {
+				format!(
+					"no lockfile found, run `{} install` first",
+					env!("CARGO_BIN_NAME")
+				)
+			})?;
+
+		let mut missing_count = 0;
+
+		for (importer, data) in &graph.importers {
+			let missing = missing_peers(&graph.nodes, &data.dependencies);
+			if missing.is_empty() {
+				continue;
+			}
+
+			println!("{}", style(importer).bold());
+			for path in &missing {
+				println!("  {}", style(format!("missing peer {path}")).red());
+			}
+
+			missing_count += missing.len();
+		}
+
+		if missing_count == 0 {
+			println!(
+				"{}",
+				SUCCESS_STYLE.apply_to("all peer dependencies are satisfied")
+			);
+			return Ok(());
+		}
+
+		if self.strict {
+			anyhow::bail!("{missing_count} peer dependencies are unsatisfied");
+		}
+
+		println!("{WARN_PREFIX}: {missing_count} peer dependencies are unsatisfied");
+
+		Ok(())
+	}
+}
@@ -0,0 +1,23 @@
+use clap::Subcommand;
+use pesde::Subproject;
+
+mod check_peers;
+mod why;
+
+#[derive(Debug, Subcommand)]
+pub enum GraphCommands {
+	/// Checks that all peer dependencies in the lockfile are satisfied
+	CheckPeers(check_peers::CheckPeersCommand),
+
+	/// Prints the paths through which a package is depended on
+	Why(why::WhyCommand),
+}
+
+impl GraphCommands {
+	pub async fn run(self, subproject: Subproject) -> anyhow::Result<()> {
+		match self {
+			GraphCommands::CheckPeers(check_peers) => check_peers.run(subproject).await,
+			GraphCommands::Why(why) => why.run(subproject).await,
+		}
+	}
+}
@@ -0,0 +1,97 @@
+use crate::cli::style::SUCCESS_STYLE;
+use anyhow::Context as _;
+use clap::Args;
+use console::style;
+use itertools::Itertools as _;
+use pesde::Subproject;
+use pesde::source::ids::PackageId;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Args)]
+pub struct WhyCommand {
+	/// The package to explain, either its full ID or its reference, such as `scope/name` or `path:dir`
+	#[arg(index = 1)]
+	package: String,
+
+	/// Whether to print the paths as JSON
+	#[arg(long)]
+	json: bool,
+}
+
+impl WhyCommand {
+	pub async fn run(self, subproject: Subproject) -> anyhow::Result<()> {
+		let graph = subproject
+			.project()
+			.graph_from_lockfile()
+			.await
+			.context("failed to read lockfile")?
+			.with_context(|| {
+				format!(
+					"no lockfile found, run `{} install` first",
+					env!("CARGO_BIN_NAME")
+				)
+			})?;
+
+		let matches = |id: &PackageId| {
+			let pkg_ref = id.pkg_ref().to_string();
+
+			id.to_string() == self.package
+				|| pkg_ref == self.package
+				|| pkg_ref
+					.split_once(':')
+					.is_some_and(|(_, pkg_ref)| pkg_ref == self.package)
+		};
+
+		anyhow::ensure!(
+			graph.nodes.keys().any(matches),
+			"package `{}` not found in the lockfile",
+			self.package
+		);
+
+		let paths = graph.paths_to(matches);
+
+		if self.json {
+			let paths = paths
+				.iter()
+				.map(|path| {
+					serde_json::json!({
+						"importer": path.importer,
+						"path": path.packages.iter().map(|(_, id)| id.to_string()).collect::<Vec<_>>(),
+					})
+				})
+				.collect::<Vec<_>>();
+
+			println!(
+				"{}",
+				serde_json::to_string_pretty(&paths).context("failed to serialize paths")?
+			);
+
+			return Ok(());
+		}
+
+		let by_importer = paths
+			.iter()
+			.fold(BTreeMap::<_, Vec<_>>::new(), |mut acc, path| {
+				acc.entry(&path.importer).or_default().push(&path.packages);
+				acc
+			});
+
+		for (importer, paths) in by_importer {
+			println!("{}", style(importer).bold());
+
+			for packages in paths {
+				let (_, id) = packages.last().unwrap();
+
+				println!(
+					"  {} {}",
+					SUCCESS_STYLE.apply_to(packages.iter().map(|(alias, _)| alias).format(">")),
+					style(id).dim()
+				);
+			}
+
+			println!();
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,248 @@
+#![expect(deprecated)]
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use crate::cli::get_index;
+use crate::cli::style::INFO_STYLE;
+use crate::cli::style::SUCCESS_STYLE;
+use crate::cli::style::URL_STYLE;
+use crate::cli::style::WARN_STYLE;
+use anyhow::Context as _;
+use clap::Args;
+use pesde::GixUrl;
+use pesde::Project;
+use pesde::RefreshedSources;
+use pesde::Subproject;
+use pesde::names::PackageName;
+use pesde::source::PackageSources;
+use pesde::source::pesde::PesdePackageSource;
+use pesde::source::pesde::backend::IndexFile;
+use pesde::source::pesde::backend::IndexFileEntry;
+use pesde::source::pesde::backend::PesdePackageSourceBackend as _;
+use pesde::source::pesde::target::TargetKind;
+use semver::Version;
+use serde::Serialize;
+
+#[derive(Debug, Args)]
+pub struct InfoCommand {
+	/// The package to show information about
+	#[arg(index = 1)]
+	package: PackageName,
+
+	/// The index to look the package up in
+	#[arg(short, long)]
+	index: Option<String>,
+
+	/// Print the information as JSON
+	#[arg(long)]
+	json: bool,
+}
+
+/// The latest available version of a package for a target
+#[derive(Debug, Serialize)]
+struct TargetInfo<'a> {
+	version: &'a Version,
+	published_at: jiff::Timestamp,
+}
+
+#[derive(Debug, Serialize)]
+struct PackageInfo<'a> {
+	name: &'a PackageName,
+	description: Option<&'a str>,
+	license: Option<&'a str>,
+	authors: &'a [String],
+	repository: Option<&'a url::Url>,
+	deprecated: Option<&'a str>,
+	targets: BTreeMap<TargetKind, TargetInfo<'a>>,
+}
+
+impl<'a> PackageInfo<'a> {
+	/// Collects the information about a package from its index file. The package-wide fields are
+	/// taken from the latest version across all targets
+	fn new(name: &'a PackageName, file: &'a IndexFile) -> anyhow::Result<Self> {
+		// stable versions are preferred over newer pre-releases, like the resolver does
+		let key = |version: &Version| (version.pre.is_empty(), version.clone());
+
+		let mut latest = BTreeMap::<TargetKind, (&Version, &IndexFileEntry)>::new();
+		for (id, entry) in file.entries.iter().filter(|(_, entry)| !entry.yanked) {
+			let current = latest.entry(id.target()).or_insert((id.version(), entry));
+			if key(current.0) < key(id.version()) {
+				*current = (id.version(), entry);
+			}
+		}
+
+		let (_, entry) = latest
+			.values()
+			.max_by_key(|(version, _)| key(version))
+			.copied()
+			.with_context(|| format!("package {name} has no available versions"))?;
+
+		Ok(Self {
+			name,
+			description: entry.description.as_deref(),
+			license: entry.license.as_deref(),
+			authors: &entry.authors,
+			repository: entry.repository.as_ref(),
+			deprecated: Some(file.meta.deprecated.as_str()).filter(|reason| !reason.is_empty()),
+			targets: latest
+				.into_iter()
+				.map(|(target, (version, entry))| {
+					(
+						target,
+						TargetInfo {
+							version,
+							published_at: entry.published_at,
+						},
+					)
+				})
+				.collect(),
+		})
+	}
+
+	fn render(&self) -> String {
+		let mut out = String::new();
+
+		_ = writeln!(out, "{}", SUCCESS_STYLE.apply_to(self.name));
+		if let Some(description) = self.description {
+			_ = writeln!(out, "{description}");
+		}
+		if let Some(reason) = self.deprecated {
+			_ = writeln!(out, "{}: {reason}", WARN_STYLE.apply_to("deprecated"));
+		}
+		_ = writeln!(out);
+
+		if let Some(license) = self.license {
+			_ = writeln!(out, "{}: {license}", INFO_STYLE.apply_to("license"));
+		}
+		if !self.authors.is_empty() {
+			_ = writeln!(
+				out,
+				"{}: {}",
+				INFO_STYLE.apply_to("authors"),
+				self.authors.join(", ")
+			);
+		}
+		if let Some(repository) = self.repository {
+			_ = writeln!(
+				out,
+				"{}: {}",
+				INFO_STYLE.apply_to("repository"),
+				URL_STYLE.apply_to(repository)
+			);
+		}
+
+		_ = writeln!(out, "{}", INFO_STYLE.apply_to("targets"));
+		for (target, info) in &self.targets {
+			_ = writeln!(
+				out,
+				"{target}: {} (published {})",
+				info.version,
+				info.published_at.strftime("%Y-%m-%d")
+			);
+		}
+
+		out
+	}
+}
+
+async fn read_index_file(
+	project: &Project,
+	index: GixUrl,
+	name: &PackageName,
+) -> anyhow::Result<IndexFile> {
+	let source = PesdePackageSource::from_url(index);
+	RefreshedSources::new()
+		.refresh(&PackageSources::Pesde(source.clone()), project)
+		.await
+		.context("failed to refresh source")?;
+
+	source
+		.repo()
+		.read_index_file(project, name.clone())
+		.await
+		.context("failed to read package index file")?
+		.with_context(|| format!("package {name} not found in index"))
+}
+
+impl InfoCommand {
+	pub async fn run(self, subproject: Subproject) -> anyhow::Result<()> {
+		let index = get_index(&subproject, self.index.as_deref()).await?;
+		let file = read_index_file(subproject.project(), index, &self.package).await?;
+		let info = PackageInfo::new(&self.package, &file)?;
+
+		if self.json {
+			println!(
+				"{}",
+				serde_json::to_string_pretty(&info).context("failed to serialize package info")?
+			);
+		} else {
+			print!("{}", info.render());
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const INDEX_FILE: &str = r#"{
+		"meta": { "deprecated": "use acme/bar instead" },
+		"1.0.0 luau": {
+			"published_at": "2024-01-01T00:00:00Z",
+			"target": { "environment": "luau" },
+			"description": "An old description",
+			"license": "MIT"
+		},
+		"1.2.0 luau": {
+			"published_at": "2024-03-01T00:00:00Z",
+			"target": { "environment": "luau" },
+			"description": "A package",
+			"license": "MIT OR Apache-2.0",
+			"authors": ["alice", "bob"],
+			"repository": "https://github.com/acme/foo"
+		},
+		"1.3.0 luau": {
+			"published_at": "2024-04-01T00:00:00Z",
+			"target": { "environment": "luau" },
+			"yanked": true
+		},
+		"2.0.0-rc.1 luau": {
+			"published_at": "2024-05-01T00:00:00Z",
+			"target": { "environment": "luau" }
+		},
+		"1.1.0 lune": {
+			"published_at": "2024-02-01T00:00:00Z",
+			"target": { "environment": "lune" }
+		}
+	}"#;
+
+	#[test]
+	fn renders_package_info() {
+		let name = "acme/foo".parse().unwrap();
+		let file = serde_json::from_str::<IndexFile>(INDEX_FILE).unwrap();
+		let package_info = PackageInfo::new(&name, &file).unwrap();
+
+		assert_eq!(
+			console::strip_ansi_codes(&package_info.render()),
+			"acme/foo\n\
+			A package\n\
+			deprecated: use acme/bar instead\n\
+			\n\
+			license: MIT OR Apache-2.0\n\
+			authors: alice, bob\n\
+			repository: https://github.com/acme/foo\n\
+			targets\n\
+			lune: 1.1.0 (published 2024-02-01)\n\
+			luau: 1.2.0 (published 2024-03-01)\n"
+		);
+
+		let json = serde_json::to_value(&package_info).unwrap();
+		assert_eq!(json["deprecated"], "use acme/bar instead");
+		assert_eq!(json["authors"], serde_json::json!(["alice", "bob"]));
+		assert_eq!(json["targets"]["luau"]["version"], "1.2.0");
+		assert_eq!(json["targets"]["lune"]["version"], "1.1.0");
+	}
+}
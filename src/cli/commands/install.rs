@@ -1,12 +1,20 @@
 use crate::cli::install::InstallOptions;
 use crate::cli::install::install;
+use crate::cli::reporters::ReporterKind;
+use anyhow::Context as _;
 use clap::Args;
 use pesde::Subproject;
 use pesde::download_and_link::InstallDependenciesMode;
+use pesde::manifest::Alias;
 use std::num::NonZeroUsize;
+use std::path::PathBuf;
 
-#[derive(Debug, Args, Copy, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct InstallCommand {
+	/// The direct dependencies to install, leaving the others untouched. Installs all of them if none are given
+	#[arg(index = 1)]
+	aliases: Vec<Alias>,
+
 	/// Whether to error on changes in the lockfile
 	#[arg(long)]
 	locked: bool,
@@ -26,6 +34,26 @@ pub struct InstallCommand {
 	/// Whether to re-install all dependencies even if they are already installed
 	#[arg(long)]
 	force: bool,
+
+	/// Whether to use the local copies of indices without refreshing them, only fetching indices which aren't available locally
+	#[arg(long)]
+	prefer_offline: bool,
+
+	/// Whether prerelease versions may satisfy version requirements which don't mention a prerelease
+	#[arg(long)]
+	include_prerelease: bool,
+
+	/// How to report progress
+	#[arg(long, value_enum, default_value_t)]
+	reporter: ReporterKind,
+
+	/// The directory to install the dependencies under instead of the project, leaving the project and its lockfile untouched
+	#[arg(long)]
+	prefix: Option<PathBuf>,
+
+	/// The file to write a JSON report of the install to, with the packages added and removed, how long each stage took and the lockfile's hash
+	#[arg(long)]
+	report: Option<PathBuf>,
 }
 
 impl InstallCommand {
@@ -44,9 +72,27 @@ impl InstallCommand {
 			network_concurrency: self.network_concurrency,
 			use_lockfile: true,
 			force: self.force,
+			prefer_offline: self.prefer_offline,
+			include_prerelease: self.include_prerelease,
+			dry_run: false,
+			reporter: self.reporter,
+			report: self.report,
+			only: (!self.aliases.is_empty()).then(|| {
+				(
+					subproject.importer().clone(),
+					self.aliases.into_iter().collect(),
+				)
+			}),
+		};
+
+		let project = match self.prefix {
+			Some(prefix) => subproject.project().with_prefix(
+				std::path::absolute(prefix).context("failed to resolve prefix directory")?,
+			),
+			None => subproject.project().clone(),
 		};
 
-		install(&options, subproject.project()).await?;
+		install(&options, &project).await?;
 
 		Ok(())
 	}
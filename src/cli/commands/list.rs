@@ -1,21 +1,50 @@
 use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fmt::Write as _;
 
 use anyhow::Context as _;
 use clap::Args;
+use clap::ValueEnum;
 
 use crate::cli::dep_type_to_key;
 use crate::cli::style::INFO_STYLE;
 use crate::cli::style::SUCCESS_STYLE;
 use pesde::Subproject;
+use pesde::graph::DependencyGraphNode;
 use pesde::manifest::Alias;
 use pesde::manifest::DependencyType;
 use pesde::source::DependencySpecifiers;
+use pesde::source::ids::PackageId;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ListFormat {
+	/// The dependencies declared in the manifest, grouped by type
+	Flat,
+	/// The resolved dependencies from the lockfile, as a tree
+	Tree,
+	/// The dependencies declared in the manifest, as JSON
+	Json,
+}
 
 #[derive(Debug, Args)]
-pub struct ListCommand;
+pub struct ListCommand {
+	/// The format to list the dependencies in
+	#[arg(long, value_enum, default_value_t = ListFormat::Flat)]
+	format: ListFormat,
+}
 
 impl ListCommand {
 	pub async fn run(self, subproject: Subproject) -> anyhow::Result<()> {
+		print!("{}", self.render(&subproject).await?);
+
+		Ok(())
+	}
+
+	async fn render(&self, subproject: &Subproject) -> anyhow::Result<String> {
+		if matches!(self.format, ListFormat::Tree) {
+			return render_tree(subproject).await;
+		}
+
 		let manifest = subproject
 			.deser_manifest()
 			.await
@@ -33,17 +62,213 @@ impl ListCommand {
 				},
 			);
 
+		if matches!(self.format, ListFormat::Json) {
+			let all_deps = all_deps
+				.into_iter()
+				.map(|(dep_ty, deps)| (dep_type_to_key(dep_ty), deps))
+				.collect::<BTreeMap<_, _>>();
+
+			return Ok(format!(
+				"{}\n",
+				serde_json::to_string_pretty(&all_deps)
+					.context("failed to serialize dependencies")?
+			));
+		}
+
+		let mut out = String::new();
 		for (dep_ty, deps) in all_deps {
 			let dep_key = dep_type_to_key(dep_ty);
-			println!("{}", INFO_STYLE.apply_to(dep_key));
+			_ = writeln!(out, "{}", INFO_STYLE.apply_to(dep_key));
 
 			for (alias, spec) in deps {
-				println!("{}: {spec}", SUCCESS_STYLE.apply_to(alias));
+				_ = writeln!(out, "{}: {spec}", SUCCESS_STYLE.apply_to(alias));
 			}
 
-			println!();
+			_ = writeln!(out);
 		}
 
-		Ok(())
+		Ok(out)
+	}
+}
+
+async fn render_tree(subproject: &Subproject) -> anyhow::Result<String> {
+	let graph = subproject
+		.project()
+		.graph_from_lockfile()
+		.await
+		.context("failed to read lockfile")?
+		.with_context(|| {
+			format!(
+				"no lockfile found, run `{} install` first",
+				env!("CARGO_BIN_NAME")
+			)
+		})?;
+
+	let mut out = String::new();
+	let Some(importer) = graph.importers.get(subproject.importer()) else {
+		return Ok(out);
+	};
+
+	let all_deps = importer.dependencies.iter().fold(
+		BTreeMap::<DependencyType, Vec<(&Alias, &PackageId)>>::new(),
+		|mut acc, (alias, (id, _, ty))| {
+			acc.entry(*ty).or_default().push((alias, id));
+			acc
+		},
+	);
+
+	// packages are only expanded the first time they are printed, which also
+	// prevents infinite recursion on cyclic dependencies
+	let mut expanded = HashSet::new();
+
+	for (dep_ty, deps) in all_deps {
+		_ = writeln!(out, "{}", INFO_STYLE.apply_to(dep_type_to_key(dep_ty)));
+
+		let len = deps.len();
+		for (i, (alias, id)) in deps.into_iter().enumerate() {
+			render_tree_node(
+				&mut out,
+				&graph.nodes,
+				alias,
+				id,
+				"",
+				i + 1 == len,
+				&mut expanded,
+			);
+		}
+
+		_ = writeln!(out);
+	}
+
+	Ok(out)
+}
+
+fn render_tree_node<'a>(
+	out: &mut String,
+	nodes: &'a BTreeMap<PackageId, DependencyGraphNode>,
+	alias: &Alias,
+	id: &'a PackageId,
+	prefix: &str,
+	last: bool,
+	expanded: &mut HashSet<&'a PackageId>,
+) {
+	let branch = if last { "└── " } else { "├── " };
+	let node = nodes.get(id);
+	let first_visit = expanded.insert(id);
+	let has_children = node.is_some_and(|node| !node.dependencies.is_empty());
+
+	_ = writeln!(
+		out,
+		"{prefix}{branch}{} {id}{}",
+		SUCCESS_STYLE.apply_to(alias),
+		if !first_visit && has_children {
+			" (*)"
+		} else {
+			""
+		}
+	);
+
+	let Some(node) = node.filter(|_| first_visit) else {
+		return;
+	};
+
+	let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+	let len = node.dependencies.len();
+	for (i, (dep_alias, dep)) in node.dependencies.iter().enumerate() {
+		render_tree_node(
+			out,
+			nodes,
+			dep_alias,
+			&dep.id,
+			&prefix,
+			i + 1 == len,
+			expanded,
+		);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use fs_err::tokio as fs;
+	use pesde::AuthConfig;
+	use pesde::Importer;
+	use pesde::MANIFEST_FILE_NAME;
+	use pesde::Project;
+
+	#[tokio::test]
+	async fn formats() {
+		let dir = tempfile::tempdir().unwrap();
+		for (package, manifest) in [
+			(
+				"project",
+				"[dependencies]\nfoo = { path = \"../foo\" }\n\n[dev_dependencies]\nbar = { path = \"../bar\" }\n",
+			),
+			("foo", "[dependencies]\nbaz = { path = \"../baz\" }\n"),
+			("bar", "[dependencies]\nfoo = { path = \"../foo\" }\n"),
+			("baz", ""),
+		] {
+			fs::create_dir_all(dir.path().join(package)).await.unwrap();
+			fs::write(dir.path().join(package).join(MANIFEST_FILE_NAME), manifest)
+				.await
+				.unwrap();
+		}
+
+		let project = Project::new(
+			dir.path().join("project"),
+			dir.path().join("data"),
+			dir.path().join("cas"),
+			AuthConfig::new(),
+			reqwest::Client::new(),
+		);
+		let subproject = project.clone().subproject(Importer::root());
+		let render = async |format| {
+			console::strip_ansi_codes(&ListCommand { format }.render(&subproject).await.unwrap())
+				.into_owned()
+		};
+
+		assert_eq!(
+			render(ListFormat::Flat).await,
+			"dependencies\n\
+			foo: path:../foo\n\
+			\n\
+			dev_dependencies\n\
+			bar: path:../bar\n\
+			\n"
+		);
+		assert_eq!(
+			serde_json::from_str::<serde_json::Value>(&render(ListFormat::Json).await).unwrap(),
+			serde_json::json!({
+				"dependencies": { "foo": { "path": "../foo" } },
+				"dev_dependencies": { "bar": { "path": "../bar" } },
+			})
+		);
+
+		// the tree is read from the lockfile
+		assert!(
+			ListCommand {
+				format: ListFormat::Tree
+			}
+			.render(&subproject)
+			.await
+			.is_err()
+		);
+		project
+			.install(pesde::download_and_link::InstallOptions::<()>::new())
+			.await
+			.unwrap();
+		assert_eq!(
+			render(ListFormat::Tree)
+				.await
+				.replace(&dir.path().display().to_string(), "<dir>"),
+			"dependencies\n\
+			└── foo path:<dir>/foo\n    \
+			    └── baz path:<dir>/baz\n\
+			\n\
+			dev_dependencies\n\
+			└── bar path:<dir>/bar\n    \
+			    └── foo path:<dir>/foo (*)\n\
+			\n"
+		);
 	}
 }
@@ -1,10 +1,16 @@
+use anyhow::Context as _;
 use pesde::Subproject;
+use pesde::version_matches_including_prerelease;
 
 mod add;
 mod auth;
 mod cas;
+mod ci_check;
 mod config;
+mod doctor;
 mod execute;
+mod graph;
+mod info;
 mod init;
 mod install;
 mod list;
@@ -30,6 +36,16 @@ pub enum Subcommand {
 	#[command(subcommand)]
 	Cas(cas::CasCommands),
 
+	/// Checks that the lockfile is up to date, peer dependencies are satisfied and indices are reachable
+	CiCheck(ci_check::CiCheckCommand),
+
+	/// Diagnoses common problems with the setup of pesde and the current project
+	Doctor(doctor::DoctorCommand),
+
+	/// Dependency graph-related commands
+	#[command(subcommand)]
+	Graph(graph::GraphCommands),
+
 	/// Initializes a manifest file in the current directory
 	Init(init::InitCommand),
 
@@ -52,6 +68,9 @@ pub enum Subcommand {
 	/// Lists all dependencies in the subproject
 	List(list::ListCommand),
 
+	/// Shows information about a package from its index
+	Info(info::InfoCommand),
+
 	/// Runs a script, an executable package, or a file with Lune
 	Run(run::RunCommand),
 
@@ -68,12 +87,49 @@ pub enum Subcommand {
 	Execute(execute::ExecuteCommand),
 }
 
+async fn check_required_pesde(subproject: &Subproject) -> anyhow::Result<()> {
+	// commands which need a valid manifest will report the error themselves
+	let Ok(manifest) = subproject.deser_manifest().await else {
+		return Ok(());
+	};
+	let Some(required) = &manifest.required_pesde else {
+		return Ok(());
+	};
+
+	let current = env!("CARGO_PKG_VERSION")
+		.parse()
+		.context("failed to parse current version")?;
+	if !version_matches_including_prerelease(required, &current) {
+		anyhow::bail!(
+			"this project requires {} {required}, but the current version is {current}. upgrade {} to a matching version",
+			env!("CARGO_BIN_NAME"),
+			env!("CARGO_BIN_NAME"),
+		);
+	}
+
+	Ok(())
+}
+
 impl Subcommand {
 	pub async fn run(self, subproject: Subproject) -> anyhow::Result<()> {
+		// these don't work on the current project
+		if !matches!(
+			self,
+			Subcommand::Auth(_)
+				| Subcommand::Config(_)
+				| Subcommand::Init(_)
+				| Subcommand::Execute(_)
+		) {
+			check_required_pesde(&subproject).await?;
+		}
+
 		match self {
 			Subcommand::Auth(auth) => auth.run(subproject).await,
 			Subcommand::Config(config) => config.run().await,
 			Subcommand::Cas(cas) => cas.run(subproject).await,
+			Subcommand::CiCheck(ci_check) => ci_check.run(subproject).await,
+			Subcommand::Doctor(doctor) => doctor.run(subproject).await,
+			Subcommand::Graph(graph) => graph.run(subproject).await,
 			Subcommand::Init(init) => init.run(subproject).await,
 			Subcommand::Add(add) => add.run(subproject).await,
 			Subcommand::Remove(remove) => remove.run(subproject).await,
@@ -81,6 +137,7 @@ impl Subcommand {
 			Subcommand::Update(update) => update.run(subproject).await,
 			Subcommand::Outdated(outdated) => outdated.run(subproject).await,
 			Subcommand::List(list) => list.run(subproject).await,
+			Subcommand::Info(info) => info.run(subproject).await,
 			Subcommand::Run(run) => run.run(subproject).await,
 			#[cfg(feature = "patches")]
 			Subcommand::Patch(patch) => patch.run(subproject.project().clone()).await,
@@ -90,3 +147,50 @@ impl Subcommand {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use fs_err::tokio as fs;
+	use pesde::AuthConfig;
+	use pesde::Importer;
+	use pesde::MANIFEST_FILE_NAME;
+	use pesde::Project;
+
+	#[tokio::test]
+	async fn required_pesde() {
+		let dir = tempfile::tempdir().unwrap();
+		let project = Project::new(
+			dir.path(),
+			dir.path().join("data"),
+			dir.path().join("cas"),
+			AuthConfig::new(),
+			reqwest::Client::new(),
+		);
+		let subproject = project.clone().subproject(Importer::root());
+		let check = async |manifest: &str| {
+			fs::write(dir.path().join(MANIFEST_FILE_NAME), manifest)
+				.await
+				.unwrap();
+			project.clear_manifest_cache().await;
+			check_required_pesde(&subproject).await
+		};
+
+		check("").await.unwrap();
+		check(&format!(
+			"required_pesde = \"={}\"",
+			env!("CARGO_PKG_VERSION")
+		))
+		.await
+		.unwrap();
+		// an invalid manifest is left for the command to report
+		check("required_pesde = 1").await.unwrap();
+
+		let err = check("required_pesde = \"<0.0.1\"").await.unwrap_err();
+		assert!(
+			err.to_string()
+				.contains(&format!("requires {} <0.0.1", env!("CARGO_BIN_NAME"))),
+			"{err}"
+		);
+	}
+}
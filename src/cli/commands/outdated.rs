@@ -7,6 +7,7 @@ use crate::cli::style::REMOVED_STYLE;
 use crate::cli::style::SUCCESS_STYLE;
 use anyhow::Context as _;
 use clap::Args;
+use clap::ValueEnum;
 use console::style;
 use itertools::Either;
 use pesde::RefreshedSources;
@@ -18,11 +19,52 @@ use semver::Version;
 use semver::VersionReq;
 use tokio::task::JoinSet;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum BumpLevel {
+	/// Only the patch version changed
+	Patch,
+	/// The minor version changed
+	Minor,
+	/// The major version changed
+	Major,
+}
+
+impl BumpLevel {
+	/// The level of the update from `current` to `new`, or `None` if `new` isn't newer
+	fn between(current: &Version, new: &Version) -> Option<Self> {
+		if new <= current {
+			return None;
+		}
+
+		Some(if new.major != current.major {
+			BumpLevel::Major
+		} else if new.minor != current.minor {
+			BumpLevel::Minor
+		} else {
+			BumpLevel::Patch
+		})
+	}
+}
+
+impl std::fmt::Display for BumpLevel {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			BumpLevel::Patch => write!(f, "patch"),
+			BumpLevel::Minor => write!(f, "minor"),
+			BumpLevel::Major => write!(f, "major"),
+		}
+	}
+}
+
 #[derive(Debug, Args)]
 pub struct OutdatedCommand {
 	/// Whether to check within version requirements
 	#[arg(short, long)]
 	strict: bool,
+
+	/// Exit with an error if any update at or above this level is available
+	#[arg(long, value_enum)]
+	fail_on: Option<BumpLevel>,
 }
 
 impl OutdatedCommand {
@@ -122,10 +164,16 @@ impl OutdatedCommand {
 			return Ok(());
 		}
 
+		let mut failing = 0usize;
+
 		for (importer, updates) in importer_updates {
 			println!("{}", style(importer).bold());
 
 			for (alias, (current_id, new_id)) in updates {
+				if fails_on(self.fail_on, &current_id, &new_id) {
+					failing += 1;
+				}
+
 				println!(
 					"  {} {} → {}",
 					INFO_STYLE.apply_to(alias),
@@ -135,6 +183,35 @@ impl OutdatedCommand {
 			}
 		}
 
+		if let Some(level) = self.fail_on
+			&& failing > 0
+		{
+			anyhow::bail!("{failing} dependencies have {level} or greater updates available");
+		}
+
 		Ok(())
 	}
 }
+
+/// Whether the update from `current` to `new` should fail the command
+fn fails_on(fail_on: Option<BumpLevel>, current: &Version, new: &Version) -> bool {
+	fail_on.is_some_and(|fail_on| BumpLevel::between(current, new) >= Some(fail_on))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fail_on_levels() {
+		let current = Version::new(1, 2, 3);
+		let minor = Version::new(1, 3, 0);
+
+		assert_eq!(BumpLevel::between(&current, &minor), Some(BumpLevel::Minor));
+		assert!(!fails_on(None, &current, &minor));
+		assert!(fails_on(Some(BumpLevel::Patch), &current, &minor));
+		assert!(fails_on(Some(BumpLevel::Minor), &current, &minor));
+		assert!(!fails_on(Some(BumpLevel::Major), &current, &minor));
+		assert!(!fails_on(Some(BumpLevel::Patch), &minor, &current));
+	}
+}
@@ -4,6 +4,8 @@ use anyhow::Context as _;
 use clap::Args;
 
 use crate::cli::dep_type_to_key;
+use crate::cli::install::InstallOptions;
+use crate::cli::install::install;
 use crate::cli::style::INFO_STYLE;
 use crate::cli::style::SUCCESS_STYLE;
 use pesde::Subproject;
@@ -15,6 +17,10 @@ pub struct RemoveCommand {
 	/// The alias of the package to remove
 	#[arg(index = 1)]
 	alias: Alias,
+
+	/// Whether to install afterwards, removing packages which are no longer used
+	#[arg(long)]
+	prune: bool,
 }
 
 impl RemoveCommand {
@@ -52,6 +58,10 @@ impl RemoveCommand {
 			INFO_STYLE.apply_to(dep_key)
 		);
 
+		if self.prune {
+			install(&InstallOptions::default(), subproject.project()).await?;
+		}
+
 		Ok(())
 	}
 }
@@ -1,35 +1,397 @@
+use crate::cli::install::InstallOptions;
+use crate::cli::install::install;
+use crate::cli::style::WARN_PREFIX;
 use anyhow::Context as _;
 use clap::Args;
+use fs_err::tokio as fs;
+use pesde::MANIFEST_FILE_NAME;
+use pesde::PACKAGES_CONTAINER_NAME;
 use pesde::Subproject;
+use pesde::graph::DependencyGraph;
+use pesde::graph::DependencyGraphNode;
+use pesde::manifest::Alias;
+use pesde::manifest::Manifest;
+use pesde::source::RealmExt as _;
+use pesde::source::ids::PackageId;
+use std::collections::BTreeSet;
 use std::ffi::OsString;
+use std::io::IsTerminal as _;
+use std::path::PathBuf;
 
 #[derive(Debug, Args)]
 pub struct RunCommand {
-	/// The script name to run
+	/// The script name to run, or `<ALIAS>:<SCRIPT>` to run a script of a dependency
 	#[arg(index = 1)]
 	script: String,
 
 	/// Arguments to pass to the script
-	#[arg(index = 2, trailing_var_arg = true)]
+	#[arg(index = 2, trailing_var_arg = true, allow_hyphen_values = true)]
 	args: Vec<OsString>,
+
+	/// Whether to install the dependencies without asking if they aren't installed
+	#[arg(long)]
+	auto_install: bool,
 }
 
 impl RunCommand {
 	pub async fn run(self, subproject: Subproject) -> anyhow::Result<()> {
+		self.run_with(subproject, &mut || {
+			if std::io::stdin().is_terminal() {
+				inquire::Confirm::new("dependencies are not installed, install them now?")
+					.with_default(true)
+					.prompt()
+					.context("failed to read answer")
+			} else {
+				println!(
+					"{WARN_PREFIX}: dependencies are not installed, run `{} install` or pass --auto-install",
+					env!("CARGO_BIN_NAME")
+				);
+				Ok(false)
+			}
+		})
+		.await
+	}
+
+	/// Runs the script, calling `confirm` to ask whether to install the dependencies first if
+	/// they aren't installed and --auto-install wasn't passed
+	async fn run_with(
+		self,
+		subproject: Subproject,
+		confirm: &mut dyn FnMut() -> anyhow::Result<bool>,
+	) -> anyhow::Result<()> {
 		let manifest = subproject
 			.deser_manifest()
 			.await
 			.context("failed to deserialize manifest")?;
-		pesde::scripts::execute_script(
-			&subproject,
+
+		if needs_install(&subproject, &manifest).await? {
+			let confirmed = self.auto_install || confirm()?;
+
+			if confirmed {
+				install(&InstallOptions::default(), subproject.project()).await?;
+			}
+		}
+
+		if let Some(script) = manifest.scripts.get(&self.script) {
+			let code = pesde::scripts::execute_script(&subproject, script, &mut (), self.args)
+				.await
+				.context("failed to execute script")?;
+			exit_on_failure(code);
+			return Ok(());
+		}
+
+		let Some((alias, script)) = self.script.split_once(':') else {
+			anyhow::bail!("script not found");
+		};
+		let alias = alias.parse::<Alias>().context("invalid dependency alias")?;
+
+		let dir = dependency_dir(&subproject, &alias).await?;
+		let manifest = fs::read_to_string(dir.join(MANIFEST_FILE_NAME))
+			.await
+			.with_context(|| format!("failed to read manifest of dependency `{alias}`"))?;
+		let manifest = toml::from_str::<Manifest>(&manifest)
+			.with_context(|| format!("failed to deserialize manifest of dependency `{alias}`"))?;
+
+		let code = pesde::scripts::execute_script_in(
+			&dir,
 			manifest
 				.scripts
-				.get(&self.script)
-				.context("script not found")?,
+				.get(script)
+				.with_context(|| format!("script `{script}` not found in dependency `{alias}`"))?,
 			&mut (),
 			self.args,
 		)
-		.await?;
+		.await
+		.context("failed to execute script")?;
+		exit_on_failure(code);
+
 		Ok(())
 	}
 }
+
+/// Exits with the script's exit code so callers (shells, CI) can observe failures
+fn exit_on_failure(code: i32) {
+	if code != 0i32 {
+		std::process::exit(code);
+	}
+}
+
+async fn dependency_dir(subproject: &Subproject, alias: &Alias) -> anyhow::Result<PathBuf> {
+	let graph = subproject
+		.project()
+		.graph_from_lockfile()
+		.await
+		.context("failed to read lockfile")?
+		.with_context(|| {
+			format!(
+				"no lockfile found, run `{} install` first",
+				env!("CARGO_BIN_NAME")
+			)
+		})?;
+
+	let (id, node) = graph
+		.importers
+		.get(subproject.importer())
+		.and_then(|importer| importer.dependencies.get(alias))
+		.and_then(|(id, _, _)| graph.nodes.get(id).map(|node| (id, node)))
+		.with_context(|| format!("dependency `{alias}` not found"))?;
+
+	let dir = container_dir(subproject, &graph, id, node);
+
+	anyhow::ensure!(
+		fs::metadata(&dir).await.is_ok(),
+		"dependency `{alias}` is not installed, run `{} install` first",
+		env!("CARGO_BIN_NAME")
+	);
+
+	Ok(dir)
+}
+
+fn container_dir(
+	subproject: &Subproject,
+	graph: &DependencyGraph,
+	id: &PackageId,
+	node: &DependencyGraphNode,
+) -> PathBuf {
+	subproject
+		.dependencies_dir()
+		.join(graph.realm_of(subproject.importer(), id).packages_dir())
+		.join(PACKAGES_CONTAINER_NAME)
+		.join(DependencyGraphNode::container_dir(id, &node.structure_kind))
+}
+
+/// Whether the lockfile is missing, doesn't list the same dependencies as the manifest, or
+/// any direct dependency isn't installed. This doesn't check whether the specifiers still match
+async fn needs_install(subproject: &Subproject, manifest: &Manifest) -> anyhow::Result<bool> {
+	let aliases = manifest
+		.all_dependencies()
+		.context("failed to get all dependencies")?
+		.into_keys()
+		.collect::<BTreeSet<_>>();
+
+	let Some(graph) = subproject
+		.project()
+		.graph_from_lockfile()
+		.await
+		.context("failed to read lockfile")?
+	else {
+		return Ok(!aliases.is_empty());
+	};
+
+	let Some(importer) = graph.importers.get(subproject.importer()) else {
+		return Ok(!aliases.is_empty());
+	};
+
+	if !importer.dependencies.keys().eq(aliases.iter()) {
+		return Ok(true);
+	}
+
+	for (id, _, _) in importer.dependencies.values() {
+		let Some(node) = graph.nodes.get(id) else {
+			return Ok(true);
+		};
+
+		if fs::metadata(container_dir(subproject, &graph, id, node))
+			.await
+			.is_err()
+		{
+			return Ok(true);
+		}
+	}
+
+	Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use clap::Parser as _;
+	use pesde::AuthConfig;
+	use pesde::Importer;
+	use pesde::Project;
+
+	#[tokio::test]
+	async fn needs_install_checks_lockfile_and_installed_dependencies() {
+		let dir = tempfile::tempdir().unwrap();
+		let project_dir = dir.path().join("project");
+		fs::create_dir_all(&project_dir).await.unwrap();
+		fs::create_dir_all(dir.path().join("foo")).await.unwrap();
+		fs::write(dir.path().join("foo").join(MANIFEST_FILE_NAME), "")
+			.await
+			.unwrap();
+
+		let project = Project::new(
+			&project_dir,
+			dir.path().join("data"),
+			dir.path().join("cas"),
+			AuthConfig::new(),
+			reqwest::Client::new(),
+		);
+		let subproject = project.clone().subproject(Importer::root());
+		let needs_install = async |manifest: &str| {
+			fs::write(project_dir.join(MANIFEST_FILE_NAME), manifest)
+				.await
+				.unwrap();
+			project.clear_manifest_cache().await;
+			let manifest = subproject.deser_manifest().await.unwrap();
+			super::needs_install(&subproject, &manifest).await.unwrap()
+		};
+		let with_foo = "[dependencies]\nfoo = { path = \"../foo\" }\n";
+
+		// nothing to install
+		assert!(!needs_install("").await);
+		// no lockfile
+		assert!(needs_install(with_foo).await);
+
+		project
+			.install(pesde::download_and_link::InstallOptions::<()>::new())
+			.await
+			.unwrap();
+		assert!(!needs_install(with_foo).await);
+
+		// the lockfile lists a dependency the manifest no longer has
+		assert!(needs_install("").await);
+
+		// the dependency was removed from disk
+		fs::remove_dir_all(subproject.dependencies_dir())
+			.await
+			.unwrap();
+		assert!(needs_install(with_foo).await);
+	}
+
+	#[tokio::test]
+	async fn dependency_scripts() {
+		let dir = tempfile::tempdir().unwrap();
+		let project_dir = dir.path().join("project");
+		fs::create_dir_all(&project_dir).await.unwrap();
+		fs::write(
+			project_dir.join(MANIFEST_FILE_NAME),
+			"[dependencies]\nfoo = { path = \"../foo\" }\n",
+		)
+		.await
+		.unwrap();
+		fs::create_dir_all(dir.path().join("foo")).await.unwrap();
+		fs::write(
+			dir.path().join("foo").join(MANIFEST_FILE_NAME),
+			"[scripts]\nmark = \"echo marked > mark.txt\"\n",
+		)
+		.await
+		.unwrap();
+
+		let project = Project::new(
+			&project_dir,
+			dir.path().join("data"),
+			dir.path().join("cas"),
+			AuthConfig::new(),
+			reqwest::Client::new(),
+		);
+		let subproject = project.clone().subproject(Importer::root());
+		let run = async |script: &str| {
+			Cli::try_parse_from(["run", script])
+				.unwrap()
+				.run
+				.run(subproject.clone())
+				.await
+		};
+
+		project
+			.install(pesde::download_and_link::InstallOptions::<()>::new())
+			.await
+			.unwrap();
+		run("foo:mark").await.unwrap();
+
+		// the script runs in the installed dependency's directory
+		let installed_dir = dependency_dir(&subproject, &"foo".parse().unwrap())
+			.await
+			.unwrap();
+		assert_eq!(
+			fs::read_to_string(installed_dir.join("mark.txt"))
+				.await
+				.unwrap()
+				.trim(),
+			"marked"
+		);
+
+		assert!(run("foo:missing").await.is_err());
+		assert!(run("bar:mark").await.is_err());
+	}
+
+	#[tokio::test]
+	async fn installs_before_running() {
+		let dir = tempfile::tempdir().unwrap();
+		let project_dir = dir.path().join("project");
+		fs::create_dir_all(&project_dir).await.unwrap();
+		fs::write(
+			project_dir.join(MANIFEST_FILE_NAME),
+			"[scripts]\nmark = \"echo marked > mark.txt\"\n\n[dependencies]\nfoo = { path = \"../foo\" }\n",
+		)
+		.await
+		.unwrap();
+		fs::create_dir_all(dir.path().join("foo")).await.unwrap();
+		fs::write(dir.path().join("foo").join(MANIFEST_FILE_NAME), "")
+			.await
+			.unwrap();
+
+		let project = Project::new(
+			&project_dir,
+			dir.path().join("data"),
+			dir.path().join("cas"),
+			AuthConfig::new(),
+			reqwest::Client::new(),
+		);
+		let subproject = project.clone().subproject(Importer::root());
+		let run = async |args: &[&str], answer: Option<bool>| {
+			_ = fs::remove_file(project_dir.join("mark.txt")).await;
+			let mut asked = false;
+			Cli::try_parse_from(std::iter::once("run").chain(args.iter().copied()))
+				.unwrap()
+				.run
+				.run_with(subproject.clone(), &mut || {
+					asked = true;
+					Ok(answer.expect("asked to install"))
+				})
+				.await
+				.unwrap();
+
+			// the script runs whether or not the dependencies were installed
+			assert!(fs::metadata(project_dir.join("mark.txt")).await.is_ok());
+			asked
+		};
+		let installed = async || {
+			let manifest = subproject.deser_manifest().await.unwrap();
+			!needs_install(&subproject, &manifest).await.unwrap()
+		};
+
+		// declining leaves the project uninstalled
+		assert!(run(&["mark"], Some(false)).await);
+		assert!(!installed().await);
+
+		assert!(run(&["mark"], Some(true)).await);
+		assert!(installed().await);
+		// nothing is asked once installed
+		assert!(!run(&["mark"], None).await);
+
+		fs::remove_dir_all(subproject.dependencies_dir())
+			.await
+			.unwrap();
+		assert!(!run(&["--auto-install", "mark"], None).await);
+		assert!(installed().await);
+	}
+
+	#[derive(clap::Parser)]
+	struct Cli {
+		#[command(flatten)]
+		run: RunCommand,
+	}
+
+	#[test]
+	fn hyphenated_arguments() {
+		let run = Cli::try_parse_from(["run", "--auto-install", "build", "--release", "-v", "out"])
+			.unwrap()
+			.run;
+
+		assert!(run.auto_install);
+		assert_eq!(run.script, "build");
+		assert_eq!(run.args, ["--release", "-v", "out"]);
+	}
+}
@@ -1,9 +1,21 @@
+use crate::cli::dep_type_to_key;
 use crate::cli::install::InstallOptions;
+use crate::cli::install::get_graph;
 use crate::cli::install::install;
+use crate::cli::style::ADDED_STYLE;
+use crate::cli::style::INFO_STYLE;
+use crate::cli::style::REMOVED_STYLE;
+use crate::cli::style::WARN_PREFIX;
+use anyhow::Context as _;
 use clap::Args;
+use pesde::RefreshedSources;
 use pesde::Subproject;
-use pesde::download_and_link::InstallDependenciesMode;
+use pesde::source::DependencySpecifiers;
+use pesde::source::PackageSource as _;
+use semver::Version;
+use semver::VersionReq;
 use std::num::NonZeroUsize;
+use std::str::FromStr as _;
 
 #[derive(Debug, Args, Copy, Clone)]
 pub struct UpdateCommand {
@@ -18,17 +30,34 @@ pub struct UpdateCommand {
 	/// Whether to re-install all dependencies even if they are already installed
 	#[arg(long)]
 	force: bool,
+
+	/// Print the changes that would be made to the lockfile without writing it
+	#[arg(long)]
+	dry_run: bool,
+
+	/// Update direct dependencies to their latest versions, even if they are outside the version requirements in the manifest
+	#[arg(long)]
+	latest: bool,
+
+	/// Whether prerelease versions may satisfy version requirements which don't mention a prerelease
+	#[arg(long)]
+	include_prerelease: bool,
 }
 
 impl UpdateCommand {
 	pub async fn run(self, subproject: Subproject) -> anyhow::Result<()> {
+		if self.latest {
+			update_requirements(&subproject, self.dry_run).await?;
+		}
+
 		let options = InstallOptions {
-			locked: false,
-			install_dependencies_mode: InstallDependenciesMode::All,
 			write: !self.no_install,
 			network_concurrency: self.network_concurrency,
 			use_lockfile: false,
 			force: self.force,
+			dry_run: self.dry_run,
+			include_prerelease: self.include_prerelease,
+			..Default::default()
 		};
 
 		install(&options, subproject.project()).await?;
@@ -36,3 +65,217 @@ impl UpdateCommand {
 		Ok(())
 	}
 }
+
+/// The latest stable version out of `versions`, if it is newer than `current` and not allowed
+/// by `req`
+fn latest_outside_requirement(
+	req: &VersionReq,
+	current: &Version,
+	versions: impl DoubleEndedIterator<Item = Version>,
+) -> Option<Version> {
+	versions
+		.rev()
+		.find(|version| version.pre.is_empty())
+		.filter(|latest| latest > current && !req.matches(latest))
+}
+
+/// Rewrites the version requirements of the direct dependencies which don't allow their latest version to a caret requirement on it
+async fn update_requirements(subproject: &Subproject, dry_run: bool) -> anyhow::Result<()> {
+	let refreshed_sources = RefreshedSources::new();
+	let graph = get_graph(subproject.project(), &refreshed_sources).await?;
+
+	let Some(importer) = graph.importers.get(subproject.importer()) else {
+		return Ok(());
+	};
+
+	let mut manifest = toml_edit::DocumentMut::from_str(
+		&subproject
+			.read_manifest()
+			.await
+			.context("failed to read manifest")?,
+	)
+	.context("failed to parse manifest")?;
+	let mut changed = false;
+
+	for (alias, (id, spec, ty)) in &importer.dependencies {
+		let (req, any_spec) = match spec {
+			#[expect(deprecated)]
+			DependencySpecifiers::Pesde(spec) => {
+				let mut any_spec = spec.clone();
+				any_spec.version = VersionReq::STAR;
+				(&spec.version, DependencySpecifiers::Pesde(any_spec))
+			}
+			DependencySpecifiers::Wally(spec) => {
+				let mut any_spec = spec.clone();
+				any_spec.version = VersionReq::STAR;
+				(&spec.version, DependencySpecifiers::Wally(any_spec))
+			}
+			DependencySpecifiers::Git(_) | DependencySpecifiers::Path(_) => continue,
+		};
+
+		refreshed_sources
+			.refresh(id.source(), subproject.project())
+			.await
+			.context("failed to refresh source")?;
+
+		let versions = id
+			.source()
+			.resolve(subproject, &any_spec, &refreshed_sources)
+			.await
+			.with_context(|| format!("failed to resolve versions of `{alias}`"))?
+			.versions;
+		let Some(latest) = latest_outside_requirement(req, id.version(), versions.into_keys())
+		else {
+			continue;
+		};
+
+		let new_req = format!("^{latest}");
+		println!(
+			"{}: {} → {}",
+			INFO_STYLE.apply_to(alias),
+			REMOVED_STYLE.apply_to(req),
+			ADDED_STYLE.apply_to(&new_req),
+		);
+
+		if !VersionReq::parse(&format!("^{}", id.version()))
+			.unwrap()
+			.matches(&latest)
+		{
+			println!(
+				"{WARN_PREFIX}: {alias} {latest} may contain breaking changes from {}",
+				id.version()
+			);
+		}
+
+		manifest[dep_type_to_key(*ty)][alias.as_str()]["version"] = toml_edit::value(new_req);
+		changed = true;
+	}
+
+	if changed && !dry_run {
+		subproject
+			.write_manifest(manifest.to_string())
+			.await
+			.context("failed to write manifest")?;
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::local_index::LocalIndex;
+	use fs_err::tokio as fs;
+	use pesde::AuthConfig;
+	use pesde::Importer;
+	use pesde::MANIFEST_FILE_NAME;
+	use pesde::Project;
+
+	#[test]
+	fn latest_outside_requirement_skips_allowed_versions() {
+		let version = |version: &str| version.parse::<Version>().unwrap();
+		let latest = |req: &str, current: &str, versions: &[&str]| {
+			latest_outside_requirement(
+				&req.parse().unwrap(),
+				&version(current),
+				versions.iter().map(|v| version(v)),
+			)
+			.map(|version| version.to_string())
+		};
+
+		assert_eq!(
+			latest("^1.0.0", "1.0.0", &["1.0.0", "1.2.0", "2.0.0"]).as_deref(),
+			Some("2.0.0")
+		);
+		// the requirement already allows the latest version
+		assert_eq!(latest("^1.0.0", "1.0.0", &["1.0.0", "1.2.0"]), None);
+		// prereleases aren't updated to
+		assert_eq!(
+			latest("^1.0.0", "1.0.0", &["1.0.0", "1.2.0", "2.0.0-rc.1"]).as_deref(),
+			None
+		);
+		assert_eq!(
+			latest("=1.0.0", "1.0.0", &["1.0.0", "1.2.0", "2.0.0-rc.1"]).as_deref(),
+			Some("1.2.0")
+		);
+		// never downgrades
+		assert_eq!(latest("=2.0.0", "2.0.0", &["1.0.0"]), None);
+		assert_eq!(latest("^1.0.0", "1.0.0", &[]), None);
+	}
+
+	#[tokio::test]
+	async fn latest_updates_manifest_and_lockfile() {
+		let dir = tempfile::tempdir().unwrap();
+		let mut index = LocalIndex::new(&dir.path().join("index"));
+		let publish = async |index: &mut LocalIndex, version: &str| {
+			index
+				.publish(
+					"acme/foo",
+					version,
+					"{ environment = \"luau\", lib = \"init.luau\" }",
+					&[("init.luau", "return {}")],
+				)
+				.await;
+		};
+		publish(&mut index, "1.0.0").await;
+
+		let project_dir = dir.path().join("project");
+		fs::create_dir_all(&project_dir).await.unwrap();
+		fs::write(
+			project_dir.join(MANIFEST_FILE_NAME),
+			format!(
+				"[indices]\ndefault = \"{}\"\n\n[dependencies]\nfoo = {{ name = \"acme/foo\", version = \"^1.0.0\", target = \"luau\" }}\n",
+				index.url()
+			),
+		)
+		.await
+		.unwrap();
+
+		let project = Project::new(
+			&project_dir,
+			dir.path().join("data"),
+			dir.path().join("cas"),
+			AuthConfig::new(),
+			reqwest::Client::new(),
+		);
+		install(&InstallOptions::default(), &project).await.unwrap();
+		publish(&mut index, "2.0.0").await;
+
+		let update = |dry_run| UpdateCommand {
+			no_install: false,
+			network_concurrency: NonZeroUsize::new(1).unwrap(),
+			force: false,
+			dry_run,
+			latest: true,
+			include_prerelease: false,
+		};
+		let state = async || {
+			let manifest = fs::read_to_string(project_dir.join(MANIFEST_FILE_NAME))
+				.await
+				.unwrap();
+			let requirement =
+				toml::from_str::<toml::Table>(&manifest).unwrap()["dependencies"]["foo"]["version"]
+					.as_str()
+					.unwrap()
+					.to_string();
+
+			let graph = project.graph_from_lockfile().await.unwrap().unwrap();
+			let (id, _, _) =
+				&graph.importers[&Importer::root()].dependencies[&"foo".parse().unwrap()];
+
+			(requirement, id.version().to_string())
+		};
+
+		update(true)
+			.run(project.clone().subproject(Importer::root()))
+			.await
+			.unwrap();
+		assert_eq!(state().await, ("^1.0.0".to_string(), "1.0.0".to_string()));
+
+		update(false)
+			.run(project.clone().subproject(Importer::root()))
+			.await
+			.unwrap();
+		assert_eq!(state().await, ("^2.0.0".to_string(), "2.0.0".to_string()));
+	}
+}
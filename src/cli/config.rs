@@ -4,6 +4,7 @@ use fs_err::tokio as fs;
 use pesde::GixUrl;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use tracing::instrument;
 
 use super::config_path;
@@ -13,6 +14,9 @@ use super::config_path;
 pub struct CliConfig {
 	pub default_index: GixUrl,
 
+	#[serde(skip_serializing_if = "BTreeMap::is_empty")]
+	pub indices: BTreeMap<String, GixUrl>,
+
 	pub tokens: Tokens,
 
 	#[serde(skip_serializing_if = "Option::is_none")]
@@ -24,6 +28,8 @@ impl Default for CliConfig {
 		Self {
 			default_index: "https://github.com/pesde-pkg/index".parse().unwrap(),
 
+			indices: BTreeMap::new(),
+
 			tokens: Tokens::default(),
 
 			last_checked_updates: None,
@@ -31,6 +37,25 @@ impl Default for CliConfig {
 	}
 }
 
+impl CliConfig {
+	/// The default index, the `PESDE_DEFAULT_INDEX` environment variable taking precedence over the config file
+	pub fn resolved_default_index(&self) -> anyhow::Result<GixUrl> {
+		resolve_default_index(
+			std::env::var("PESDE_DEFAULT_INDEX").ok().as_deref(),
+			&self.default_index,
+		)
+	}
+}
+
+fn resolve_default_index(env_index: Option<&str>, config_index: &GixUrl) -> anyhow::Result<GixUrl> {
+	match env_index {
+		Some(index) => index
+			.parse()
+			.with_context(|| format!("PESDE_DEFAULT_INDEX ({index}) is not a valid URL")),
+		None => Ok(config_index.clone()),
+	}
+}
+
 #[instrument(level = "trace")]
 pub async fn read_config() -> anyhow::Result<CliConfig> {
 	let config_string = match fs::read_to_string(config_path()?).await {
@@ -63,3 +88,23 @@ pub async fn write_config(config: &CliConfig) -> anyhow::Result<()> {
 
 	Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn env_default_index_overrides_config() {
+		let config = CliConfig::default();
+		let env_index = "https://github.com/acme/index";
+
+		assert_eq!(
+			resolve_default_index(Some(env_index), &config.default_index).unwrap(),
+			env_index.parse::<GixUrl>().unwrap()
+		);
+		assert_eq!(
+			resolve_default_index(None, &config.default_index).unwrap(),
+			config.default_index
+		);
+	}
+}
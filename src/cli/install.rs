@@ -1,31 +1,56 @@
 use crate::cli::dep_type_to_key;
 use crate::cli::reporters;
 use crate::cli::reporters::CliReporter;
+use crate::cli::reporters::DynWriter;
+use crate::cli::reporters::ReporterKind;
 use crate::cli::style::ADDED_STYLE;
 use crate::cli::style::REMOVED_STYLE;
 use crate::cli::style::WARN_PREFIX;
 use anyhow::Context as _;
 use console::style;
-use itertools::EitherOrBoth;
+use fs_err::tokio as fs;
+use indicatif::MultiProgress;
 use itertools::Itertools as _;
+use pesde::Importer;
+use pesde::LOCKFILE_FILE_NAME;
 use pesde::Project;
 use pesde::RefreshedSources;
 use pesde::download_and_link::DownloadAndLinkOptions;
 use pesde::download_and_link::InstallDependenciesMode;
+use pesde::download_and_link::InstallOptions as PesdeInstallOptions;
+use pesde::download_and_link::errors::InstallErrorKind;
 use pesde::graph::DependencyGraph;
-use pesde::lockfile::Lockfile;
+use pesde::graph::DependencyGraphNode;
+use pesde::graph::GraphDiff;
+use pesde::hash::Hash;
+use pesde::hash::HashAlgorithm;
+use pesde::manifest::Alias;
 use pesde::manifest::DependencyType;
+use pesde::reporters::DownloadsReporter;
+use pesde::reporters::InstallReporter;
+use pesde::reporters::InstallStage;
+use pesde::reporters::MultiReporter;
+use pesde::reporters::PatchesReporter;
+use pesde::reporters::ResolveReporter;
+use pesde::source::DependencySpecifiers;
 use pesde::source::PackageRefs;
 use pesde::source::PackageSources;
+use pesde::source::ids::PackageId;
 #[expect(deprecated)]
 use pesde::source::pesde::backend::PesdePackageSourceBackend as _;
-use std::cmp::Ordering;
+use serde::Serialize;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::num::NonZeroUsize;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
 use std::time::Instant;
 use tokio::task::JoinSet;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct InstallOptions {
 	pub locked: bool,
 	pub install_dependencies_mode: InstallDependenciesMode,
@@ -33,6 +58,33 @@ pub struct InstallOptions {
 	pub use_lockfile: bool,
 	pub network_concurrency: NonZeroUsize,
 	pub force: bool,
+	pub prefer_offline: bool,
+	pub include_prerelease: bool,
+	pub dry_run: bool,
+	pub only: Option<(Importer, BTreeSet<Alias>)>,
+	pub reporter: ReporterKind,
+	/// The file to write a JSON report of the install to
+	pub report: Option<PathBuf>,
+}
+
+impl Default for InstallOptions {
+	/// The options of a plain `pesde install`
+	fn default() -> Self {
+		InstallOptions {
+			locked: false,
+			install_dependencies_mode: InstallDependenciesMode::All,
+			write: true,
+			use_lockfile: true,
+			network_concurrency: NonZeroUsize::new(16).unwrap(),
+			force: false,
+			prefer_offline: false,
+			include_prerelease: false,
+			dry_run: false,
+			only: None,
+			reporter: ReporterKind::Auto,
+			report: None,
+		}
+	}
 }
 
 async fn get_graph_internal(
@@ -40,28 +92,23 @@ async fn get_graph_internal(
 	refreshed_sources: &RefreshedSources,
 	locked: bool,
 	use_lockfile: bool,
+	reporter: &impl ResolveReporter,
 ) -> anyhow::Result<(Option<DependencyGraph>, DependencyGraph)> {
-	let lockfile = if use_lockfile {
-		match project.deser_lockfile().await {
-			Ok(lockfile) => Some(lockfile),
-			Err(e) => {
-				if let pesde::errors::LockfileReadErrorKind::Io(e) = e.inner()
-					&& e.kind() == std::io::ErrorKind::NotFound
-				{
-					None
-				} else {
-					return Err(e.into());
-				}
-			}
-		}
-	} else {
-		None
+	// the lockfile is always read so that changes can be reported, but it is only
+	// used for resolution if `use_lockfile` is set
+	let old_graph = match project.graph_from_lockfile().await {
+		Ok(graph) => graph,
+		Err(_) if !use_lockfile => None,
+		Err(e) => return Err(e.into()),
 	};
 
-	let old_graph = lockfile.map(|lockfile| lockfile.graph);
-
 	let (graph, updated) = project
-		.dependency_graph(old_graph.as_ref(), refreshed_sources, false)
+		.dependency_graph(
+			old_graph.as_ref().filter(|_| use_lockfile),
+			refreshed_sources,
+			false,
+			reporter,
+		)
 		.await
 		.context("failed to build dependency graph")?;
 
@@ -79,16 +126,19 @@ pub async fn get_graph(
 	project: &Project,
 	refreshed_sources: &RefreshedSources,
 ) -> anyhow::Result<DependencyGraph> {
-	let (_, graph) = get_graph_internal(project, refreshed_sources, false, true).await?;
+	let lockfile = project
+		.resolve_lockfile(true, refreshed_sources)
+		.await
+		.context("failed to build dependency graph")?;
 
-	Ok(graph)
+	Ok(lockfile.graph)
 }
 
 pub async fn get_graph_locked(
 	project: &Project,
 	refreshed_sources: &RefreshedSources,
 ) -> anyhow::Result<DependencyGraph> {
-	let (_, graph) = get_graph_internal(project, refreshed_sources, true, true).await?;
+	let (_, graph) = get_graph_internal(project, refreshed_sources, true, true, &()).await?;
 
 	Ok(graph)
 }
@@ -96,182 +146,318 @@ pub async fn get_graph_locked(
 pub async fn install(options: &InstallOptions, project: &Project) -> anyhow::Result<()> {
 	let start = Instant::now();
 
-	let refreshed_sources = RefreshedSources::new();
+	let refreshed_sources = RefreshedSources::new()
+		.prefer_offline(options.prefer_offline)
+		.include_prerelease(options.include_prerelease);
+	let stage_timer = Arc::new(StageTimer::default());
 
-	let (new_lockfile, old_graph) =
-		reporters::run_with_reporter(|multi, root_progress, reporter| async {
+	let (old_graph, graph, installed_at) = reporters::run_with_reporter_kind(
+		options.reporter,
+		|multi, root_progress, reporter| async {
 			let multi = multi;
 			let root_progress = root_progress;
 
-			root_progress.reset();
-			root_progress.set_message("resolve");
-
-			let (old_graph, graph) = get_graph_internal(
-				project,
-				&refreshed_sources,
-				options.locked,
-				options.use_lockfile,
-			)
-			.await?;
-
-			#[expect(deprecated)]
-			let mut tasks = graph
-				.nodes
-				.keys()
-				.filter_map(|id| {
-					let PackageSources::Pesde(source) = id.source() else {
-						return None;
-					};
-					let PackageRefs::Pesde(pkg_ref) = id.pkg_ref() else {
-						return None;
-					};
-					let source = source.clone();
-					let name = pkg_ref.name.clone();
-					let project = project.clone();
-					let refreshed_sources = refreshed_sources.clone();
-
-					Some(async move {
-						refreshed_sources
-							.refresh(&PackageSources::Pesde(source.clone()), &project)
-							.await
-							.context("failed to refresh source")?;
-
-						let file = source
-							.repo()
-							.read_index_file(&project, name.clone())
-							.await
-							.context("failed to read package index file")?
-							.context("package not found in index")?;
-
-						Ok::<_, anyhow::Error>(if file.meta.deprecated.is_empty() {
-							None
-						} else {
-							Some((name, file.meta.deprecated))
-						})
-					})
-				})
-				.collect::<JoinSet<_>>();
-
-			while let Some(task) = tasks.join_next().await {
-				let Some((name, reason)) = task.unwrap()? else {
-					continue;
-				};
+			let deprecation_warnings = Arc::new(DeprecationWarnings {
+				project: project.clone(),
+				refreshed_sources: refreshed_sources.clone(),
+				multi_progress: multi.clone(),
+				tasks: Default::default(),
+			});
+
+			let install_options = PesdeInstallOptions::<InstallReporters>::new()
+				.download_and_link(
+					DownloadAndLinkOptions::new()
+						.reporter(MultiReporter::new(
+							reporter,
+							Arc::new(MultiReporter::new(
+								stage_timer.clone(),
+								deprecation_warnings.clone(),
+							)),
+						))
+						.refreshed_sources(refreshed_sources.clone())
+						.install_dependencies_mode(options.install_dependencies_mode)
+						.network_concurrency(options.network_concurrency)
+						.force(options.force),
+				)
+				.locked(options.locked)
+				.use_lockfile(options.use_lockfile)
+				.link(options.write && !options.dry_run)
+				// installs into a prefix mustn't touch the project
+				.write_lockfile(!options.dry_run && project.prefix().is_none());
+			let install_options = match options.only.clone() {
+				Some((importer, aliases)) => install_options.only(importer, aliases),
+				None => install_options,
+			};
 
-				multi.suspend(|| {
-					println!("{WARN_PREFIX}: package {name} is deprecated: {reason}");
-				});
-			}
+			let result = project.install(install_options).await;
+			let installed_at = Instant::now();
 
-			if options.write {
-				root_progress.reset();
-				root_progress.set_length(0);
-				root_progress.set_message("download");
-				root_progress.set_style(reporters::root_progress_style_with_progress());
-
-				project
-					.download_and_link(
-						&graph,
-						DownloadAndLinkOptions::<CliReporter>::new()
-							.reporter(reporter)
-							.refreshed_sources(refreshed_sources.clone())
-							.install_dependencies_mode(options.install_dependencies_mode)
-							.network_concurrency(options.network_concurrency)
-							.force(options.force),
-					)
-					.await
-					.context("failed to download and link dependencies")?;
-			}
+			let (old_graph, graph) = match result {
+				Ok(graphs) => graphs,
+				Err(e) if matches!(e.inner(), InstallErrorKind::LockfileOutOfSync) => {
+					anyhow::bail!(
+						"lockfile is out of sync, run `{} install` without --locked to update it",
+						env!("CARGO_BIN_NAME")
+					);
+				}
+				Err(e) => return Err(e).context("failed to install dependencies"),
+			};
 
 			root_progress.reset();
 			root_progress.set_message("finish");
 
-			let new_lockfile = Lockfile { graph };
+			deprecation_warnings.finish().await?;
+
+			anyhow::Ok((old_graph, graph, installed_at))
+		},
+	)
+	.await?;
 
-			project
-				.write_lockfile(&new_lockfile)
-				.await
-				.context("failed to write lockfile")?;
+	let elapsed = start.elapsed();
 
-			anyhow::Ok((new_lockfile, old_graph))
-		})
+	if let Some(path) = &options.report {
+		let diff = graph.diff(old_graph.as_ref().unwrap_or(&DependencyGraph::default()));
+		let mut durations = stage_timer.durations(installed_at);
+		durations.push(("total", elapsed));
+
+		write_install_report(
+			path,
+			&diff,
+			durations,
+			&project.dir().join(LOCKFILE_FILE_NAME),
+		)
 		.await?;
+	}
 
-	let elapsed = start.elapsed();
+	print_install_summary(old_graph, &graph);
 
-	print_install_summary(old_graph, new_lockfile.graph);
+	if options.dry_run {
+		println!("dry run, lockfile was not written");
+	}
 
 	println!("done in {:.2}s", elapsed.as_secs_f64());
 
 	Ok(())
 }
 
-pub fn print_install_summary(old_graph: Option<DependencyGraph>, new_graph: DependencyGraph) {
-	let old_importers = old_graph
-		.map_or(BTreeMap::new(), |old_graph| old_graph.importers)
-		.into_iter();
-	let new_importers = new_graph.importers.into_iter();
-
-	let importer_pairs = old_importers
-		.merge_join_by(new_importers, |(old_importer, _), (new_importer, _)| {
-			old_importer.cmp(new_importer)
-		})
-		.map(|m| match m {
-			EitherOrBoth::Both((importer, old), (_, new)) => {
-				(importer, old.dependencies, new.dependencies)
-			}
-			EitherOrBoth::Left((importer, old)) => (importer, old.dependencies, BTreeMap::new()),
-			EitherOrBoth::Right((importer, new)) => (importer, BTreeMap::new(), new.dependencies),
-		});
+type InstallReporters =
+	MultiReporter<CliReporter<DynWriter>, MultiReporter<StageTimer, DeprecationWarnings>>;
+
+/// Records when each install stage starts, for the install report
+#[derive(Debug, Default)]
+struct StageTimer(Mutex<Vec<(InstallStage, Instant)>>);
+
+impl StageTimer {
+	/// The duration of each stage, each lasting until the next one starts or `end`
+	fn durations(&self, end: Instant) -> Vec<(&'static str, Duration)> {
+		let stages = self.0.lock().unwrap();
+
+		stages
+			.iter()
+			.zip(stages.iter().skip(1).map(|(_, start)| *start).chain([end]))
+			.map(|((stage, start), next_start)| {
+				let name = match stage {
+					InstallStage::Resolve => "resolve",
+					InstallStage::Download => "download",
+					_ => "install",
+				};
+
+				(name, next_start.saturating_duration_since(*start))
+			})
+			.collect()
+	}
+}
+
+impl DownloadsReporter for StageTimer {
+	type DownloadProgressReporter = ();
+
+	fn report_download(self: Arc<Self>, _name: String) -> Self::DownloadProgressReporter {}
+}
+
+impl PatchesReporter for StageTimer {
+	type PatchProgressReporter = ();
+
+	fn report_patch(self: Arc<Self>, _name: String) -> Self::PatchProgressReporter {}
+}
+
+impl ResolveReporter for StageTimer {}
+
+impl InstallReporter for StageTimer {
+	fn report_stage(&self, stage: InstallStage) {
+		self.0.lock().unwrap().push((stage, Instant::now()));
+	}
+}
+
+/// Looks up whether the resolved pesde packages are deprecated as soon as the graph is resolved,
+/// warning about each one which is
+struct DeprecationWarnings {
+	project: Project,
+	refreshed_sources: RefreshedSources,
+	multi_progress: MultiProgress,
+	tasks: Mutex<JoinSet<anyhow::Result<()>>>,
+}
+
+impl DeprecationWarnings {
+	/// Waits for the lookups to finish
+	async fn finish(&self) -> anyhow::Result<()> {
+		let mut tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+		while let Some(task) = tasks.join_next().await {
+			task.unwrap()?;
+		}
+
+		Ok(())
+	}
+}
+
+impl DownloadsReporter for DeprecationWarnings {
+	type DownloadProgressReporter = ();
+
+	fn report_download(self: Arc<Self>, _name: String) -> Self::DownloadProgressReporter {}
+}
 
-	for (importer, old, new) in importer_pairs {
-		let mut peer_warnings = vec![];
+impl PatchesReporter for DeprecationWarnings {
+	type PatchProgressReporter = ();
 
-		for (alias, (id, _, _)) in &new {
-			let Some(node) = new_graph.nodes.get(id) else {
+	fn report_patch(self: Arc<Self>, _name: String) -> Self::PatchProgressReporter {}
+}
+
+impl ResolveReporter for DeprecationWarnings {}
+
+impl InstallReporter for DeprecationWarnings {
+	#[expect(deprecated)]
+	fn report_resolved(&self, graph: &DependencyGraph) {
+		let mut tasks = self.tasks.lock().unwrap();
+
+		for id in graph.nodes.keys() {
+			let PackageSources::Pesde(source) = id.source() else {
 				continue;
 			};
+			let PackageRefs::Pesde(pkg_ref) = id.pkg_ref() else {
+				continue;
+			};
+			let source = source.clone();
+			let name = pkg_ref.name.clone();
+			let project = self.project.clone();
+			let refreshed_sources = self.refreshed_sources.clone();
+			let multi_progress = self.multi_progress.clone();
+
+			tasks.spawn(async move {
+				refreshed_sources
+					.refresh(&PackageSources::Pesde(source.clone()), &project)
+					.await
+					.context("failed to refresh source")?;
+
+				let file = source
+					.repo()
+					.read_index_file(&project, name.clone())
+					.await
+					.context("failed to read package index file")?
+					.context("package not found in index")?;
+
+				if !file.meta.deprecated.is_empty() {
+					multi_progress.suspend(|| {
+						println!(
+							"{WARN_PREFIX}: package {name} is deprecated: {}",
+							file.meta.deprecated
+						);
+					});
+				}
+
+				Ok(())
+			});
+		}
+	}
+}
 
-			let mut queue = node
-				.dependencies
-				.iter()
-				.map(|(dep_alias, dep)| (vec![(id, alias)], (&dep.id, dep_alias), dep.ty))
-				.collect::<Vec<_>>();
+#[derive(Debug, Serialize)]
+struct InstallReport<'a> {
+	added: &'a BTreeSet<PackageId>,
+	removed: &'a BTreeSet<PackageId>,
+	/// The duration of each stage in seconds
+	durations: BTreeMap<&'static str, f64>,
+	lockfile_hash: Option<Hash>,
+}
 
-			while let Some((path, (dep_id, dep_alias), dep_ty)) = queue.pop() {
-				if dep_ty == DependencyType::Peer {
-					let mut iter = path
+/// Writes a JSON report of an install to `path`
+async fn write_install_report(
+	path: &Path,
+	diff: &GraphDiff,
+	durations: Vec<(&'static str, Duration)>,
+	lockfile_path: &Path,
+) -> anyhow::Result<()> {
+	let lockfile_hash = match fs::read(lockfile_path).await {
+		Ok(lockfile) => Some(Hash::from_bytes(HashAlgorithm::Sha256, lockfile)),
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+		Err(e) => return Err(e).context("failed to read lockfile"),
+	};
+
+	let report = InstallReport {
+		added: &diff.added,
+		removed: &diff.removed,
+		durations: durations
+			.into_iter()
+			.map(|(stage, duration)| (stage, duration.as_secs_f64()))
+			.collect(),
+		lockfile_hash,
+	};
+
+	fs::write(
+		path,
+		serde_json::to_string_pretty(&report).context("failed to serialize install report")?,
+	)
+	.await
+	.context("failed to write install report")
+}
+
+pub fn missing_peers(
+	nodes: &BTreeMap<PackageId, DependencyGraphNode>,
+	dependencies: &BTreeMap<Alias, (PackageId, DependencySpecifiers, DependencyType)>,
+) -> Vec<String> {
+	let mut missing = vec![];
+
+	for (alias, (id, _, _)) in dependencies {
+		let Some(node) = nodes.get(id) else {
+			continue;
+		};
+
+		let mut queue = node
+			.dependencies
+			.iter()
+			.map(|(dep_alias, dep)| (vec![(id, alias)], (&dep.id, dep_alias), dep.ty))
+			.collect::<Vec<_>>();
+
+		while let Some((path, (dep_id, dep_alias), dep_ty)) = queue.pop() {
+			if dep_ty == DependencyType::Peer {
+				let mut iter = path
+					.iter()
+					.map(|(id, _)| id)
+					.rev()
+					// skip our parent since we're always going to be descendants of it
+					.skip(1)
+					.take(2);
+
+				let satisfied = if iter.len() > 0 {
+					iter.any(|id| nodes[id].dependencies.values().any(|dep| dep.id == *dep_id))
+				} else {
+					dependencies
 						.iter()
-						.map(|(id, _)| id)
-						.rev()
-						// skip our parent since we're always going to be descendants of it
-						.skip(1)
-						.take(2);
-
-					let satisfied = if iter.len() > 0 {
-						iter.any(|id| {
-							new_graph.nodes[id]
-								.dependencies
-								.values()
-								.any(|dep| dep.id == *dep_id)
-						})
-					} else {
-						new.iter().any(|(_, (node_id, _, _))| node_id == dep_id)
-					};
-
-					if !satisfied {
-						peer_warnings.push(
-							style(format!(
-								"missing peer {}>{dep_alias}",
-								path.iter().map(|(_, alias)| alias.as_str()).format(">"),
-							))
-							.red(),
-						);
-					}
+						.any(|(_, (node_id, _, _))| node_id == dep_id)
+				};
+
+				if !satisfied {
+					missing.push(format!(
+						"{}>{dep_alias}",
+						path.iter().map(|(_, alias)| alias.as_str()).format(">"),
+					));
 				}
+			}
 
-				if let Some(dep_node) = new_graph.nodes.get(dep_id) {
-					queue.extend(dep_node.dependencies.iter().map(
-						|(inner_dep_alias, inner_dep)| {
+			if let Some(dep_node) = nodes.get(dep_id) {
+				queue.extend(
+					dep_node
+						.dependencies
+						.iter()
+						.map(|(inner_dep_alias, inner_dep)| {
 							(
 								path.iter()
 									.copied()
@@ -280,37 +466,64 @@ pub fn print_install_summary(old_graph: Option<DependencyGraph>, new_graph: Depe
 								(&inner_dep.id, inner_dep_alias),
 								inner_dep.ty,
 							)
-						},
-					));
-				}
+						}),
+				);
 			}
 		}
+	}
+
+	missing
+}
+
+pub fn print_install_summary(old_graph: Option<DependencyGraph>, new_graph: &DependencyGraph) {
+	let diff = new_graph.diff(&old_graph.unwrap_or_default());
+
+	let importers = new_graph
+		.importers
+		.keys()
+		.chain(diff.importers.keys())
+		.collect::<BTreeSet<_>>();
+
+	for importer in importers {
+		let peer_warnings = new_graph
+			.importers
+			.get(importer)
+			.map(|graph_importer| missing_peers(&new_graph.nodes, &graph_importer.dependencies))
+			.unwrap_or_default()
+			.into_iter()
+			.map(|path| style(format!("missing peer {path}")).red())
+			.collect::<Vec<_>>();
 
 		enum Change {
 			Added,
 			Removed,
 		}
 
-		let groups = new
-			.into_iter()
-			.merge_join_by(
-				old,
-				|(new_alias, (new_id, _, _)), (old_alias, (old_id, _, _))| {
-					new_alias.cmp(old_alias).then(if new_id == old_id {
-						Ordering::Equal
-					} else {
-						Ordering::Less
-					})
-				},
-			)
-			.filter_map(|m| match m {
-				EitherOrBoth::Both(_, _) => None,
-				EitherOrBoth::Left((alias, (id, _, ty))) => Some((ty, (Change::Added, alias, id))),
-				EitherOrBoth::Right((alias, (id, _, ty))) => {
-					Some((ty, (Change::Removed, alias, id)))
-				}
+		let mut changes = diff
+			.importers
+			.get(importer)
+			.map(|diff| {
+				diff.added
+					.iter()
+					.map(|(alias, (id, ty))| (*ty, (Change::Added, alias, id)))
+					.chain(diff.changed.iter().flat_map(
+						|(alias, ((old_id, old_ty), (new_id, new_ty)))| {
+							[
+								(*new_ty, (Change::Added, alias, new_id)),
+								(*old_ty, (Change::Removed, alias, old_id)),
+							]
+						},
+					))
+					.chain(
+						diff.removed
+							.iter()
+							.map(|(alias, (id, ty))| (*ty, (Change::Removed, alias, id))),
+					)
+					.collect::<Vec<_>>()
 			})
-			.into_group_map();
+			.unwrap_or_default();
+		changes.sort_by_key(|(_, (_, alias, _))| *alias);
+		let groups = changes.into_iter().into_group_map();
 
 		if groups.is_empty() && peer_warnings.is_empty() {
 			continue;
@@ -350,3 +563,36 @@ pub fn print_install_summary(old_graph: Option<DependencyGraph>, new_graph: Depe
 		println!();
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn install_report_keys() {
+		let dir = tempfile::tempdir().unwrap();
+		let report_path = dir.path().join("report.json");
+
+		let diff = DependencyGraph::default().diff(&DependencyGraph::default());
+		write_install_report(
+			&report_path,
+			&diff,
+			vec![
+				("resolve", Duration::from_millis(5)),
+				("total", Duration::from_secs(1)),
+			],
+			&dir.path().join(LOCKFILE_FILE_NAME),
+		)
+		.await
+		.unwrap();
+
+		let report = serde_json::from_str::<serde_json::Value>(
+			&fs::read_to_string(&report_path).await.unwrap(),
+		)
+		.unwrap();
+		let keys = report.as_object().unwrap().keys().collect::<Vec<_>>();
+
+		assert_eq!(keys, ["added", "durations", "lockfile_hash", "removed"]);
+		assert_eq!(report["durations"]["total"], 1.0f64);
+	}
+}
@@ -1,3 +1,4 @@
+use crate::cli::config::CliConfig;
 use crate::cli::config::read_config;
 use crate::cli::style::ERROR_STYLE;
 use crate::cli::style::INFO_STYLE;
@@ -5,6 +6,8 @@ use crate::cli::style::WARN_STYLE;
 use anyhow::Context as _;
 use pesde::DEFAULT_INDEX_NAME;
 use pesde::GixUrl;
+use pesde::Importer;
+use pesde::Project;
 use pesde::Subproject;
 use pesde::errors::ManifestReadErrorKind;
 use pesde::manifest::DependencyType;
@@ -28,6 +31,7 @@ use pesde::source::pesde::target::TargetKind;
 use pesde::source::wally::WallyPackageSource;
 use pesde::source::wally::specifier::WallyDependencySpecifier;
 use semver::VersionReq;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::LazyLock;
@@ -87,31 +91,32 @@ enum AnyPackageIdentifier {
 }
 
 impl AnyPackageIdentifier {
+	#[expect(deprecated)]
 	async fn source_and_specifier(
 		&self,
 		realm: Option<Realm>,
-		get_index: impl AsyncFnOnce(bool) -> anyhow::Result<(String, GixUrl)>,
+		get_index: impl AsyncFnOnce(Option<TargetKind>) -> anyhow::Result<(String, GixUrl)>,
 	) -> anyhow::Result<(PackageSources, DependencySpecifiers)> {
 		Ok(match self {
-			#[expect(deprecated)]
 			AnyPackageIdentifier::PesdePackageName(VersionedPackageName(name, version)) => {
-				let (index_name, index_url) = get_index(true).await?;
+				let target = match realm {
+					Some(Realm::Shared) => TargetKind::Roblox,
+					Some(Realm::Server) => TargetKind::RobloxServer,
+					None => TargetKind::Luau,
+				};
+				let (index_name, index_url) = get_index(Some(target)).await?;
 				let source = PackageSources::Pesde(PesdePackageSource::from_url(index_url));
 				let specifier = DependencySpecifiers::Pesde(PesdeDependencySpecifier {
 					name: name.clone(),
 					version: version.clone().unwrap_or(VersionReq::STAR),
 					index: index_name,
-					target: match realm {
-						Some(Realm::Shared) => TargetKind::Roblox,
-						Some(Realm::Server) => TargetKind::RobloxServer,
-						None => TargetKind::Luau,
-					},
+					target,
 				});
 
 				(source, specifier)
 			}
 			AnyPackageIdentifier::WallyPackageName(VersionedPackageName(name, version)) => {
-				let (index_name, index_url) = get_index(false).await?;
+				let (index_name, index_url) = get_index(None).await?;
 				let source = PackageSources::Wally(WallyPackageSource::from_url(index_url));
 				let specifier = DependencySpecifiers::Wally(WallyDependencySpecifier {
 					name: name.clone(),
@@ -135,6 +140,7 @@ impl AnyPackageIdentifier {
 				PackageSources::Path(PathPackageSource),
 				DependencySpecifiers::Path(PathDependencySpecifier {
 					path: path.clone(),
+					workspace: false,
 					realm,
 				}),
 			),
@@ -156,9 +162,14 @@ impl FromStr for AnyPackageIdentifier {
 			Ok(AnyPackageIdentifier::Git((repo.parse()?, rev.to_string())))
 		} else if let Some(name) = s
 			.strip_prefix("wally#")
-			// pesde names cannot contain `-`, so if the string contains it we can assume it's a wally name
-			.or_else(|| s.contains('-').then_some(s))
-		{
+			// pesde names cannot contain `-`, so if the name contains it we can assume it's a wally name.
+			// only the name is checked, as prerelease versions contain it too
+			.or_else(|| {
+				s.split_once('@')
+					.map_or(s, |(name, _)| name)
+					.contains('-')
+					.then_some(s)
+			}) {
 			Ok(AnyPackageIdentifier::WallyPackageName(name.parse()?))
 		} else {
 			Ok(AnyPackageIdentifier::PesdePackageName(s.parse()?))
@@ -200,6 +211,29 @@ pub fn display_err(result: anyhow::Result<()>, prefix: &str) {
 	}
 }
 
+/// Applies the `workspace.dependencies_dir` of the project's root manifest, if there is one
+pub async fn with_manifest_dependencies_dir(project: Project) -> anyhow::Result<Project> {
+	let manifest = match project
+		.clone()
+		.subproject(Importer::root())
+		.deser_manifest()
+		.await
+	{
+		Ok(manifest) => manifest,
+		Err(e) => match e.into_inner() {
+			ManifestReadErrorKind::Io(e) if e.kind() == std::io::ErrorKind::NotFound => {
+				return Ok(project);
+			}
+			e => return Err(e).context("failed to read manifest"),
+		},
+	};
+
+	Ok(match &manifest.workspace.dependencies_dir {
+		Some(dependencies_dir) => project.with_dependencies_dir(dependencies_dir.clone()),
+		None => project,
+	})
+}
+
 pub async fn get_index(subproject: &Subproject, index: Option<&str>) -> anyhow::Result<GixUrl> {
 	let manifest = match subproject.deser_manifest().await {
 		Ok(manifest) => Some(manifest),
@@ -208,36 +242,48 @@ pub async fn get_index(subproject: &Subproject, index: Option<&str>) -> anyhow::
 			e => return Err(e.into()),
 		},
 	};
+	let config = read_config().await?;
 
-	let index_url = match index {
-		Some(index) => index.parse().ok(),
-		None => match manifest {
-			Some(_) => None,
-			None => Some(read_config().await?.default_index),
-		},
+	let Some(index) = index else {
+		return match manifest {
+			Some(manifest) => manifest
+				.indices
+				.pesde
+				.get(DEFAULT_INDEX_NAME)
+				.with_context(|| format!("index {DEFAULT_INDEX_NAME} not found in manifest"))
+				.cloned(),
+			None => config.resolved_default_index(),
+		};
 	};
 
-	if let Some(url) = index_url {
-		return Ok(url);
-	}
+	lookup_index(
+		manifest.as_ref().map(|manifest| &manifest.indices.pesde),
+		&config,
+		index,
+	)
+}
 
-	let index_name = index.unwrap_or(DEFAULT_INDEX_NAME);
+/// Looks up an index by name in the manifest's indices, then in the config's, and otherwise parses it as a URL
+fn lookup_index(
+	manifest_indices: Option<&BTreeMap<String, GixUrl>>,
+	config: &CliConfig,
+	index: &str,
+) -> anyhow::Result<GixUrl> {
+	// names are looked up first, as any name would also be parsed as the URL of a local path
+	if let Some(url) = manifest_indices
+		.and_then(|indices| indices.get(index))
+		.or_else(|| config.indices.get(index))
+	{
+		return Ok(url.clone());
+	}
 
-	manifest
-		.unwrap()
-		.indices
-		.pesde
-		.get(index_name)
-		.with_context(|| format!("index {index_name} not found in manifest"))
-		.cloned()
+	index
+		.parse()
+		.with_context(|| format!("index {index} not found and is not a valid URL"))
 }
 
 pub fn dep_type_to_key(dep_type: DependencyType) -> &'static str {
-	match dep_type {
-		DependencyType::Standard => "dependencies",
-		DependencyType::Dev => "dev_dependencies",
-		DependencyType::Peer => "peer_dependencies",
-	}
+	dep_type.manifest_key()
 }
 
 pub static GITHUB_URL: LazyLock<GixUrl> = LazyLock::new(|| "https://github.com".parse().unwrap());
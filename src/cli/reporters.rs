@@ -1,5 +1,6 @@
 //! Progress reporters for the CLI
 
+use std::collections::HashMap;
 use std::future::Future;
 use std::io::Stdout;
 use std::io::Write;
@@ -9,13 +10,21 @@ use std::sync::Once;
 use std::sync::OnceLock;
 use std::time::Duration;
 
+use clap::ValueEnum;
+use console::Term;
 use indicatif::MultiProgress;
 use indicatif::ProgressBar;
+use indicatif::ProgressDrawTarget;
 use indicatif::ProgressStyle;
+use pesde::GixUrl;
 use pesde::reporters::DownloadProgressReporter;
 use pesde::reporters::DownloadsReporter;
+use pesde::reporters::InstallReporter;
+use pesde::reporters::InstallStage;
 use pesde::reporters::PatchProgressReporter;
 use pesde::reporters::PatchesReporter;
+use pesde::reporters::RefreshReporter;
+use pesde::reporters::ResolveReporter;
 
 pub const TICK_CHARS: &str = "⣷⣯⣟⡿⢿⣻⣽⣾";
 
@@ -72,6 +81,62 @@ where
 	run_with_reporter_and_writer(std::io::stdout(), f).await
 }
 
+/// How progress is reported
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum ReporterKind {
+	/// Progress bars if the output is a terminal, otherwise plain
+	#[default]
+	Auto,
+	/// Progress bars, even if the output is not a terminal
+	Bars,
+	/// A line for each finished download or patch, suitable for CI logs
+	Plain,
+	/// No progress output
+	None,
+}
+
+pub type DynWriter = Box<dyn Write + Send + Sync>;
+
+pub async fn run_with_reporter_kind<F, R, Fut>(kind: ReporterKind, f: F) -> R
+where
+	F: FnOnce(MultiProgress, ProgressBar, Arc<CliReporter<DynWriter>>) -> Fut,
+	Fut: Future<Output = R>,
+{
+	run_with_reporter_kind_and_writer(kind, Box::new(std::io::stdout()), f).await
+}
+
+async fn run_with_reporter_kind_and_writer<F, R, Fut>(
+	kind: ReporterKind,
+	writer: DynWriter,
+	f: F,
+) -> R
+where
+	F: FnOnce(MultiProgress, ProgressBar, Arc<CliReporter<DynWriter>>) -> Fut,
+	Fut: Future<Output = R>,
+{
+	let writer: DynWriter = match kind {
+		ReporterKind::None => Box::new(std::io::sink()),
+		_ => writer,
+	};
+
+	run_with_reporter_and_writer(writer, |multi_progress, root_progress, reporter| {
+		match kind {
+			ReporterKind::Auto => {}
+			ReporterKind::Bars => {
+				multi_progress
+					.set_draw_target(ProgressDrawTarget::term_like(Box::new(Term::stderr())));
+			}
+			// bars which are hidden make the reporter print a line per finished step instead
+			ReporterKind::Plain | ReporterKind::None => {
+				multi_progress.set_draw_target(ProgressDrawTarget::hidden());
+			}
+		}
+
+		f(multi_progress, root_progress, reporter)
+	})
+	.await
+}
+
 pub struct CliReporter<W = Stdout> {
 	writer: Mutex<W>,
 	child_style: ProgressStyle,
@@ -215,3 +280,106 @@ impl<W: Write + Send + Sync + 'static> PatchProgressReporter for CliPatchProgres
 		self.root_reporter.root_progress.inc(1);
 	}
 }
+
+impl<W: Write + Send + Sync + 'static> ResolveReporter for CliReporter<W> {
+	fn report_resolve_progress(&self, resolved: usize, total: usize) {
+		self.root_progress.set_length(total as u64);
+		self.root_progress.set_position(resolved as u64);
+	}
+}
+
+impl<W: Write + Send + Sync + 'static> InstallReporter for CliReporter<W> {
+	fn report_stage(&self, stage: InstallStage) {
+		self.root_progress.reset();
+		self.root_progress.set_length(0);
+		self.root_progress
+			.set_style(root_progress_style_with_progress());
+		self.root_progress.set_message(match stage {
+			InstallStage::Resolve => "resolve",
+			InstallStage::Download => "download",
+			_ => "install",
+		});
+	}
+}
+
+/// Shows the progress of each Git index being fetched, below the progress bars of the current
+/// command if there are any
+#[derive(Debug, Default)]
+pub struct CliRefreshReporter {
+	progress: Mutex<HashMap<GixUrl, ProgressBar>>,
+}
+
+impl RefreshReporter for CliRefreshReporter {
+	#[allow(unknown_lints, clippy::literal_string_with_formatting_args)]
+	fn report_refresh_progress(&self, url: &GixUrl, objects: usize, bytes: usize) {
+		let mut progress = self.progress.lock().unwrap();
+		let progress = progress.entry(url.clone()).or_insert_with(|| {
+			let progress = ProgressBar::new(0);
+			let progress = match &*crate::PROGRESS_BARS.lock().unwrap() {
+				Some(multi_progress) => multi_progress.add(progress),
+				None => progress,
+			};
+			progress.set_style(ProgressStyle::with_template("{msg:.dim} {bytes:.dim}").unwrap());
+			progress
+		});
+
+		progress.set_message(format!("- fetching {url} ({objects} objects)"));
+		progress.set_position(bytes as u64);
+	}
+
+	fn report_refresh_done(&self, url: &GixUrl) {
+		let progress = self.progress.lock().unwrap().remove(url);
+		if let Some(progress) = progress {
+			progress.finish_and_clear();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A writer whose output can be read while the reporter holds it
+	#[derive(Clone, Default)]
+	struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+	impl Write for SharedWriter {
+		fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+			self.0.lock().unwrap().write(buf)
+		}
+
+		fn flush(&mut self) -> std::io::Result<()> {
+			Ok(())
+		}
+	}
+
+	async fn lines_written(kind: ReporterKind) -> String {
+		let writer = SharedWriter::default();
+
+		run_with_reporter_kind_and_writer(
+			kind,
+			Box::new(writer.clone()),
+			async |_, _, reporter| {
+				let download = reporter.clone().report_download("acme/foo".to_string());
+				download.report_start();
+				download.report_done();
+
+				reporter.report_patch("acme/bar".to_string()).report_done();
+			},
+		)
+		.await;
+
+		String::from_utf8(writer.0.lock().unwrap().clone()).unwrap()
+	}
+
+	#[tokio::test]
+	async fn reporter_kinds() {
+		assert_eq!(
+			lines_written(ReporterKind::Plain).await,
+			"downloaded acme/foo\npatched acme/bar\n"
+		);
+		assert_eq!(lines_written(ReporterKind::None).await, "");
+		// the progress bars show the same progress instead
+		assert_eq!(lines_written(ReporterKind::Bars).await, "");
+	}
+}
@@ -8,8 +8,12 @@ use crate::Subproject;
 use crate::graph::DependencyGraph;
 use crate::graph::DependencyGraphNode;
 use crate::linking::generator::get_file_types;
+use crate::lockfile::Lockfile;
+use crate::manifest::Alias;
 use crate::manifest::DependencyType;
 use crate::reporters::DownloadsReporter;
+use crate::reporters::InstallReporter;
+use crate::reporters::InstallStage;
 use crate::reporters::PatchesReporter;
 use crate::source::PackageExports;
 use crate::source::PackageSource as _;
@@ -18,6 +22,8 @@ use crate::source::ids::PackageId;
 use fs_err::tokio as fs;
 use futures::TryStreamExt as _;
 
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::num::NonZeroUsize;
@@ -64,6 +70,8 @@ pub struct DownloadAndLinkOptions<Reporter = ()> {
 	pub network_concurrency: NonZeroUsize,
 	/// Whether to re-install all dependencies even if they are already installed
 	pub force: bool,
+	/// Whether to remove packages which are no longer used
+	pub remove_unused: bool,
 }
 
 impl<Reporter> Default for DownloadAndLinkOptions<Reporter>
@@ -77,6 +85,7 @@ where
 			install_dependencies_mode: InstallDependenciesMode::All,
 			network_concurrency: NonZeroUsize::new(16).unwrap(),
 			force: false,
+			remove_unused: true,
 		}
 	}
 }
@@ -128,6 +137,13 @@ where
 		self.force = force;
 		self
 	}
+
+	/// Sets whether to remove packages which are no longer used
+	#[must_use]
+	pub fn remove_unused(mut self, remove_unused: bool) -> Self {
+		self.remove_unused = remove_unused;
+		self
+	}
 }
 
 impl Clone for DownloadAndLinkOptions {
@@ -138,11 +154,200 @@ impl Clone for DownloadAndLinkOptions {
 			install_dependencies_mode: self.install_dependencies_mode,
 			network_concurrency: self.network_concurrency,
 			force: self.force,
+			remove_unused: self.remove_unused,
 		}
 	}
 }
 
+/// Options for installing a project.
+#[derive(Debug)]
+pub struct InstallOptions<Reporter = ()> {
+	/// The options for downloading and linking, which also hold the reporter.
+	pub download_and_link: DownloadAndLinkOptions<Reporter>,
+	/// Whether to error if the lockfile is out of date.
+	pub locked: bool,
+	/// Whether to use the lockfile to resolve dependencies.
+	pub use_lockfile: bool,
+	/// Whether to download and link the dependencies.
+	pub link: bool,
+	/// Whether to write the lockfile.
+	pub write_lockfile: bool,
+	/// The direct dependencies of an importer to download and link, instead of all of them.
+	/// The lockfile still contains the whole graph, and other packages are left untouched
+	pub only: Option<(Importer, BTreeSet<Alias>)>,
+}
+
+impl<Reporter> Default for InstallOptions<Reporter>
+where
+	Reporter: InstallReporter + 'static,
+{
+	fn default() -> Self {
+		Self {
+			download_and_link: Default::default(),
+			locked: false,
+			use_lockfile: true,
+			link: true,
+			write_lockfile: true,
+			only: None,
+		}
+	}
+}
+
+impl<Reporter> InstallOptions<Reporter>
+where
+	Reporter: InstallReporter + 'static,
+{
+	/// Creates a new install options.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the options for downloading and linking.
+	#[must_use]
+	pub fn download_and_link(
+		mut self,
+		download_and_link: DownloadAndLinkOptions<Reporter>,
+	) -> Self {
+		self.download_and_link = download_and_link;
+		self
+	}
+
+	/// Sets whether to error if the lockfile is out of date.
+	#[must_use]
+	pub fn locked(mut self, locked: bool) -> Self {
+		self.locked = locked;
+		self
+	}
+
+	/// Sets whether to use the lockfile to resolve dependencies.
+	#[must_use]
+	pub fn use_lockfile(mut self, use_lockfile: bool) -> Self {
+		self.use_lockfile = use_lockfile;
+		self
+	}
+
+	/// Sets whether to download and link the dependencies.
+	#[must_use]
+	pub fn link(mut self, link: bool) -> Self {
+		self.link = link;
+		self
+	}
+
+	/// Sets whether to write the lockfile.
+	#[must_use]
+	pub fn write_lockfile(mut self, write_lockfile: bool) -> Self {
+		self.write_lockfile = write_lockfile;
+		self
+	}
+
+	/// Sets the direct dependencies of an importer to download and link, instead of all of them
+	#[must_use]
+	pub fn only(mut self, importer: Importer, aliases: BTreeSet<Alias>) -> Self {
+		self.only = Some((importer, aliases));
+		self
+	}
+}
+
 impl Project {
+	/// Resolves the dependencies of the project, downloads and links them, and
+	/// writes the lockfile. Returns the graph from the previous lockfile, if any,
+	/// along with the new graph
+	#[instrument(
+		skip_all,
+		fields(locked = options.locked, use_lockfile = options.use_lockfile),
+		level = "debug"
+	)]
+	pub async fn install<Reporter>(
+		&self,
+		options: InstallOptions<Reporter>,
+	) -> Result<(Option<DependencyGraph>, DependencyGraph), errors::InstallError>
+	where
+		Reporter: InstallReporter + 'static,
+	{
+		let InstallOptions {
+			download_and_link,
+			locked,
+			use_lockfile,
+			link,
+			write_lockfile,
+			only,
+		} = options;
+
+		let report_stage = |stage| {
+			if let Some(reporter) = &download_and_link.reporter {
+				reporter.report_stage(stage);
+			}
+		};
+
+		report_stage(InstallStage::Resolve);
+
+		// the lockfile is always read so that the caller can compare the graphs,
+		// but it is only used for resolution if `use_lockfile` is set
+		let old_graph = match self.graph_from_lockfile().await {
+			Ok(graph) => graph,
+			Err(_) if !use_lockfile => None,
+			Err(e) => return Err(e.into()),
+		};
+		let previous_graph = old_graph.as_ref().filter(|_| use_lockfile);
+		let refreshed_sources = &download_and_link.refreshed_sources;
+
+		let (graph, updated) = match &download_and_link.reporter {
+			Some(reporter) => {
+				self.dependency_graph(previous_graph, refreshed_sources, false, reporter.as_ref())
+					.await?
+			}
+			None => {
+				self.dependency_graph(previous_graph, refreshed_sources, false, &())
+					.await?
+			}
+		};
+
+		if updated && locked {
+			return Err(errors::InstallErrorKind::LockfileOutOfSync.into());
+		}
+
+		if let Some(reporter) = &download_and_link.reporter {
+			reporter.report_resolved(&graph);
+		}
+
+		if link {
+			report_stage(InstallStage::Download);
+
+			match only {
+				Some((importer, aliases)) => {
+					let dependencies = graph
+						.importers
+						.get(&importer)
+						.map(|graph_importer| &graph_importer.dependencies);
+					if let Some(alias) = aliases.iter().find(|alias| {
+						!dependencies.is_some_and(|dependencies| dependencies.contains_key(*alias))
+					}) {
+						return Err(errors::InstallErrorKind::AliasNotFound(alias.clone()).into());
+					}
+
+					// packages outside of the subgraph are still in use
+					self.download_and_link(
+						&graph.subgraph(&importer, &aliases),
+						download_and_link.remove_unused(false),
+					)
+					.await?;
+				}
+				None => {
+					self.download_and_link(&graph, download_and_link).await?;
+				}
+			}
+		}
+
+		let lockfile = Lockfile { graph };
+
+		if write_lockfile {
+			self.write_lockfile(&lockfile).await?;
+		}
+
+		Ok((old_graph, lockfile.graph))
+	}
+
 	/// Downloads a graph of dependencies and links them in the correct order
 	#[instrument(
 		skip_all,
@@ -163,6 +368,7 @@ impl Project {
 			install_dependencies_mode,
 			network_concurrency,
 			force,
+			remove_unused,
 		} = options;
 
 		if force {
@@ -321,24 +527,37 @@ impl Project {
 
 			#[cfg(feature = "patches")]
 			{
+				use crate::manifest::PatchKey;
 				use crate::patches::apply_patch;
-				let mut tasks = self
+
+				let patches = self
 					.clone()
 					.subproject(Importer::root())
 					.deser_manifest()
 					.await?
 					.workspace
 					.patches
-					.iter()
-					.filter_map(|(id, patch_path)| {
-						downloaded_packages.get(id).map(|importers| {
-							(
-								id,
-								importers,
-								Arc::<Path>::from(patch_path.to_path(self.dir())),
+					.clone();
+
+				let mut package_patches = BTreeMap::<&PackageId, (&PatchKey, Arc<Path>)>::new();
+				for (key, patch_path) in &patches {
+					for id in downloaded_packages.keys().filter(|id| key.matches(id)) {
+						if let Some((other, _)) = package_patches
+							.insert(id, (key, Arc::<Path>::from(patch_path.to_path(self.dir()))))
+						{
+							return Err(errors::DownloadAndLinkErrorKind::ConflictingPatches(
+								id.clone(),
+								other.to_string(),
+								key.to_string(),
 							)
-						})
-					})
+							.into());
+						}
+					}
+				}
+
+				let mut tasks = package_patches
+					.into_iter()
+					.map(|(id, (_, patch_path))| (id, &downloaded_packages[id], patch_path))
 					.flat_map(|(id, importers, patch_path)| {
 						importers
 							.iter()
@@ -351,7 +570,7 @@ impl Project {
 							container_dir(&self.clone().subproject(importer.clone()), &id);
 
 						async move {
-							match reporter {
+							let result = match reporter {
 								Some(reporter) => {
 									apply_patch(&id, container_dir, &patch_path, reporter.clone())
 										.await
@@ -359,7 +578,13 @@ impl Project {
 								None => {
 									apply_patch(&id, container_dir, &patch_path, ().into()).await
 								}
-							}
+							};
+
+							result.map_err(|e| {
+								errors::DownloadAndLinkError::from(
+									errors::DownloadAndLinkErrorKind::Patch(id, e),
+								)
+							})
 						}
 					})
 					.collect::<JoinSet<_>>();
@@ -487,7 +712,9 @@ impl Project {
 			.instrument(tracing::debug_span!("link (all)"))
 			.await?;
 
-		if matches!(install_dependencies_mode, InstallDependenciesMode::Prod) || !force {
+		if remove_unused
+			&& (matches!(install_dependencies_mode, InstallDependenciesMode::Prod) || !force)
+		{
 			self.remove_unused(graph).await?;
 		}
 
@@ -534,8 +761,13 @@ pub mod errors {
 
 		/// Patching a package failed
 		#[cfg(feature = "patches")]
-		#[error("error applying patch")]
-		Patch(#[from] crate::patches::errors::ApplyPatchError),
+		#[error("error applying patch to `{0}`")]
+		Patch(PackageId, #[source] crate::patches::errors::ApplyPatchError),
+
+		/// A package is matched by more than one patch
+		#[cfg(feature = "patches")]
+		#[error("package `{0}` is matched by both patch `{1}` and patch `{2}`")]
+		ConflictingPatches(PackageId, String, String),
 
 		/// The library file was not found
 		#[error("library file at `{0}` not found")]
@@ -545,4 +777,261 @@ pub mod errors {
 		#[error("invalid checksum for `{0}`")]
 		BadChecksum(PackageId),
 	}
+
+	/// An error that can occur when installing a project
+	#[derive(Debug, Error, thiserror_ext::Box)]
+	#[thiserror_ext(newtype(name = InstallError))]
+	#[non_exhaustive]
+	pub enum InstallErrorKind {
+		/// Reading the lockfile failed
+		#[error("error reading lockfile")]
+		LockfileRead(#[from] crate::errors::LockfileReadError),
+
+		/// Resolving the dependency graph failed
+		#[error("error building dependency graph")]
+		DependencyGraph(#[from] crate::resolver::errors::DependencyGraphError),
+
+		/// The lockfile is out of sync with the manifest
+		#[error("lockfile is out of sync")]
+		LockfileOutOfSync,
+
+		/// A dependency to install was not found
+		#[error("dependency `{0}` not found")]
+		AliasNotFound(crate::manifest::Alias),
+
+		/// Downloading and linking the dependencies failed
+		#[error("error downloading and linking dependencies")]
+		DownloadAndLink(#[from] DownloadAndLinkError),
+
+		/// Writing the lockfile failed
+		#[error("error writing lockfile")]
+		LockfileWrite(#[from] crate::errors::LockfileWriteError),
+	}
+}
+
+#[cfg(all(test, feature = "patches"))]
+mod tests {
+	use crate::Importer;
+	use crate::MANIFEST_FILE_NAME;
+	use crate::PACKAGES_CONTAINER_NAME;
+	use crate::RefreshedSources;
+	use crate::graph::DependencyGraphNode;
+	use crate::source::RealmExt as _;
+	use crate::source::local_index::LocalIndex;
+	use fs_err::tokio as fs;
+	use std::fmt::Write as _;
+	use std::path::Path;
+
+	/// Creates a project depending on the versions of `acme/lib` published with the given contents,
+	/// with the given `[workspace.patches]` entries. `{url}` in them is replaced by the index URL
+	async fn patched_project(
+		dir: &Path,
+		versions: &[(&str, &str)],
+		patches: &str,
+	) -> crate::Project {
+		let mut index = LocalIndex::new(&dir.join("index"));
+		for (version, contents) in versions {
+			index
+				.publish(
+					"acme/lib",
+					version,
+					"{ environment = \"luau\", lib = \"init.luau\" }",
+					&[("init.luau", contents)],
+				)
+				.await;
+		}
+
+		let project_dir = dir.join("project");
+		fs::create_dir_all(project_dir.join("patches"))
+			.await
+			.unwrap();
+		fs::write(
+			project_dir.join("patches").join("lib.patch"),
+			"diff --git a/init.luau b/init.luau\n--- a/init.luau\n+++ b/init.luau\n@@ -1 +1 @@\n-return 1\n+return 2\n",
+		)
+		.await
+		.unwrap();
+
+		let mut manifest = format!(
+			"[indices]\ndefault = \"{}\"\n\n[workspace.patches]\n{}\n[dependencies]\n",
+			index.url(),
+			patches.replace("{url}", &index.url())
+		);
+		for (alias, (version, _)) in ('a'..).zip(versions) {
+			_ = writeln!(
+				manifest,
+				"{alias} = {{ name = \"acme/lib\", version = \"={version}\", target = \"luau\" }}"
+			);
+		}
+		fs::write(project_dir.join(MANIFEST_FILE_NAME), manifest)
+			.await
+			.unwrap();
+
+		crate::tests::test_project(dir, &project_dir)
+	}
+
+	#[tokio::test]
+	async fn patch_version_range() {
+		let dir = tempfile::tempdir().unwrap();
+		let project = patched_project(
+			dir.path(),
+			&[
+				("1.0.0", "return 1\n"),
+				("1.1.0", "return 1\n"),
+				("2.0.0", "return 1\n"),
+			],
+			"\"pesde:{url}:acme/lib+luau@>=1.0.0, <2\" = \"patches/lib.patch\"\n",
+		)
+		.await;
+
+		let graph = project
+			.resolve_lockfile(false, &RefreshedSources::new())
+			.await
+			.unwrap()
+			.graph;
+		project
+			.download_and_link(&graph, super::DownloadAndLinkOptions::<()>::new())
+			.await
+			.unwrap();
+
+		let contents = async |alias: &str| {
+			let (id, _, _) =
+				&graph.importers[&Importer::root()].dependencies[&alias.parse().unwrap()];
+			let path = project
+				.clone()
+				.subproject(Importer::root())
+				.dependencies_dir()
+				.join(graph.realm_of(&Importer::root(), id).packages_dir())
+				.join(PACKAGES_CONTAINER_NAME)
+				.join(DependencyGraphNode::container_dir(
+					id,
+					&graph.nodes[id].structure_kind,
+				))
+				.join("init.luau");
+			fs::read_to_string(path).await.unwrap()
+		};
+
+		// both versions in the range are patched, and the one outside it, which shares its file in
+		// the CAS with them, is left alone
+		assert_eq!(contents("a").await, "return 2\n");
+		assert_eq!(contents("b").await, "return 2\n");
+		assert_eq!(contents("c").await, "return 1\n");
+	}
+
+	#[tokio::test]
+	async fn conflicting_patches() {
+		let dir = tempfile::tempdir().unwrap();
+		let project = patched_project(
+			dir.path(),
+			&[("1.0.0", "return 1\n")],
+			"\"pesde:{url}:acme/lib+luau@^1\" = \"patches/lib.patch\"\n\"pesde:{url}:acme/lib+luau@1.0.0\" = \"patches/lib.patch\"\n",
+		)
+		.await;
+
+		let graph = project
+			.resolve_lockfile(false, &RefreshedSources::new())
+			.await
+			.unwrap()
+			.graph;
+		let err = project
+			.download_and_link(&graph, super::DownloadAndLinkOptions::<()>::new())
+			.await
+			.unwrap_err();
+
+		let super::errors::DownloadAndLinkErrorKind::ConflictingPatches(id, first, second) =
+			err.inner()
+		else {
+			panic!("expected conflicting patches, got {err:?}");
+		};
+		assert_eq!(id.version().to_string(), "1.0.0");
+		assert!(first.ends_with("@1.0.0"), "{first}");
+		assert!(second.ends_with("@^1"), "{second}");
+	}
+
+	#[tokio::test]
+	async fn patch_version_range_names_failing_version() {
+		let dir = tempfile::tempdir().unwrap();
+		let project = patched_project(
+			dir.path(),
+			&[("1.0.0", "return 1\n"), ("1.1.0", "return 3\n")],
+			"\"pesde:{url}:acme/lib+luau@^1\" = \"patches/lib.patch\"\n",
+		)
+		.await;
+
+		let graph = project
+			.resolve_lockfile(false, &RefreshedSources::new())
+			.await
+			.unwrap()
+			.graph;
+		let err = project
+			.download_and_link(&graph, super::DownloadAndLinkOptions::<()>::new())
+			.await
+			.unwrap_err();
+
+		let super::errors::DownloadAndLinkErrorKind::Patch(id, _) = err.inner() else {
+			panic!("expected a patch error, got {err:?}");
+		};
+		assert_eq!(id.version().to_string(), "1.1.0");
+		assert!(err.to_string().contains("@1.1.0"), "{err}");
+	}
+
+	#[tokio::test]
+	async fn install_only_subtree() {
+		let dir = tempfile::tempdir().unwrap();
+		let mut index = LocalIndex::new(&dir.path().join("index"));
+		for name in ["acme/foo", "acme/bar"] {
+			index
+				.publish(
+					name,
+					"1.0.0",
+					"{ environment = \"luau\", lib = \"init.luau\" }",
+					&[("init.luau", "return {}")],
+				)
+				.await;
+		}
+
+		let project_dir = dir.path().join("project");
+		fs::create_dir_all(&project_dir).await.unwrap();
+		fs::write(
+			project_dir.join(MANIFEST_FILE_NAME),
+			format!(
+				"[indices]\ndefault = \"{}\"\n\n[dependencies]\nfoo = {{ name = \"acme/foo\", version = \"^1.0.0\", target = \"luau\" }}\nbar = {{ name = \"acme/bar\", version = \"^1.0.0\", target = \"luau\" }}\n",
+				index.url()
+			),
+		)
+		.await
+		.unwrap();
+		let project = crate::tests::test_project(dir.path(), &project_dir);
+
+		let only_foo = || {
+			super::InstallOptions::<()>::new()
+				.only(Importer::root(), ["foo".parse().unwrap()].into())
+		};
+		let packages_dir = project
+			.clone()
+			.subproject(Importer::root())
+			.dependencies_dir()
+			.join(None.packages_dir());
+		let linker = |alias: &str| packages_dir.join(format!("{alias}.luau"));
+
+		// the lockfile still covers every dependency, but only foo is installed
+		project.install(only_foo()).await.unwrap();
+		let graph = project.graph_from_lockfile().await.unwrap().unwrap();
+		assert_eq!(graph.importers[&Importer::root()].dependencies.len(), 2);
+		assert!(fs::metadata(linker("foo")).await.is_ok());
+		assert!(fs::metadata(linker("bar")).await.is_err());
+
+		// an installed sibling is left alone
+		project
+			.install(super::InstallOptions::<()>::new())
+			.await
+			.unwrap();
+		fs::write(linker("bar"), "-- untouched").await.unwrap();
+		project.install(only_foo()).await.unwrap();
+		assert!(fs::metadata(linker("foo")).await.is_ok());
+		assert_eq!(
+			fs::read_to_string(linker("bar")).await.unwrap(),
+			"-- untouched"
+		);
+	}
 }
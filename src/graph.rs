@@ -1,6 +1,8 @@
 //! The dependency graph
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashSet;
+use std::collections::VecDeque;
 
 use serde::Deserialize;
 use serde::Serialize;
@@ -12,10 +14,12 @@ use crate::manifest::Alias;
 use crate::manifest::DependencyType;
 use crate::source::DependencySpecifier as _;
 use crate::source::DependencySpecifiers;
+use crate::source::PackageRefs;
 use crate::source::Realm;
 use crate::source::ResolvedPackage;
 use crate::source::StructureKind;
 use crate::source::ids::PackageId;
+use relative_path::RelativePathBuf;
 
 /// A dependency graph importer
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -103,8 +107,61 @@ pub struct DependencyGraphNode {
 	pub structure_kind: StructureKind,
 }
 
+/// The changes to the direct dependencies of an importer between two graphs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImporterDiff {
+	/// The dependencies which were added
+	pub added: BTreeMap<Alias, (PackageId, DependencyType)>,
+	/// The dependencies which were removed
+	pub removed: BTreeMap<Alias, (PackageId, DependencyType)>,
+	/// The dependencies which now resolve to a different package, as (old, new)
+	pub changed: BTreeMap<Alias, ((PackageId, DependencyType), (PackageId, DependencyType))>,
+}
+
+impl ImporterDiff {
+	/// Whether there are no changes
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+	}
+}
+
+/// The differences between two dependency graphs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphDiff {
+	/// The changes to the direct dependencies of each importer which has any
+	pub importers: BTreeMap<Importer, ImporterDiff>,
+	/// The packages, direct or transitive, which are only in the new graph
+	pub added: BTreeSet<PackageId>,
+	/// The packages, direct or transitive, which are only in the old graph
+	pub removed: BTreeSet<PackageId>,
+}
+
+impl GraphDiff {
+	/// Whether the packages in `added` or `removed` are a direct dependency of any importer
+	#[must_use]
+	pub fn is_direct(&self, id: &PackageId) -> bool {
+		self.importers.values().any(|diff| {
+			diff.added
+				.values()
+				.chain(diff.removed.values())
+				.chain(diff.changed.values().flat_map(|(old, new)| [old, new]))
+				.any(|(dep_id, _)| dep_id == id)
+		})
+	}
+}
+
+/// A path from an importer through the dependency graph
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DependencyPath {
+	/// The importer the path starts at
+	pub importer: Importer,
+	/// The aliases and IDs of the packages along the path, starting with a direct dependency of the importer
+	pub packages: Vec<(Alias, PackageId)>,
+}
+
 /// A graph of dependencies in a project
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct DependencyGraph {
 	/// The importers in the graph
 	pub importers: BTreeMap<Importer, DependencyGraphImporter>,
@@ -171,6 +228,189 @@ impl DependencyGraph {
 		ret
 	}
 
+	/// Returns the part of the graph needed by the given direct dependencies of an importer,
+	/// containing only that importer, those dependencies and the packages they depend on
+	#[must_use]
+	pub fn subgraph(&self, importer: &Importer, aliases: &BTreeSet<Alias>) -> DependencyGraph {
+		let dependencies = self
+			.importers
+			.get(importer)
+			.map(|graph_importer| {
+				graph_importer
+					.dependencies
+					.iter()
+					.filter(|(alias, _)| aliases.contains(*alias))
+					.map(|(alias, dep)| (alias.clone(), dep.clone()))
+					.collect::<BTreeMap<_, _>>()
+			})
+			.unwrap_or_default();
+
+		let mut nodes = BTreeMap::new();
+		let mut queue = dependencies
+			.values()
+			.map(|(id, _, _)| id)
+			.collect::<Vec<_>>();
+
+		while let Some(pkg_id) = queue.pop() {
+			if nodes.contains_key(pkg_id) {
+				continue;
+			}
+
+			if let Some(node) = self.nodes.get(pkg_id) {
+				queue.extend(node.dependencies.values().map(|dep| &dep.id));
+				nodes.insert(pkg_id.clone(), node.clone());
+			}
+		}
+
+		DependencyGraph {
+			importers: BTreeMap::from([(
+				importer.clone(),
+				DependencyGraphImporter { dependencies },
+			)]),
+			overrides: self.overrides.clone(),
+			nodes,
+		}
+	}
+
+	/// Returns the differences between `old` and this graph
+	#[must_use]
+	pub fn diff(&self, old: &DependencyGraph) -> GraphDiff {
+		let empty = DependencyGraphImporter {
+			dependencies: BTreeMap::new(),
+		};
+
+		let importers = self
+			.importers
+			.keys()
+			.chain(old.importers.keys())
+			.collect::<BTreeSet<_>>()
+			.into_iter()
+			.filter_map(|importer| {
+				let new_dependencies = &self.importers.get(importer).unwrap_or(&empty).dependencies;
+				let old_dependencies = &old.importers.get(importer).unwrap_or(&empty).dependencies;
+				let mut diff = ImporterDiff::default();
+
+				for (alias, (id, _, ty)) in new_dependencies {
+					match old_dependencies.get(alias) {
+						None => {
+							diff.added.insert(alias.clone(), (id.clone(), *ty));
+						}
+						Some((old_id, _, old_ty)) if old_id != id => {
+							diff.changed.insert(
+								alias.clone(),
+								((old_id.clone(), *old_ty), (id.clone(), *ty)),
+							);
+						}
+						Some(_) => {}
+					}
+				}
+
+				for (alias, (id, _, ty)) in old_dependencies {
+					if !new_dependencies.contains_key(alias) {
+						diff.removed.insert(alias.clone(), (id.clone(), *ty));
+					}
+				}
+
+				(!diff.is_empty()).then(|| (importer.clone(), diff))
+			})
+			.collect();
+
+		GraphDiff {
+			importers,
+			added: self
+				.nodes
+				.keys()
+				.filter(|id| !old.nodes.contains_key(*id))
+				.cloned()
+				.collect(),
+			removed: old
+				.nodes
+				.keys()
+				.filter(|id| !self.nodes.contains_key(*id))
+				.cloned()
+				.collect(),
+		}
+	}
+
+	/// Returns the shortest path from each direct dependency of each importer to every package for
+	/// which `matches` returns true, sorted. Only the shortest paths are returned, as the number of
+	/// all paths grows exponentially with the size of the graph
+	#[must_use]
+	pub fn paths_to(&self, matches: impl Fn(&PackageId) -> bool) -> Vec<DependencyPath> {
+		let mut paths = vec![];
+
+		for (importer, graph_importer) in &self.importers {
+			for (alias, (id, _, _)) in &graph_importer.dependencies {
+				// breadth-first, so that the first path reaching a package is the shortest one
+				let mut visited = HashSet::from([id]);
+				let mut queue = VecDeque::from([vec![(alias, id)]]);
+
+				while let Some(path) = queue.pop_front() {
+					let (_, id) = path.last().unwrap();
+
+					if matches(id) {
+						paths.push(DependencyPath {
+							importer: importer.clone(),
+							packages: path
+								.iter()
+								.map(|(alias, id)| ((*alias).clone(), (*id).clone()))
+								.collect(),
+						});
+					}
+
+					let Some(node) = self.nodes.get(*id) else {
+						continue;
+					};
+
+					for (dep_alias, dep) in &node.dependencies {
+						if !visited.insert(&dep.id) {
+							continue;
+						}
+
+						let mut path = path.clone();
+						path.push((dep_alias, &dep.id));
+						queue.push_back(path);
+					}
+				}
+			}
+		}
+
+		paths.sort();
+		paths
+	}
+
+	/// Returns the dependencies between the importers of this graph, which are the direct path
+	/// dependencies of an importer pointing to the directory of another importer
+	#[must_use]
+	pub fn workspace_graph(&self) -> WorkspaceGraph {
+		let dependencies = self
+			.importers
+			.iter()
+			.map(|(importer, graph_importer)| {
+				let dependencies = graph_importer
+					.dependencies
+					.values()
+					.filter_map(|(id, _, _)| {
+						let PackageRefs::Path(pkg_ref) = id.pkg_ref() else {
+							return None;
+						};
+						// absolute paths are outside of the workspace
+						let path = RelativePathBuf::from_path(&pkg_ref.path).ok()?;
+
+						Some(Importer::new(path))
+					})
+					.filter(|dependency| {
+						dependency != importer && self.importers.contains_key(dependency)
+					})
+					.collect();
+
+				(importer.clone(), dependencies)
+			})
+			.collect();
+
+		WorkspaceGraph { dependencies }
+	}
+
 	/// Returns the resolved package for a given package ID, if it exists in the graph
 	#[must_use]
 	pub fn resolved_package(&self, package_id: &PackageId) -> Option<ResolvedPackage> {
@@ -180,3 +420,337 @@ impl DependencyGraph {
 		})
 	}
 }
+
+/// The dependencies between the importers of a workspace
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceGraph {
+	/// The importers each importer directly depends on
+	pub dependencies: BTreeMap<Importer, BTreeSet<Importer>>,
+}
+
+impl WorkspaceGraph {
+	/// Returns the importers ordered such that every importer comes after the importers it depends on
+	pub fn topological_order(&self) -> Result<Vec<Importer>, errors::WorkspaceCycleError> {
+		let mut remaining = self.dependencies.clone();
+		let mut order = Vec::with_capacity(remaining.len());
+
+		loop {
+			let ready = remaining
+				.iter()
+				.filter(|(_, dependencies)| {
+					dependencies
+						.iter()
+						.all(|dependency| !remaining.contains_key(dependency))
+				})
+				.map(|(importer, _)| importer.clone())
+				.collect::<Vec<_>>();
+
+			if ready.is_empty() {
+				break;
+			}
+
+			for importer in &ready {
+				remaining.remove(importer);
+			}
+			order.extend(ready);
+		}
+
+		if !remaining.is_empty() {
+			return Err(
+				errors::WorkspaceCycleErrorKind::Cycle(remaining.into_keys().collect()).into(),
+			);
+		}
+
+		Ok(order)
+	}
+
+	/// Returns the importers which depend on the given importer, directly or transitively
+	#[must_use]
+	pub fn dependents(&self, importer: &Importer) -> BTreeSet<Importer> {
+		let mut dependents = BTreeSet::new();
+		let mut queue = vec![importer];
+
+		while let Some(current) = queue.pop() {
+			for (dependent, dependencies) in &self.dependencies {
+				if dependencies.contains(current) && dependents.insert(dependent.clone()) {
+					queue.push(dependent);
+				}
+			}
+		}
+
+		dependents
+	}
+}
+
+/// Errors that can occur when working with dependency graphs
+pub mod errors {
+	use std::collections::BTreeSet;
+
+	use itertools::Itertools as _;
+	use thiserror::Error;
+
+	use crate::Importer;
+
+	/// Errors that can occur when ordering the members of a workspace
+	#[derive(Debug, Error, thiserror_ext::Box)]
+	#[thiserror_ext(newtype(name = WorkspaceCycleError))]
+	#[non_exhaustive]
+	pub enum WorkspaceCycleErrorKind {
+		/// The workspace members depend on each other in a cycle
+		#[error("workspace members {} depend on each other in a cycle", .0.iter().join(", "))]
+		Cycle(BTreeSet<Importer>),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use relative_path::RelativePath;
+
+	fn graph(toml: &str) -> DependencyGraph {
+		toml::from_str(toml).unwrap()
+	}
+
+	#[test]
+	fn diff() {
+		let old = graph(
+			r#"
+			[importers."".dependencies]
+			a = ["wally:https://github.com/pesde-pkg/index:foo/a@1.0.0", { path = "a" }, "standard"]
+			b = ["path:b", { path = "b" }, "dev"]
+
+			[nodes."wally:https://github.com/pesde-pkg/index:foo/a@1.0.0"]
+			structure_kind = "pesde_v2"
+			dependencies = { c = ["path:c", "standard"] }
+
+			[nodes."path:b"]
+			structure_kind = "pesde_v2"
+
+			[nodes."path:c"]
+			structure_kind = "pesde_v2"
+			"#,
+		);
+		let new = graph(
+			r#"
+			[importers."".dependencies]
+			a = ["wally:https://github.com/pesde-pkg/index:foo/a@2.0.0", { path = "a" }, "standard"]
+			d = ["path:d", { path = "d" }, "peer"]
+
+			[importers."packages/e".dependencies]
+			d = ["path:d", { path = "d" }, "standard"]
+
+			[nodes."wally:https://github.com/pesde-pkg/index:foo/a@2.0.0"]
+			structure_kind = "pesde_v2"
+			dependencies = { f = ["path:f", "standard"] }
+
+			[nodes."path:d"]
+			structure_kind = "pesde_v2"
+
+			[nodes."path:f"]
+			structure_kind = "pesde_v2"
+			"#,
+		);
+
+		let id = |id: &str| id.parse::<PackageId>().unwrap();
+		let alias = |alias: &str| alias.parse::<Alias>().unwrap();
+
+		let diff = new.diff(&old);
+
+		assert_eq!(
+			diff.importers[&Importer::root()],
+			ImporterDiff {
+				added: BTreeMap::from([(alias("d"), (id("path:d"), DependencyType::Peer))]),
+				removed: BTreeMap::from([(alias("b"), (id("path:b"), DependencyType::Dev))]),
+				changed: BTreeMap::from([(
+					alias("a"),
+					(
+						(
+							id("wally:https://github.com/pesde-pkg/index:foo/a@1.0.0"),
+							DependencyType::Standard
+						),
+						(
+							id("wally:https://github.com/pesde-pkg/index:foo/a@2.0.0"),
+							DependencyType::Standard
+						),
+					)
+				)]),
+			}
+		);
+		assert_eq!(
+			diff.importers[&Importer::new(RelativePath::new("packages/e"))],
+			ImporterDiff {
+				added: BTreeMap::from([(alias("d"), (id("path:d"), DependencyType::Standard))]),
+				..Default::default()
+			}
+		);
+
+		assert_eq!(
+			diff.added,
+			BTreeSet::from([
+				id("wally:https://github.com/pesde-pkg/index:foo/a@2.0.0"),
+				id("path:d"),
+				id("path:f"),
+			])
+		);
+		assert_eq!(
+			diff.removed,
+			BTreeSet::from([
+				id("wally:https://github.com/pesde-pkg/index:foo/a@1.0.0"),
+				id("path:b"),
+				id("path:c"),
+			])
+		);
+
+		assert!(diff.is_direct(&id("path:d")));
+		assert!(diff.is_direct(&id("path:b")));
+		assert!(!diff.is_direct(&id("path:f")));
+		assert!(!diff.is_direct(&id("path:c")));
+
+		assert_eq!(new.diff(&new), GraphDiff::default());
+	}
+
+	#[test]
+	fn workspace_graph() {
+		let graph = graph(
+			r#"
+			[importers."".dependencies]
+			a = ["path:packages/a", { path = "packages/a" }, "standard"]
+
+			[importers."packages/a".dependencies]
+			b = ["path:packages/b", { path = "../b" }, "standard"]
+			c = ["path:c", { path = "../../c" }, "standard"]
+
+			[importers."packages/b"]
+
+			[nodes."path:packages/a"]
+			structure_kind = "pesde_v2"
+
+			[nodes."path:packages/b"]
+			structure_kind = "pesde_v2"
+
+			[nodes."path:c"]
+			structure_kind = "pesde_v2"
+			"#,
+		);
+
+		let importer = |path: &str| Importer::new(RelativePath::new(path));
+
+		let workspace_graph = graph.workspace_graph();
+		assert_eq!(
+			workspace_graph.dependencies,
+			BTreeMap::from([
+				(Importer::root(), BTreeSet::from([importer("packages/a")])),
+				(
+					importer("packages/a"),
+					BTreeSet::from([importer("packages/b")])
+				),
+				(importer("packages/b"), BTreeSet::new()),
+			])
+		);
+		assert_eq!(
+			workspace_graph.topological_order().unwrap(),
+			vec![
+				importer("packages/b"),
+				importer("packages/a"),
+				Importer::root()
+			]
+		);
+		assert_eq!(
+			workspace_graph.dependents(&importer("packages/b")),
+			BTreeSet::from([Importer::root(), importer("packages/a")])
+		);
+
+		let cyclic = WorkspaceGraph {
+			dependencies: BTreeMap::from([
+				(importer("a"), BTreeSet::from([importer("b")])),
+				(importer("b"), BTreeSet::from([importer("a")])),
+				(importer("c"), BTreeSet::new()),
+			]),
+		};
+		let err = cyclic.topological_order().unwrap_err();
+		let errors::WorkspaceCycleErrorKind::Cycle(members) = err.inner();
+		assert_eq!(*members, BTreeSet::from([importer("a"), importer("b")]));
+	}
+
+	#[test]
+	fn paths_to() {
+		let graph = graph(
+			r#"
+			[importers."".dependencies]
+			a = ["path:a", { path = "a" }, "standard"]
+			b = ["path:b", { path = "b" }, "dev"]
+
+			[importers."packages/e".dependencies]
+			c = ["path:c", { path = "c" }, "standard"]
+
+			[nodes."path:a"]
+			structure_kind = "pesde_v2"
+			dependencies = { c = ["path:c", "standard"] }
+
+			[nodes."path:b"]
+			structure_kind = "pesde_v2"
+			dependencies = { a = ["path:a", "standard"], c2 = ["path:c", "standard"] }
+
+			[nodes."path:c"]
+			structure_kind = "pesde_v2"
+			dependencies = { b = ["path:b", "standard"] }
+			"#,
+		);
+
+		let id = |id: &str| id.parse::<PackageId>().unwrap();
+		let package =
+			|alias: &str, package_id: &str| (alias.parse::<Alias>().unwrap(), id(package_id));
+
+		let paths = graph.paths_to(|package_id| *package_id == id("path:c"));
+		assert_eq!(
+			paths,
+			vec![
+				DependencyPath {
+					importer: Importer::root(),
+					packages: vec![package("a", "path:a"), package("c", "path:c")],
+				},
+				DependencyPath {
+					importer: Importer::root(),
+					packages: vec![package("b", "path:b"), package("c2", "path:c")],
+				},
+				DependencyPath {
+					importer: Importer::new(RelativePath::new("packages/e")),
+					packages: vec![package("c", "path:c")],
+				},
+			]
+		);
+
+		assert!(
+			graph
+				.paths_to(|package_id| *package_id == id("path:d"))
+				.is_empty()
+		);
+	}
+
+	#[test]
+	fn paths_to_is_not_exponential() {
+		use std::fmt::Write as _;
+
+		// every package of a layer depends on both packages of the next layer, which makes for
+		// 2^LAYERS paths from the importer to the last package
+		const LAYERS: usize = 40;
+		let mut toml = String::from(
+			"[importers.\"\".dependencies]\nd = [\"path:0a\", { path = \"0a\" }, \"standard\"]\n",
+		);
+		for layer in 0..LAYERS {
+			for package in ["a", "b"] {
+				_ = writeln!(
+					toml,
+					"[nodes.\"path:{layer}{package}\"]\nstructure_kind = \"pesde_v2\"\ndependencies = {{ a = [\"path:{next}a\", \"standard\"], b = [\"path:{next}b\", \"standard\"] }}",
+					next = layer + 1
+				);
+			}
+		}
+		let graph = graph(&toml);
+
+		let last = format!("path:{LAYERS}a").parse::<PackageId>().unwrap();
+		let paths = graph.paths_to(|id| *id == last);
+		assert_eq!(paths.len(), 1);
+		assert_eq!(paths[0].packages.len(), LAYERS + 1);
+	}
+}
@@ -5,6 +5,8 @@
 
 use crate::lockfile::Lockfile;
 use crate::manifest::Manifest;
+use crate::manifest::ManifestWorkspace;
+use crate::reporters::RefreshReporter;
 use crate::source::PackageSource as _;
 use crate::source::PackageSources;
 use fs_err::tokio as fs;
@@ -25,6 +27,8 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::SystemTime;
 use tokio::io::AsyncReadExt as _;
 use tokio::sync::Mutex;
 use tokio::sync::OwnedRwLockReadGuard;
@@ -52,6 +56,8 @@ pub(crate) mod util;
 pub const MANIFEST_FILE_NAME: &str = "pesde.toml";
 /// The name of the lockfile
 pub const LOCKFILE_FILE_NAME: &str = "pesde.lock";
+/// The name of the file listing the resolved packages, written next to the lockfile if enabled
+pub const SOURCES_FILE_NAME: &str = "pesde.sources.json";
 /// The name of the default index
 pub const DEFAULT_INDEX_NAME: &str = "default";
 /// The name of the packages container
@@ -65,6 +71,7 @@ pub(crate) fn default_index_name() -> String {
 #[derive(Debug, Default)]
 struct AuthConfigShared {
 	tokens: HashMap<GixUrl, String>,
+	proxy: Option<reqwest::Proxy>,
 }
 
 /// Struct containing the authentication configuration
@@ -97,6 +104,20 @@ impl AuthConfig {
 	pub fn tokens(&self) -> &HashMap<GixUrl, String> {
 		&self.shared.tokens
 	}
+
+	/// Set the proxy HTTP requests are sent through
+	/// Panics if the `AuthConfig` is shared
+	#[must_use]
+	pub fn with_proxy(mut self, proxy: Option<reqwest::Proxy>) -> Self {
+		Arc::get_mut(&mut self.shared).unwrap().proxy = proxy;
+		self
+	}
+
+	/// Get the proxy
+	#[must_use]
+	pub fn proxy(&self) -> Option<&reqwest::Proxy> {
+		self.shared.proxy.as_ref()
+	}
 }
 
 /// A workspace member. Can be empty for the root project.
@@ -143,9 +164,33 @@ struct ProjectShared {
 	dir: PathBuf,
 	data_dir: PathBuf,
 	cas_dir: PathBuf,
+	dependencies_dir: Option<RelativePathBuf>,
+	prefix: Option<PathBuf>,
 	auth_config: AuthConfig,
-	manifests: Mutex<HashMap<Importer, Arc<RwLock<Manifest>>>>,
+	/// The deserialized manifests, along with the modification time of the file they were read from.
+	/// Shared with copies of the project, as they read the same manifests
+	manifests: Arc<Mutex<HashMap<Importer, (Option<SystemTime>, Arc<RwLock<Manifest>>)>>>,
 	reqwest: reqwest::Client,
+	interrupt: Arc<AtomicBool>,
+	refresh_reporter: Arc<dyn RefreshReporter>,
+}
+
+impl ProjectShared {
+	/// Copies the configuration of this project
+	fn copy(&self) -> Self {
+		ProjectShared {
+			dir: self.dir.clone(),
+			data_dir: self.data_dir.clone(),
+			cas_dir: self.cas_dir.clone(),
+			dependencies_dir: self.dependencies_dir.clone(),
+			prefix: self.prefix.clone(),
+			auth_config: self.auth_config.clone(),
+			manifests: self.manifests.clone(),
+			reqwest: self.reqwest.clone(),
+			interrupt: self.interrupt.clone(),
+			refresh_reporter: self.refresh_reporter.clone(),
+		}
+	}
 }
 
 /// The main struct of the pesde library, representing a project
@@ -157,6 +202,11 @@ pub struct Project {
 
 impl Project {
 	/// Create a new `Project`
+	///
+	/// HTTP requests are sent with `reqwest`, unless `auth_config` has a proxy. As the proxies of a
+	/// built client can't be changed, a client sending requests through it is built instead.
+	/// Git indices are fetched by gix, which only honors the proxy environment variables.
+	/// Panics if the proxied client can't be built, like [reqwest::Client::new]
 	#[must_use]
 	pub fn new(
 		dir: impl Into<PathBuf>,
@@ -166,15 +216,69 @@ impl Project {
 		reqwest: reqwest::Client,
 	) -> Self {
 		let dir = dir.into();
+		let reqwest = match auth_config.proxy() {
+			Some(proxy) => reqwest::Client::builder()
+				.user_agent(concat!(
+					env!("CARGO_PKG_NAME"),
+					"/",
+					env!("CARGO_PKG_VERSION")
+				))
+				.proxy(proxy.clone())
+				.build()
+				.expect("failed to build the proxied client"),
+			None => reqwest,
+		};
 
 		Project {
 			shared: ProjectShared {
 				dir,
 				cas_dir: cas_dir.into(),
 				data_dir: data_dir.into(),
+				dependencies_dir: None,
+				prefix: None,
 				auth_config,
 				manifests: Default::default(),
 				reqwest,
+				interrupt: Default::default(),
+				refresh_reporter: Arc::new(()),
+			}
+			.into(),
+		}
+	}
+
+	/// Returns a copy of this project which installs dependencies under `prefix` instead of
+	/// the project directory. The manifests and lockfile are still read from the project directory
+	#[must_use]
+	pub fn with_prefix(&self, prefix: impl Into<PathBuf>) -> Self {
+		Project {
+			shared: ProjectShared {
+				prefix: Some(prefix.into()),
+				..self.shared.copy()
+			}
+			.into(),
+		}
+	}
+
+	/// Returns a copy of this project which installs dependencies to `dependencies_dir`, relative to
+	/// each subproject's directory, instead of the default directory
+	#[must_use]
+	pub fn with_dependencies_dir(&self, dependencies_dir: impl Into<RelativePathBuf>) -> Self {
+		Project {
+			shared: ProjectShared {
+				dependencies_dir: Some(dependencies_dir.into()),
+				..self.shared.copy()
+			}
+			.into(),
+		}
+	}
+
+	/// Returns a copy of this project which reports the progress of Git index refreshes to `reporter`
+	#[must_use]
+	pub fn with_refresh_reporter(&self, reporter: Arc<dyn RefreshReporter>) -> Self {
+		Project {
+			shared: ProjectShared {
+				refresh_reporter: reporter,
+				..self.shared.copy()
 			}
 			.into(),
 		}
@@ -186,6 +290,12 @@ impl Project {
 		&self.shared.dir
 	}
 
+	/// The directory dependencies are installed under instead of the project directory, if any
+	#[must_use]
+	pub fn prefix(&self) -> Option<&Path> {
+		self.shared.prefix.as_deref()
+	}
+
 	/// The directory to store general-purpose data
 	#[must_use]
 	pub fn data_dir(&self) -> &Path {
@@ -210,6 +320,24 @@ impl Project {
 		&self.shared.reqwest
 	}
 
+	/// The flag which aborts ongoing Git index refreshes when set. Refreshes fail with an
+	/// interrupted error while it is set, so it must be cleared before refreshing again
+	#[must_use]
+	pub fn interrupt(&self) -> &Arc<AtomicBool> {
+		&self.shared.interrupt
+	}
+
+	/// The reporter of Git index refresh progress
+	#[must_use]
+	pub fn refresh_reporter(&self) -> &Arc<dyn RefreshReporter> {
+		&self.shared.refresh_reporter
+	}
+
+	/// Clears the deserialized manifests, so that they are read again on next use
+	pub async fn clear_manifest_cache(&self) {
+		self.shared.manifests.lock().await.clear();
+	}
+
 	/// Create a subproject for an importer
 	#[must_use]
 	pub fn subproject(self, importer: Importer) -> Subproject {
@@ -228,22 +356,76 @@ impl Project {
 		lockfile::parse_lockfile(&string).map_err(Into::into)
 	}
 
-	/// Write the lockfile
+	/// Deserialize the lockfile and return its dependency graph, or `None` if there is no lockfile
+	#[instrument(skip(self), level = "debug")]
+	pub async fn graph_from_lockfile(
+		&self,
+	) -> Result<Option<graph::DependencyGraph>, errors::LockfileReadError> {
+		match self.deser_lockfile().await {
+			Ok(lockfile) => Ok(Some(lockfile.graph)),
+			Err(e) => match e.inner() {
+				errors::LockfileReadErrorKind::Io(e)
+					if e.kind() == std::io::ErrorKind::NotFound =>
+				{
+					Ok(None)
+				}
+				_ => Err(e),
+			},
+		}
+	}
+
+	/// Returns the dependencies between the members of the workspace, as recorded in the lockfile,
+	/// or `None` if there is no lockfile
+	#[instrument(skip(self), level = "debug")]
+	pub async fn workspace_graph(
+		&self,
+	) -> Result<Option<graph::WorkspaceGraph>, errors::LockfileReadError> {
+		Ok(self
+			.graph_from_lockfile()
+			.await?
+			.map(|graph| graph.workspace_graph()))
+	}
+
+	/// Write the lockfile, and the sources file if `workspace.sources_file` is set
 	#[instrument(skip(self, lockfile), level = "debug")]
 	pub async fn write_lockfile(
 		&self,
 		lockfile: &Lockfile,
 	) -> Result<(), errors::LockfileWriteError> {
-		let lockfile = toml::to_string(lockfile)?;
-		let lockfile = format!(
+		let contents = toml::to_string(lockfile)?;
+		let contents = format!(
 			r"# This file is automatically @generated by pesde.
 # It is not intended for manual editing.
 format = {}
-{lockfile}",
+{contents}",
 			lockfile::CURRENT_FORMAT
 		);
 
-		fs::write(self.dir().join(LOCKFILE_FILE_NAME), lockfile).await?;
+		// the manifest is read first so that a broken manifest doesn't leave the lockfile and
+		// sources file out of sync
+		let sources_file = self
+			.clone()
+			.subproject(Importer::root())
+			.deser_manifest()
+			.await?
+			.workspace
+			.sources_file;
+
+		fs::write(self.dir().join(LOCKFILE_FILE_NAME), contents).await?;
+
+		let sources_file_path = self.dir().join(SOURCES_FILE_NAME);
+		if sources_file {
+			let contents = serde_json::to_string_pretty(&lockfile::sources_file(&lockfile.graph))?;
+			fs::write(sources_file_path, contents).await?;
+		} else {
+			// a sources file left behind from when it was enabled would no longer match the lockfile
+			match fs::remove_file(sources_file_path).await {
+				Ok(()) => {}
+				Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+				Err(e) => return Err(e.into()),
+			}
+		}
+
 		Ok(())
 	}
 }
@@ -282,7 +464,15 @@ impl Subproject {
 	/// The dependencies directory
 	#[must_use]
 	pub fn dependencies_dir(&self) -> PathBuf {
-		self.dir().join(env!("CARGO_PKG_NAME")).join("dependencies")
+		let base = match self.project().prefix() {
+			Some(prefix) => self.importer().as_path().to_path(prefix),
+			None => self.dir(),
+		};
+
+		match &self.project().shared.dependencies_dir {
+			Some(dependencies_dir) => dependencies_dir.to_path(base),
+			None => base.join(env!("CARGO_PKG_NAME")).join("dependencies"),
+		}
 	}
 
 	/// Read the manifest file
@@ -292,19 +482,43 @@ impl Subproject {
 		Ok(string)
 	}
 
+	/// Read the manifest file as an untyped TOML table, allowing fields unknown to [Manifest] to be inspected
+	#[instrument(skip(self), ret(level = "trace"), level = "debug")]
+	pub async fn read_manifest_value(&self) -> Result<toml::Table, errors::ManifestReadError> {
+		let string = self.read_manifest().await?;
+		toml::from_str(&string)
+			.map_err(|e| errors::ManifestReadErrorKind::Serde(self.dir(), e).into())
+	}
+
 	/// Deserialize the manifest file
 	#[instrument(skip(self), ret(level = "trace"), level = "debug")]
 	pub async fn deser_manifest(
 		&self,
 	) -> Result<OwnedRwLockReadGuard<Manifest>, errors::ManifestReadError> {
+		let path = self.dir().join(MANIFEST_FILE_NAME);
+		let modified = fs::metadata(&path).await?.modified().ok();
+
 		let mut manifests_guard = self.project().shared.manifests.lock().await;
-		if !manifests_guard.contains_key(self.importer()) {
-			let manifest = fs::read_to_string(self.dir().join(MANIFEST_FILE_NAME)).await?;
-			let manifest = toml::from_str::<Manifest>(&manifest)
-				.map_err(|e| errors::ManifestReadErrorKind::Serde(self.dir(), e))?;
-			manifests_guard.insert(self.importer().clone(), Arc::new(RwLock::new(manifest)));
-		}
-		Ok(manifests_guard[self.importer()].clone().read_owned().await)
+		// the file is re-read if it was modified since, e.g. by the user while a command runs
+		let manifest = match manifests_guard.get(self.importer()) {
+			Some((cached_modified, manifest))
+				if modified.is_some() && *cached_modified == modified =>
+			{
+				manifest.clone()
+			}
+			_ => {
+				tracing::trace!("parsing manifest of {}", self.importer());
+				let manifest = fs::read_to_string(&path).await?;
+				let manifest = toml::from_str::<Manifest>(&manifest)
+					.map_err(|e| errors::ManifestReadErrorKind::Serde(self.dir(), e))?;
+				let manifest = Arc::new(RwLock::new(manifest));
+				manifests_guard.insert(self.importer().clone(), (modified, manifest.clone()));
+				manifest
+			}
+		};
+		drop(manifests_guard);
+
+		Ok(manifest.read_owned().await)
 	}
 
 	/// Write the manifest file
@@ -364,9 +578,63 @@ pub async fn matching_globs<'a>(
 	Ok(paths)
 }
 
+/// Gets the directories of the members of a workspace in `dir`. These are matched by the member
+/// globs, or if there are none and `discover_members` is set, are all the directories below `dir`
+/// which contain a manifest
+#[instrument(skip(workspace), ret, level = "trace")]
+pub async fn workspace_members(
+	dir: &Path,
+	workspace: &ManifestWorkspace,
+) -> Result<HashSet<PathBuf>, errors::MatchingGlobsError> {
+	if !workspace.members.is_empty() {
+		return matching_globs(dir, workspace.members.iter().map(String::as_str)).await;
+	}
+
+	if !workspace.discover_members {
+		return Ok(HashSet::new());
+	}
+
+	// dependencies may contain manifests too, so their directories aren't searched
+	let dependencies_dir = |member: &Path| match &workspace.dependencies_dir {
+		Some(dependencies_dir) => dependencies_dir.to_path(member),
+		None => member.join(env!("CARGO_PKG_NAME")).join("dependencies"),
+	};
+
+	let mut excluded = HashSet::from([dependencies_dir(dir)]);
+	let mut read_dirs = vec![fs::read_dir(dir).await?];
+	let mut paths = HashSet::new();
+
+	while let Some(mut read_dir) = read_dirs.pop() {
+		while let Some(entry) = read_dir.next_entry().await? {
+			let path = entry.path();
+			if !entry.file_type().await?.is_dir()
+				|| excluded.contains(&path)
+				|| source::is_ignored_dir(&entry.file_name().to_string_lossy())
+			{
+				continue;
+			}
+
+			if fs::metadata(path.join(MANIFEST_FILE_NAME)).await.is_ok() {
+				excluded.insert(dependencies_dir(&path));
+				paths.insert(path.clone());
+			}
+
+			read_dirs.push(fs::read_dir(&path).await?);
+		}
+	}
+
+	Ok(paths)
+}
+
 /// A struct containing sources already having been refreshed
 #[derive(Debug, Clone, Default)]
-pub struct RefreshedSources(Arc<tokio::sync::Mutex<HashSet<u64>>>);
+pub struct RefreshedSources {
+	refreshed: Arc<tokio::sync::Mutex<HashSet<u64>>>,
+	/// The sources whose refresh was skipped because of `prefer_offline`
+	offline: Arc<tokio::sync::Mutex<HashSet<u64>>>,
+	prefer_offline: bool,
+	include_prerelease: bool,
+}
 
 impl RefreshedSources {
 	/// Create a new empty `RefreshedSources`
@@ -375,6 +643,28 @@ impl RefreshedSources {
 		RefreshedSources::default()
 	}
 
+	/// Sets whether sources which have a local copy of their index are not refreshed
+	#[must_use]
+	pub fn prefer_offline(mut self, prefer_offline: bool) -> Self {
+		self.prefer_offline = prefer_offline;
+		self
+	}
+
+	/// Sets whether prerelease versions may satisfy version requirements which don't mention a
+	/// prerelease
+	#[must_use]
+	pub fn include_prerelease(mut self, include_prerelease: bool) -> Self {
+		self.include_prerelease = include_prerelease;
+		self
+	}
+
+	/// Whether prerelease versions may satisfy version requirements which don't mention a
+	/// prerelease
+	#[must_use]
+	pub fn includes_prerelease(&self) -> bool {
+		self.include_prerelease
+	}
+
 	/// Refreshes the source asynchronously if it has not already been refreshed.
 	/// Will prevent more refreshes of the same source.
 	pub async fn refresh(
@@ -382,21 +672,53 @@ impl RefreshedSources {
 		source: &PackageSources,
 		project: &Project,
 	) -> Result<(), source::errors::RefreshError> {
-		let mut hasher = std::hash::DefaultHasher::new();
-		source.hash(&mut hasher);
-		let hash = hasher.finish();
+		let hash = Self::hash(source);
 
-		let mut refreshed_sources = self.0.lock().await;
+		let mut refreshed_sources = self.refreshed.lock().await;
 
-		if refreshed_sources.insert(hash) {
-			source.refresh(project).await
-		} else {
-			Ok(())
+		if !refreshed_sources.insert(hash) {
+			return Ok(());
 		}
+
+		if self.prefer_offline && source.has_local_index(project).await {
+			tracing::debug!("using local index of {source} without refreshing");
+			self.offline.lock().await.insert(hash);
+			return Ok(());
+		}
+
+		source.refresh(project).await
+	}
+
+	/// Refreshes the source if its refresh was skipped because it has a local index, returning
+	/// whether it was refreshed. The local index may be missing packages published since it was
+	/// last refreshed
+	pub async fn refresh_offline(
+		&self,
+		source: &PackageSources,
+		project: &Project,
+	) -> Result<bool, source::errors::RefreshError> {
+		if !self.offline.lock().await.remove(&Self::hash(source)) {
+			return Ok(false);
+		}
+
+		tracing::debug!("refreshing local index of {source}");
+		source.refresh(project).await?;
+
+		Ok(true)
+	}
+
+	fn hash(source: &PackageSources) -> u64 {
+		let mut hasher = std::hash::DefaultHasher::new();
+		source.hash(&mut hasher);
+		hasher.finish()
 	}
 }
 
 /// Find the project & workspace directory roots
+///
+/// Returns the directory to create the [Project] in, along with the importer of the project found
+/// from `cwd`. Within a workspace member, this is the workspace root rather than the member's
+/// directory (which older versions returned), as the importer is relative to the workspace root
 pub async fn find_roots(cwd: PathBuf) -> Result<(PathBuf, Importer), errors::FindRootsError> {
 	let mut current_path = Some(cwd.clone());
 	let mut project_root = None::<PathBuf>;
@@ -415,11 +737,7 @@ pub async fn find_roots(cwd: PathBuf) -> Result<(PathBuf, Importer), errors::Fin
 			errors::ManifestReadError::from(errors::ManifestReadErrorKind::Serde(path.into(), e))
 		})?;
 
-		if manifest.workspace.members.is_empty() {
-			return Ok(HashSet::new());
-		}
-
-		matching_globs(path, manifest.workspace.members.iter().map(String::as_str))
+		workspace_members(path, &manifest.workspace)
 			.await
 			.map_err(|e| errors::FindRootsErrorKind::Globbing(e).into())
 	}
@@ -440,7 +758,7 @@ pub async fn find_roots(cwd: PathBuf) -> Result<(PathBuf, Importer), errors::Fin
 			&& let Some(project_root) = project_root
 		{
 			let importer = to_importer!(project_root, workspace_dir);
-			return Ok((project_root, importer));
+			return Ok((workspace_dir.to_path_buf(), importer));
 		}
 
 		let mut manifest = match fs::File::open(path.join(MANIFEST_FILE_NAME)).await {
@@ -470,7 +788,7 @@ pub async fn find_roots(cwd: PathBuf) -> Result<(PathBuf, Importer), errors::Fin
 				{
 					// initializing a new member of a workspace
 					let importer = to_importer!(cwd, path);
-					return Ok((cwd, importer));
+					return Ok((path, importer));
 				}
 
 				project_root = Some(path);
@@ -485,7 +803,7 @@ pub async fn find_roots(cwd: PathBuf) -> Result<(PathBuf, Importer), errors::Fin
 	let project_root = project_root.unwrap_or(cwd);
 	let workspace_root = workspace_dir.as_deref().unwrap_or(&project_root);
 	let importer = to_importer!(project_root, workspace_root);
-	Ok((project_root, importer))
+	Ok((workspace_root.to_path_buf(), importer))
 }
 
 /// Returns whether a version matches a version requirement
@@ -495,6 +813,28 @@ pub fn version_matches(req: &VersionReq, version: &Version) -> bool {
 	*req == VersionReq::STAR || req.matches(version)
 }
 
+/// Returns whether a version matches a version requirement, like [version_matches], but
+/// prerelease versions may match even if the requirement doesn't mention a prerelease
+#[must_use]
+pub fn version_matches_including_prerelease(req: &VersionReq, version: &Version) -> bool {
+	if version.pre.is_empty() {
+		return version_matches(req, version);
+	}
+
+	// every prerelease of this version satisfies this comparator, and semver allows prereleases
+	// to match if any comparator mentions a prerelease of the same version
+	let mut req = req.clone();
+	req.comparators.push(semver::Comparator {
+		op: semver::Op::GreaterEq,
+		major: version.major,
+		minor: Some(version.minor),
+		patch: Some(version.patch),
+		pre: semver::Prerelease::new("0").unwrap(),
+	});
+
+	version_matches(&req, version)
+}
+
 /// A thin wrapper around `gix::Url` to serde in a human-readable way as well as providing cheap cloning
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct GixUrl(Arc<gix::Url>);
@@ -573,6 +913,14 @@ pub mod errors {
 		/// An error occurred while serializing the lockfile
 		#[error("error serializing lockfile")]
 		Serde(#[from] toml::ser::Error),
+
+		/// An error occurred while reading the manifest
+		#[error("error reading manifest")]
+		ManifestRead(#[from] ManifestReadError),
+
+		/// An error occurred while serializing the sources file
+		#[error("error serializing sources file")]
+		SourcesSerde(#[from] serde_json::Error),
 	}
 
 	/// Errors that can occur when finding matching globs
@@ -606,3 +954,710 @@ pub mod errors {
 	/// Errors that can occur when parsing a `gix::Url`
 	pub type GixUrlError = gix::url::parse::Error;
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+
+	/// Creates a project in `project_dir`, keeping its data and CAS directories in `dir`
+	pub(crate) fn test_project(dir: &Path, project_dir: impl Into<PathBuf>) -> Project {
+		Project::new(
+			project_dir,
+			dir.join("data"),
+			dir.join("cas"),
+			AuthConfig::new(),
+			reqwest::Client::new(),
+		)
+	}
+
+	/// Serves HTTP on a local port for the rest of the test, answering every request with the full
+	/// response `respond` returns for the head of the request
+	pub(crate) async fn mock_http_server(
+		respond: impl Fn(&str) -> String + Send + 'static,
+	) -> std::net::SocketAddr {
+		use tokio::io::AsyncWriteExt as _;
+
+		let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let addr = listener.local_addr().unwrap();
+		tokio::spawn(async move {
+			loop {
+				let (mut stream, _) = listener.accept().await.unwrap();
+				let mut request = vec![];
+				let mut buf = [0u8; 1024];
+				while !request.ends_with(b"\r\n\r\n") {
+					let read = stream.read(&mut buf).await.unwrap();
+					if read == 0 {
+						break;
+					}
+					request.extend_from_slice(&buf[..read]);
+				}
+
+				let response = respond(&String::from_utf8_lossy(&request));
+				// the client may hang up early, e.g. as soon as it sees the content length
+				let _ = stream.write_all(response.as_bytes()).await;
+			}
+		});
+
+		addr
+	}
+
+	/// A response for [mock_http_server] with a JSON body
+	pub(crate) fn json_response(status: &str, body: &str) -> String {
+		format!(
+			"HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+			body.len()
+		)
+	}
+
+	/// Creates a temporary `project` directory whose manifest (`project_manifest` followed by the
+	/// dependency) depends on a sibling `foo` package by path. The returned directory must be kept
+	/// alive for as long as the project is used
+	pub(crate) async fn path_dependency_project(
+		project_manifest: &str,
+		foo_manifest: &str,
+	) -> (tempfile::TempDir, Project) {
+		let dir = tempfile::tempdir().unwrap();
+		let project_dir = dir.path().join("project");
+
+		fs::create_dir_all(&project_dir).await.unwrap();
+		fs::create_dir_all(dir.path().join("foo")).await.unwrap();
+		fs::write(
+			project_dir.join(MANIFEST_FILE_NAME),
+			format!("{project_manifest}\n[dependencies]\nfoo = {{ path = \"../foo\" }}\n"),
+		)
+		.await
+		.unwrap();
+		fs::write(
+			dir.path().join("foo").join(MANIFEST_FILE_NAME),
+			foo_manifest,
+		)
+		.await
+		.unwrap();
+
+		let project = test_project(dir.path(), &project_dir);
+
+		(dir, project)
+	}
+
+	#[test]
+	fn prerelease_matching() {
+		let req = "^1.0.0".parse().unwrap();
+		let version = |v: &str| v.parse::<Version>().unwrap();
+
+		assert!(!version_matches(&req, &version("1.2.0-beta.1")));
+		assert!(version_matches_including_prerelease(
+			&req,
+			&version("1.2.0-beta.1")
+		));
+		assert!(version_matches_including_prerelease(
+			&req,
+			&version("1.2.0")
+		));
+		assert!(!version_matches_including_prerelease(
+			&req,
+			&version("1.0.0-beta.1")
+		));
+		assert!(!version_matches_including_prerelease(
+			&req,
+			&version("2.0.0-beta.1")
+		));
+
+		let req = "^1.2.0-beta.1".parse().unwrap();
+		assert!(version_matches(&req, &version("1.2.0-beta.2")));
+		assert!(!version_matches(&req, &version("1.3.0-beta.1")));
+		assert!(version_matches_including_prerelease(
+			&req,
+			&version("1.3.0-beta.1")
+		));
+	}
+
+	/// Creates a project in `dir` depending on `acme/foo`, which is published to a local index
+	async fn index_dependency_project(dir: &Path) -> PathBuf {
+		use crate::source::local_index::LocalIndex;
+
+		let mut index = LocalIndex::new(&dir.join("index"));
+		index
+			.publish(
+				"acme/foo",
+				"1.0.0",
+				"{ environment = \"luau\", lib = \"init.luau\" }",
+				&[("init.luau", "return {}")],
+			)
+			.await;
+
+		let project_dir = dir.join("project");
+		fs::create_dir_all(&project_dir).await.unwrap();
+		fs::write(
+			project_dir.join(MANIFEST_FILE_NAME),
+			format!(
+				"[indices]\ndefault = \"{}\"\n\n[dependencies]\nfoo = {{ name = \"acme/foo\", version = \"^1.0.0\", target = \"luau\" }}\n",
+				index.url()
+			),
+		)
+		.await
+		.unwrap();
+
+		project_dir
+	}
+
+	#[tokio::test]
+	async fn install_from_vendored_index() {
+		let requests = Arc::new(std::sync::Mutex::new(vec![]));
+		let proxy = mock_http_server({
+			let requests = requests.clone();
+			move |request| {
+				requests.lock().unwrap().push(request.to_string());
+				json_response("404 Not Found", "")
+			}
+		})
+		.await;
+
+		// the index and its archives are all read from the directory, so nothing reaches the proxy
+		let dir = tempfile::tempdir().unwrap();
+		let project_dir = index_dependency_project(dir.path()).await;
+		let project = Project::new(
+			&project_dir,
+			dir.path().join("data"),
+			dir.path().join("cas"),
+			AuthConfig::new(),
+			reqwest::Client::builder()
+				.proxy(reqwest::Proxy::all(format!("http://{proxy}")).unwrap())
+				.build()
+				.unwrap(),
+		);
+
+		project
+			.install(crate::download_and_link::InstallOptions::<()>::new())
+			.await
+			.unwrap();
+
+		let graph = project.graph_from_lockfile().await.unwrap().unwrap();
+		assert_eq!(graph.nodes.len(), 1);
+		assert!(
+			fs::metadata(
+				project
+					.subproject(Importer::root())
+					.dependencies_dir()
+					.join("packages")
+					.join("foo.luau")
+			)
+			.await
+			.is_ok()
+		);
+		assert_eq!(*requests.lock().unwrap(), Vec::<String>::new());
+	}
+
+	#[tokio::test]
+	async fn custom_dependencies_dir() {
+		let dir = tempfile::tempdir().unwrap();
+		let project_dir = index_dependency_project(dir.path()).await;
+		let project = test_project(dir.path(), &project_dir).with_dependencies_dir("build/pesde");
+
+		project
+			.install(crate::download_and_link::InstallOptions::<()>::new())
+			.await
+			.unwrap();
+
+		let graph = project.graph_from_lockfile().await.unwrap().unwrap();
+		let (id, _, _) = &graph.importers[&Importer::root()].dependencies[&"foo".parse().unwrap()];
+		let container =
+			graph::DependencyGraphNode::container_dir(id, &graph.nodes[id].structure_kind);
+
+		let packages_dir = project_dir.join("build").join("pesde").join("packages");
+		let linker = fs::read_to_string(packages_dir.join("foo.luau"))
+			.await
+			.unwrap();
+		assert!(
+			linker.contains(&format!(
+				"require(\"./{PACKAGES_CONTAINER_NAME}/{}\")",
+				RelativePathBuf::from_path(&container).unwrap()
+			)),
+			"{linker}"
+		);
+		assert_eq!(
+			fs::read_to_string(
+				packages_dir
+					.join(PACKAGES_CONTAINER_NAME)
+					.join(&container)
+					.join("init.luau")
+			)
+			.await
+			.unwrap(),
+			"return {}"
+		);
+		assert!(
+			fs::metadata(project_dir.join(env!("CARGO_PKG_NAME")))
+				.await
+				.is_err()
+		);
+	}
+
+	#[tokio::test]
+	async fn manifest_cache() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join(MANIFEST_FILE_NAME);
+		let write = async |description: &str, modified: SystemTime| {
+			fs::write(&path, format!("description = \"{description}\"\n"))
+				.await
+				.unwrap();
+			std::fs::File::options()
+				.write(true)
+				.open(&path)
+				.unwrap()
+				.set_modified(modified)
+				.unwrap();
+		};
+		let description = async |project: &Project| {
+			project
+				.clone()
+				.subproject(Importer::root())
+				.deser_manifest()
+				.await
+				.unwrap()
+				.description
+				.clone()
+		};
+
+		let project = test_project(dir.path(), dir.path());
+
+		let first = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000);
+		write("a", first).await;
+		assert_eq!(description(&project).await.as_deref(), Some("a"));
+
+		// an unchanged modification time means the cached manifest is still used
+		write("b", first).await;
+		assert_eq!(description(&project).await.as_deref(), Some("a"));
+
+		project.clear_manifest_cache().await;
+		assert_eq!(description(&project).await.as_deref(), Some("b"));
+
+		write("c", first + std::time::Duration::from_secs(1)).await;
+		assert_eq!(description(&project).await.as_deref(), Some("c"));
+	}
+
+	#[tokio::test]
+	async fn discover_workspace_members() {
+		let dir = tempfile::tempdir().unwrap();
+		for manifest_dir in [
+			"",
+			"packages/a",
+			"packages/a/nested",
+			"tools/b",
+			"pesde/dependencies/c",
+			"packages/a/pesde/dependencies/d",
+			".git/e",
+		] {
+			let manifest_dir = dir.path().join(manifest_dir);
+			fs::create_dir_all(&manifest_dir).await.unwrap();
+			fs::write(manifest_dir.join(MANIFEST_FILE_NAME), "")
+				.await
+				.unwrap();
+		}
+		fs::create_dir_all(dir.path().join("docs")).await.unwrap();
+
+		let workspace = ManifestWorkspace {
+			discover_members: true,
+			..Default::default()
+		};
+		let members = workspace_members(dir.path(), &workspace).await.unwrap();
+		assert_eq!(
+			members,
+			HashSet::from(
+				["packages/a", "packages/a/nested", "tools/b"]
+					.map(|member| dir.path().join(member))
+			)
+		);
+
+		// explicit globs are authoritative
+		let workspace = ManifestWorkspace {
+			members: vec!["tools/*".into()],
+			discover_members: true,
+			..Default::default()
+		};
+		let members = workspace_members(dir.path(), &workspace).await.unwrap();
+		assert_eq!(members, HashSet::from([dir.path().join("tools/b")]));
+	}
+
+	#[tokio::test]
+	#[expect(deprecated)]
+	async fn requests_use_proxy() {
+		use crate::source::pesde::PesdePackageSource;
+		use crate::source::pesde::backend::PesdePackageSourceBackend as _;
+		let requests = Arc::new(std::sync::Mutex::new(vec![]));
+		let proxy = mock_http_server({
+			let requests = requests.clone();
+			move |request| {
+				requests.lock().unwrap().push(request.to_string());
+				json_response("404 Not Found", "")
+			}
+		})
+		.await;
+		let proxy = reqwest::Proxy::http(format!("http://{proxy}")).unwrap();
+
+		let dir = tempfile::tempdir().unwrap();
+		let new_project = |auth_config, reqwest| {
+			Project::new(
+				dir.path(),
+				dir.path().join("data"),
+				dir.path().join("cas"),
+				auth_config,
+				reqwest,
+			)
+		};
+		let projects = [
+			new_project(
+				AuthConfig::new(),
+				reqwest::Client::builder()
+					.proxy(proxy.clone())
+					.build()
+					.unwrap(),
+			),
+			new_project(
+				AuthConfig::new().with_proxy(Some(proxy)),
+				reqwest::Client::new(),
+			),
+		];
+
+		// the index's host doesn't exist, so the request can only be answered by the proxy
+		let source = PesdePackageSource::from_url("api+http://index.invalid".parse().unwrap());
+		for project in &projects {
+			let file = source
+				.repo()
+				.read_index_file(project, "acme/foo".parse().unwrap())
+				.await
+				.unwrap();
+			assert!(file.is_none());
+		}
+
+		let requests = requests.lock().unwrap();
+		assert_eq!(requests.len(), 2);
+		for request in requests.iter() {
+			assert!(
+				request.starts_with("GET http://index.invalid/v1/index/acme/foo HTTP/1.1\r\n"),
+				"{request}"
+			);
+		}
+	}
+
+	#[tokio::test]
+	async fn install_into_prefix() {
+		let (dir, project) = path_dependency_project("", "").await;
+		let project_dir = project.dir().to_path_buf();
+		let prefix = dir.path().join("prefix");
+		let project = project.with_prefix(&prefix);
+
+		project
+			.install(crate::download_and_link::InstallOptions::<()>::new().write_lockfile(false))
+			.await
+			.unwrap();
+
+		let dependencies_dir = project.subproject(Importer::root()).dependencies_dir();
+		assert!(dependencies_dir.starts_with(&prefix));
+		assert!(fs::metadata(&dependencies_dir).await.unwrap().is_dir());
+
+		let mut entries = fs::read_dir(&project_dir).await.unwrap();
+		let mut names = vec![];
+		while let Some(entry) = entries.next_entry().await.unwrap() {
+			names.push(entry.file_name());
+		}
+		assert_eq!(names, [MANIFEST_FILE_NAME]);
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	async fn install_into_prefix_on_other_filesystem() {
+		use std::os::unix::fs::MetadataExt as _;
+
+		let dir = tempfile::tempdir().unwrap();
+		// files can't be hard linked from the CAS to another filesystem, which needs a tmpfs here
+		let Ok(prefix) = tempfile::tempdir_in("/dev/shm") else {
+			return;
+		};
+		let device = async |path: &Path| fs::metadata(path).await.unwrap().dev();
+		if device(dir.path()).await == device(prefix.path()).await {
+			return;
+		}
+
+		let project_dir = index_dependency_project(dir.path()).await;
+		let project = test_project(dir.path(), &project_dir).with_prefix(prefix.path());
+
+		project
+			.install(crate::download_and_link::InstallOptions::<()>::new())
+			.await
+			.unwrap();
+
+		let packages_dir = project
+			.subproject(Importer::root())
+			.dependencies_dir()
+			.join("packages");
+		assert!(packages_dir.starts_with(prefix.path()));
+		assert!(
+			fs::read_to_string(packages_dir.join("foo.luau"))
+				.await
+				.unwrap()
+				.contains("require(")
+		);
+	}
+
+	#[tokio::test]
+	async fn install_reports_resolved_graph_before_download() {
+		use crate::reporters::DownloadsReporter;
+		use crate::reporters::InstallReporter;
+		use crate::reporters::InstallStage;
+		use crate::reporters::PatchesReporter;
+		use crate::reporters::ResolveReporter;
+
+		#[derive(Debug, Default)]
+		struct Events(std::sync::Mutex<Vec<String>>);
+
+		impl DownloadsReporter for Events {
+			type DownloadProgressReporter = ();
+
+			fn report_download(self: Arc<Self>, _name: String) {}
+		}
+
+		impl PatchesReporter for Events {
+			type PatchProgressReporter = ();
+
+			fn report_patch(self: Arc<Self>, _name: String) {}
+		}
+
+		impl ResolveReporter for Events {}
+
+		impl InstallReporter for Events {
+			fn report_stage(&self, stage: InstallStage) {
+				self.0.lock().unwrap().push(format!("{stage:?}"));
+			}
+
+			fn report_resolved(&self, graph: &crate::graph::DependencyGraph) {
+				self.0
+					.lock()
+					.unwrap()
+					.push(format!("resolved {}", graph.nodes.len()));
+			}
+		}
+
+		let (_dir, project) = path_dependency_project("", "").await;
+		let events = Arc::new(Events::default());
+
+		project
+			.install(
+				crate::download_and_link::InstallOptions::<Events>::new().download_and_link(
+					crate::download_and_link::DownloadAndLinkOptions::<Events>::new()
+						.reporter(events.clone()),
+				),
+			)
+			.await
+			.unwrap();
+
+		assert_eq!(
+			*events.0.lock().unwrap(),
+			["Resolve", "resolved 1", "Download"]
+		);
+	}
+
+	#[tokio::test]
+	async fn resolve_lockfile_does_not_write() {
+		let (_dir, project) = path_dependency_project("", "").await;
+		let project_dir = project.dir().to_path_buf();
+
+		let lockfile = project
+			.resolve_lockfile(false, &RefreshedSources::new())
+			.await
+			.unwrap();
+
+		let root = &lockfile.graph.importers[&Importer::root()];
+		assert!(root.dependencies.contains_key(&"foo".parse().unwrap()));
+		assert_eq!(lockfile.graph.nodes.len(), 1);
+		assert!(
+			fs::metadata(project_dir.join(LOCKFILE_FILE_NAME))
+				.await
+				.is_err()
+		);
+	}
+	#[tokio::test]
+	async fn graph_from_lockfile() {
+		let (_dir, project) = path_dependency_project("", "").await;
+		assert!(project.graph_from_lockfile().await.unwrap().is_none());
+
+		let lockfile = project
+			.resolve_lockfile(false, &RefreshedSources::new())
+			.await
+			.unwrap();
+		project.write_lockfile(&lockfile).await.unwrap();
+
+		let graph = project.graph_from_lockfile().await.unwrap().unwrap();
+		assert_eq!(graph.nodes.len(), 1);
+		assert!(
+			graph.importers[&Importer::root()]
+				.dependencies
+				.contains_key(&"foo".parse().unwrap())
+		);
+
+		// only a missing lockfile is treated as there being no graph
+		fs::write(project.dir().join(LOCKFILE_FILE_NAME), "not a lockfile")
+			.await
+			.unwrap();
+		assert!(project.graph_from_lockfile().await.is_err());
+	}
+
+	#[tokio::test]
+	async fn link_types_only_package() {
+		use crate::source::local_index::LocalIndex;
+
+		let dir = tempfile::tempdir().unwrap();
+		let mut index = LocalIndex::new(&dir.path().join("index"));
+		index
+			.publish(
+				"acme/types",
+				"1.0.0",
+				"{ environment = \"luau\", lib = \"types.luau\" }",
+				&[(
+					"types.luau",
+					"export type Point = { x: number, y: number }\nexport type Shape = { Point }\nreturn nil\n",
+				)],
+			)
+			.await;
+
+		let project_dir = dir.path().join("project");
+		fs::create_dir_all(&project_dir).await.unwrap();
+		fs::write(
+			project_dir.join(MANIFEST_FILE_NAME),
+			format!(
+				"[indices]\ndefault = \"{}\"\n\n[dependencies]\ntypes = {{ name = \"acme/types\", version = \"^1.0.0\", target = \"luau\" }}\n",
+				index.url()
+			),
+		)
+		.await
+		.unwrap();
+		let project = test_project(dir.path(), &project_dir);
+
+		project
+			.install(crate::download_and_link::InstallOptions::<()>::new())
+			.await
+			.unwrap();
+
+		// consumers can refer to the types through the linking module, e.g. `types.Point`
+		let linker = fs::read_to_string(
+			project
+				.subproject(Importer::root())
+				.dependencies_dir()
+				.join("packages")
+				.join("types.luau"),
+		)
+		.await
+		.unwrap();
+		let linker = linker.split_whitespace().collect::<Vec<_>>().join(" ");
+		assert!(
+			linker.contains("export type Point = module.Point"),
+			"{linker}"
+		);
+		assert!(
+			linker.contains("export type Shape = module.Shape"),
+			"{linker}"
+		);
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	async fn linked_bins_are_executable() {
+		use crate::source::RealmExt as _;
+		use crate::source::fs::cas_path;
+		use crate::source::local_index::LocalIndex;
+		use std::os::unix::fs::PermissionsExt as _;
+
+		let dir = tempfile::tempdir().unwrap();
+		let mut index = LocalIndex::new(&dir.path().join("index"));
+		index
+			.publish(
+				"acme/tool",
+				"1.0.0",
+				"{ environment = \"luau\", bin = \"main.luau\" }",
+				&[("main.luau", "print('hello')")],
+			)
+			.await;
+
+		let project_dir = dir.path().join("project");
+		fs::create_dir_all(&project_dir).await.unwrap();
+		fs::write(
+			project_dir.join(MANIFEST_FILE_NAME),
+			format!(
+				"[indices]\ndefault = \"{}\"\n\n[dependencies]\ntool = {{ name = \"acme/tool\", version = \"^1.0.0\", target = \"luau\" }}\n",
+				index.url()
+			),
+		)
+		.await
+		.unwrap();
+		let project = test_project(dir.path(), &project_dir);
+
+		project
+			.install(crate::download_and_link::InstallOptions::<()>::new())
+			.await
+			.unwrap();
+
+		let is_executable =
+			async |path: &Path| fs::metadata(path).await.unwrap().permissions().mode() & 0o111 != 0;
+		let graph = project.graph_from_lockfile().await.unwrap().unwrap();
+		let (id, _, _) = &graph.importers[&Importer::root()].dependencies[&"tool".parse().unwrap()];
+		let packages_dir = project
+			.clone()
+			.subproject(Importer::root())
+			.dependencies_dir()
+			.join(graph.realm_of(&Importer::root(), id).packages_dir());
+
+		assert!(is_executable(&packages_dir.join("tool.bin.luau")).await);
+		let bin_file = packages_dir
+			.join(PACKAGES_CONTAINER_NAME)
+			.join(graph::DependencyGraphNode::container_dir(
+				id,
+				&graph.nodes[id].structure_kind,
+			))
+			.join("main.luau");
+		assert!(is_executable(&bin_file).await);
+
+		// the files in the CAS, which other installs link to, keep their permissions
+		let hash =
+			crate::hash::Hash::from_bytes(crate::hash::HashAlgorithm::default(), b"print('hello')");
+		assert!(!is_executable(&cas_path(&hash, &dir.path().join("cas"))).await);
+	}
+
+	#[tokio::test]
+	async fn write_lockfile_sources_file() {
+		let (_dir, project) =
+			path_dependency_project("[workspace]\nsources_file = true\n", "").await;
+		let lockfile = project
+			.resolve_lockfile(false, &RefreshedSources::new())
+			.await
+			.unwrap();
+		let sources_file = project.dir().join(SOURCES_FILE_NAME);
+
+		project.write_lockfile(&lockfile).await.unwrap();
+		assert!(fs::metadata(&sources_file).await.is_ok());
+
+		// disabling the sources file removes the stale one
+		fs::write(
+			project.dir().join(MANIFEST_FILE_NAME),
+			"[dependencies]\nfoo = { path = \"../foo\" }\n",
+		)
+		.await
+		.unwrap();
+		project.clear_manifest_cache().await;
+		project.write_lockfile(&lockfile).await.unwrap();
+		assert!(fs::metadata(&sources_file).await.is_err());
+
+		// nothing is written if the manifest can't be read
+		fs::remove_file(project.dir().join(LOCKFILE_FILE_NAME))
+			.await
+			.unwrap();
+		fs::write(project.dir().join(MANIFEST_FILE_NAME), "not a manifest")
+			.await
+			.unwrap();
+		project.clear_manifest_cache().await;
+		assert!(project.write_lockfile(&lockfile).await.is_err());
+		assert!(
+			fs::metadata(project.dir().join(LOCKFILE_FILE_NAME))
+				.await
+				.is_err()
+		);
+	}
+}
@@ -92,15 +92,61 @@ pub(crate) fn get_file_types(file: &str) -> Vec<String> {
 	visitor.types
 }
 
+/// The default template for library linking modules
+pub const DEFAULT_LIB_TEMPLATE: &str =
+	"local module = require({REQUIRE_PATH})\n{TYPES}\nreturn module";
+
+/// The default template for binary linking modules
+pub const DEFAULT_BIN_TEMPLATE: &str =
+	"_G.PESDE_ROOT = \"{PACKAGE_ROOT}\"\nreturn require({REQUIRE_PATH})";
+
+/// Replaces the `{NAME}` placeholders of a template in a single pass, so values containing
+/// placeholders are left as they are. Unknown placeholders are kept
+fn fill_template(template: &str, values: &[(&str, &str)]) -> String {
+	let mut filled = String::with_capacity(template.len());
+	let mut rest = template;
+
+	'outer: while let Some(start) = rest.find('{') {
+		filled.push_str(&rest[..start]);
+		rest = &rest[start + 1..];
+
+		for (name, value) in values {
+			if let Some(after) = rest
+				.strip_prefix(name)
+				.and_then(|after| after.strip_prefix('}'))
+			{
+				filled.push_str(value);
+				rest = after;
+				continue 'outer;
+			}
+		}
+
+		filled.push('{');
+	}
+
+	filled.push_str(rest);
+	filled
+}
+
 /// Generate a linking module for a library
 #[must_use]
 pub fn generate_lib_linking_module(
 	path: &str,
 	types: impl IntoIterator<Item = impl Display>,
 ) -> String {
-	let types = types.into_iter().format_with("\n", |ty, f| f(&ty));
+	generate_lib_linking_module_from_template(DEFAULT_LIB_TEMPLATE, path, types)
+}
+
+/// Generate a linking module for a library from a template, see [DEFAULT_LIB_TEMPLATE]
+#[must_use]
+pub fn generate_lib_linking_module_from_template(
+	template: &str,
+	path: &str,
+	types: impl IntoIterator<Item = impl Display>,
+) -> String {
+	let types = types.into_iter().join("\n");
 
-	format!("local module = require({path})\n{types}\nreturn module")
+	fill_template(template, &[("REQUIRE_PATH", path), ("TYPES", &types)])
 }
 
 fn luau_style_path(path: &Path) -> impl Display {
@@ -191,10 +237,25 @@ pub fn get_lib_require_path(
 /// Generate a linking module for a binary
 #[must_use]
 pub fn generate_bin_linking_module(package_root: &Path, require_path: &str) -> String {
-	format!(
-		r#"_G.PESDE_ROOT = "{}"
-return require({require_path})"#,
-		package_root.to_string_lossy().escape_default()
+	generate_bin_linking_module_from_template(DEFAULT_BIN_TEMPLATE, package_root, require_path)
+}
+
+/// Generate a linking module for a binary from a template, see [DEFAULT_BIN_TEMPLATE]
+#[must_use]
+pub fn generate_bin_linking_module_from_template(
+	template: &str,
+	package_root: &Path,
+	require_path: &str,
+) -> String {
+	fill_template(
+		template,
+		&[
+			("REQUIRE_PATH", require_path),
+			(
+				"PACKAGE_ROOT",
+				&package_root.to_string_lossy().escape_default().to_string(),
+			),
+		],
 	)
 }
 
@@ -227,3 +288,47 @@ pub mod errors {
 		RealmPathNotFound(crate::source::Realm),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn default_templates() {
+		assert_eq!(
+			generate_lib_linking_module(
+				r#""./foo""#,
+				["export type A = module.A", "export type B = module.B"]
+			),
+			"local module = require(\"./foo\")\nexport type A = module.A\nexport type B = module.B\nreturn module"
+		);
+		assert_eq!(
+			generate_lib_linking_module(r#""./foo""#, Vec::<String>::new()),
+			"local module = require(\"./foo\")\n\nreturn module"
+		);
+		assert_eq!(
+			generate_bin_linking_module(Path::new("/a/\"b\""), r#""./bin""#),
+			"_G.PESDE_ROOT = \"/a/\\\"b\\\"\"\nreturn require(\"./bin\")"
+		);
+	}
+
+	#[test]
+	fn custom_templates() {
+		assert_eq!(
+			generate_lib_linking_module_from_template(
+				"local m = shim({REQUIRE_PATH}) {TYPES} {UNKNOWN} { x = 1 } return m",
+				r#""./{TYPES}""#,
+				["export type A = module.A"]
+			),
+			"local m = shim(\"./{TYPES}\") export type A = module.A {UNKNOWN} { x = 1 } return m"
+		);
+		assert_eq!(
+			generate_bin_linking_module_from_template(
+				"run({REQUIRE_PATH}, \"{PACKAGE_ROOT}\")",
+				Path::new("/a"),
+				r#""./bin""#
+			),
+			"run(\"./bin\", \"/a\")"
+		);
+	}
+}
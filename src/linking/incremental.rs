@@ -37,7 +37,7 @@ impl Project {
 					.dependencies
 					.iter()
 					.filter(|(_, (id, _, _))| graph.realm_of(&importer, id) == realm)
-					.map(|(alias, _)| format!("{alias}.luau"))
+					.flat_map(|(alias, _)| [format!("{alias}.luau"), format!("{alias}.bin.luau")])
 					.collect::<HashSet<_>>();
 
 				let mut queue = graph.importers[&importer]
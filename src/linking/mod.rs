@@ -8,6 +8,9 @@ use crate::source::PackageExports;
 use crate::source::PackageRefs;
 use crate::source::RealmExt as _;
 use crate::source::StructureKind;
+use crate::source::fs::hard_link_or_copy;
+use crate::source::fs::make_executable;
+use crate::source::fs::make_executable_copy;
 use crate::source::fs::store_in_cas;
 use crate::source::ids::PackageId;
 use crate::util::ToEscaped as _;
@@ -46,7 +49,20 @@ async fn write_cas(destination: PathBuf, cas_dir: &Path, contents: &str) -> std:
 		Err(e) => return Err(e),
 	}
 
-	fs::hard_link(cas_path, destination).await
+	hard_link_or_copy(&cas_path, &destination).await
+}
+
+/// Writes an executable file, which unlike [write_cas] isn't linked to the CAS, as that would make
+/// the file in the CAS executable too
+async fn write_executable(destination: &Path, contents: &str) -> std::io::Result<()> {
+	match fs::remove_file(destination).await {
+		Ok(_) => {}
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+		Err(e) => return Err(e),
+	}
+
+	fs::write(destination, contents).await?;
+	make_executable(destination).await
 }
 
 impl Project {
@@ -180,26 +196,51 @@ impl Project {
 						fs::create_dir_all(&dirs.base).await?;
 					}
 
-					if let Some(bin_file) = exports.bin_file.as_deref() {
+					if let Some(bin_file) = exports.bin_file.as_deref()
+						&& !exports.bin_platforms.is_current_supported()
+					{
+						tracing::warn!(
+							"not linking bin export of {id} ({bin_file}) as it does not support the current platform ({})",
+							exports.bin_platforms
+						);
+					} else if let Some(bin_file) = exports.bin_file.as_deref() {
 						let destination = dirs
 							.base
 							.join(alias.as_str())
 							.with_added_extension("bin.luau");
 
-						let bin_module = generator::generate_bin_linking_module(
-							&dirs.destination,
-							&generator::get_bin_require_path(
-								&dirs.base,
-								bin_file,
-								&dirs.destination,
-							),
-						);
-						let cas_dir = subproject.project().cas_dir().to_path_buf();
+						let require_path =
+							generator::get_bin_require_path(&dirs.base, bin_file, &dirs.destination);
+						let package_root = dirs.destination.clone();
+						let bin_path = bin_file.to_path(&dirs.destination);
+						let subproject = subproject.clone();
 
 						tasks.spawn(async move {
-							write_cas(destination, &cas_dir, &bin_module)
-								.await
-								.map_err(Into::into)
+							let bin_module = generator::generate_bin_linking_module_from_template(
+								subproject
+									.deser_manifest()
+									.await?
+									.linker_templates
+									.bin
+									.as_deref()
+									.unwrap_or(generator::DEFAULT_BIN_TEMPLATE),
+								&package_root,
+								&require_path,
+							);
+
+							write_executable(&destination, &bin_module).await?;
+
+							// files of path dependencies are symlinks to the user's files, which are left alone
+							match fs::symlink_metadata(&bin_path).await {
+								Ok(metadata) if metadata.is_file() => {
+									make_executable_copy(&bin_path).await?;
+								}
+								Ok(_) => {}
+								Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+								Err(e) => return Err(e.into()),
+							}
+
+							Ok(())
 						});
 					}
 
@@ -210,13 +251,19 @@ impl Project {
 						let cas_dir = subproject.project().cas_dir().to_path_buf();
 
 						tasks.spawn(async move {
-							let lib_module = generator::generate_lib_linking_module(
+							let manifest = subproject.deser_manifest().await?;
+							let lib_module = generator::generate_lib_linking_module_from_template(
+								manifest
+									.linker_templates
+									.lib
+									.as_deref()
+									.unwrap_or(generator::DEFAULT_LIB_TEMPLATE),
 								&generator::get_lib_require_path(
 									realm,
 									&lib_file,
 									&dirs,
 									&structure_kind,
-									&*subproject.deser_manifest().await?,
+									&manifest,
 								)
 								.map_err(|e| {
 									errors::LinkingErrorKind::GetLibRequirePath(
@@ -1,5 +1,8 @@
 //! Lockfile
 use crate::graph::DependencyGraph;
+use crate::hash::Hash;
+use crate::source::ids::PackageId;
+use semver::Version;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -33,6 +36,39 @@ pub fn parse_lockfile(lockfile: &str) -> Result<Lockfile, errors::ParseLockfileE
 	}
 }
 
+/// A package in the sources file
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SourcesFileEntry {
+	/// The ID of the package
+	pub id: PackageId,
+	/// The source the package is from
+	pub source: String,
+	/// The reference to the package within its source, such as its name or Git tree
+	#[serde(rename = "ref")]
+	pub pkg_ref: String,
+	/// The version of the package
+	pub version: Version,
+	/// The checksum of the package
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub checksum: Option<Hash>,
+}
+
+/// Lists the packages of a graph for the sources file
+#[must_use]
+pub fn sources_file(graph: &DependencyGraph) -> Vec<SourcesFileEntry> {
+	graph
+		.nodes
+		.iter()
+		.map(|(id, node)| SourcesFileEntry {
+			id: id.clone(),
+			source: id.source().to_string(),
+			pkg_ref: id.pkg_ref().to_string(),
+			version: id.version().clone(),
+			checksum: node.checksum.clone(),
+		})
+		.collect()
+}
+
 /// Errors that can occur when working with lockfiles
 pub mod errors {
 	use thiserror::Error;
@@ -55,3 +91,44 @@ pub mod errors {
 		De(#[from] toml::de::Error),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sources_file_matches_graph() {
+		let graph: DependencyGraph = toml::from_str(
+			r#"
+			[importers."".dependencies]
+			a = ["wally:https://github.com/pesde-pkg/index:foo/a@1.0.0", { path = "a" }, "standard"]
+
+			[nodes."wally:https://github.com/pesde-pkg/index:foo/a@1.0.0"]
+			structure_kind = "pesde_v2"
+			dependencies = { b = ["path:/b", "standard"] }
+
+			[nodes."path:/b"]
+			structure_kind = "pesde_v2"
+			"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			serde_json::to_value(sources_file(&graph)).unwrap(),
+			serde_json::json!([
+				{
+					"id": "wally:https://github.com/pesde-pkg/index:foo/a@1.0.0",
+					"source": "wally:https://github.com/pesde-pkg/index",
+					"ref": "wally:foo/a",
+					"version": "1.0.0",
+				},
+				{
+					"id": "path:/b",
+					"source": "path",
+					"ref": "path:/b",
+					"version": "0.0.0-pesde",
+				},
+			])
+		);
+	}
+}
@@ -1,8 +1,12 @@
 use crate::cli::PESDE_DIR;
+use crate::cli::auth::get_env_tokens;
 use crate::cli::auth::get_tokens;
 use crate::cli::display_err;
+use crate::cli::reporters::CliRefreshReporter;
+use crate::cli::with_manifest_dependencies_dir;
 use anyhow::Context as _;
 use clap::Parser;
+use clap::ValueEnum;
 use clap::builder::styling::AnsiColor;
 use cli::data_dir;
 use fs_err::tokio as fs;
@@ -10,14 +14,20 @@ use indicatif::MultiProgress;
 use pesde::AuthConfig;
 use pesde::Project;
 use pesde::find_roots;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
 use std::io;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 use tempfile::NamedTempFile;
 use tokio::task::spawn_blocking;
 use tracing::instrument;
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::Layer as _;
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::fmt::MakeWriter;
 use tracing_subscriber::layer::SubscriberExt as _;
@@ -26,6 +36,10 @@ use tracing_subscriber::util::SubscriberInitExt as _;
 mod cli;
 pub mod util;
 
+#[cfg(test)]
+#[path = "source/pesde/local_index.rs"]
+mod local_index;
+
 const STYLES: clap::builder::Styles = clap::builder::Styles::styled()
 	.header(AnsiColor::Yellow.on_default().underline())
 	.usage(AnsiColor::Yellow.on_default().underline())
@@ -44,12 +58,95 @@ struct Cli {
 	#[arg(short = 'v', short_alias = 'V', long, action = clap::builder::ArgAction::Version)]
 	version: (),
 
+	/// The format to write logs in
+	#[arg(long, global = true, value_enum, env = "PESDE_LOG_FORMAT", default_value_t = LogFormat::Pretty)]
+	log_format: LogFormat,
+
+	/// Whether to write output without colors. Also enabled by setting the `NO_COLOR` environment variable
+	#[arg(long, global = true)]
+	no_color: bool,
+
 	#[command(subcommand)]
 	subcommand: cli::commands::Subcommand,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum LogFormat {
+	/// Human-readable logs
+	Pretty,
+	/// One JSON object per line, useful for log aggregation
+	Json,
+}
+
+async fn try_hard_link(file: &Path, dir: &Path, file_name: &OsStr) -> bool {
+	let try_path = dir.join(file_name);
+
+	if fs::hard_link(file, &try_path).await.is_err() {
+		return false;
+	}
+
+	if let Err(err) = fs::remove_file(&try_path).await {
+		tracing::warn!(
+			"failed to remove temporary file at {}: {err}",
+			try_path.display()
+		);
+	}
+
+	true
+}
+
+type LinkableDirsCache = BTreeMap<PathBuf, PathBuf>;
+
+fn linkable_dirs_cache_path() -> anyhow::Result<PathBuf> {
+	Ok(data_dir()?.join("linkable_dirs.json"))
+}
+
+async fn read_linkable_dirs_cache(cache_path: &Path) -> LinkableDirsCache {
+	match fs::read_to_string(cache_path).await {
+		Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|err| {
+			tracing::debug!("ignoring invalid linkable directories cache: {err}");
+			LinkableDirsCache::new()
+		}),
+		Err(_) => LinkableDirsCache::new(),
+	}
+}
+
+/// Writes the cache to a temporary file which then replaces the cache, so that concurrent pesde
+/// processes never read a partially written cache
+async fn write_linkable_dirs_cache(
+	cache_path: &Path,
+	cache: &LinkableDirsCache,
+) -> anyhow::Result<()> {
+	let parent = cache_path
+		.parent()
+		.context("linkable directories cache has no parent directory")?
+		.to_path_buf();
+	fs::create_dir_all(&parent)
+		.await
+		.context("failed to create data directory")?;
+
+	let temp_path = spawn_blocking(move || NamedTempFile::new_in(parent))
+		.await
+		.unwrap()
+		.context("failed to create temporary file")?
+		.into_temp_path();
+	fs::write(&temp_path, serde_json::to_string(cache)?)
+		.await
+		.context("failed to write linkable directories cache")?;
+	temp_path
+		.persist(cache_path)
+		.map_err(|e| e.error)
+		.context("failed to replace linkable directories cache")
+}
+
 #[instrument(level = "trace")]
 async fn get_linkable_dir(path: &Path) -> PathBuf {
+	let cache_path = linkable_dirs_cache_path();
+	let mut cache = match &cache_path {
+		Ok(cache_path) => read_linkable_dirs_cache(cache_path).await,
+		Err(_) => LinkableDirsCache::new(),
+	};
+
 	let mut curr_path = PathBuf::new();
 	let (file_to_try, temp_path) = {
 		let path = path.to_path_buf();
@@ -70,6 +167,18 @@ async fn get_linkable_dir(path: &Path) -> PathBuf {
 
 	let temp_file_name = temp_path.file_name().expect("failed to get file name");
 
+	if let Some(cached) = cache.get(path) {
+		if try_hard_link(&file_to_try, cached, temp_file_name).await {
+			tracing::debug!("using cached linkable directory {}", cached.display());
+			return cached.clone();
+		}
+
+		tracing::debug!(
+			"cached linkable directory {} is no longer linkable",
+			cached.display()
+		);
+	}
+
 	// C: and \ are different components on Windows
 	#[cfg(windows)]
 	let components = path.components().map(|c| {
@@ -86,14 +195,14 @@ async fn get_linkable_dir(path: &Path) -> PathBuf {
 	for component in components {
 		curr_path.push(component);
 
-		let try_path = curr_path.join(temp_file_name);
-
-		if fs::hard_link(&file_to_try, &try_path).await.is_ok() {
-			if let Err(err) = fs::remove_file(&try_path).await {
-				tracing::warn!(
-					"failed to remove temporary file at {}: {err}",
-					try_path.display()
-				);
+		if try_hard_link(&file_to_try, &curr_path, temp_file_name).await {
+			cache.insert(path.to_path_buf(), curr_path.clone());
+			let written = match &cache_path {
+				Ok(cache_path) => write_linkable_dirs_cache(cache_path, &cache).await,
+				Err(err) => Err(anyhow::anyhow!("{err:#}")),
+			};
+			if let Err(err) = written {
+				tracing::warn!("failed to cache linkable directory: {err:#}");
 			}
 
 			return curr_path;
@@ -150,9 +259,22 @@ impl<'a> MakeWriter<'a> for IndicatifWriter {
 	}
 }
 
+/// Whether output should be written without colors, given the `--no-color` flag and the `NO_COLOR` environment variable
+fn no_color(flag: bool, env: Option<&std::ffi::OsStr>) -> bool {
+	// see https://no-color.org
+	flag || env.is_some_and(|value| !value.is_empty())
+}
+
 async fn run() -> anyhow::Result<()> {
+	let cli = Cli::parse();
 	let cwd = std::env::current_dir().expect("failed to get current working directory");
 
+	let no_color = no_color(cli.no_color, std::env::var_os("NO_COLOR").as_deref());
+	if no_color {
+		console::set_colors_enabled(false);
+		console::set_colors_enabled_stderr(false);
+	}
+
 	let tracing_env_filter = EnvFilter::builder()
 		.with_default_directive(LevelFilter::INFO.into())
 		.from_env_lossy()
@@ -164,18 +286,30 @@ async fn run() -> anyhow::Result<()> {
 		.add_directive("hyper=info".parse().unwrap())
 		.add_directive("h2=info".parse().unwrap());
 
-	let fmt_layer = tracing_subscriber::fmt::layer().with_writer(IndicatifWriter);
+	let fmt_layer = match cli.log_format {
+		LogFormat::Pretty => {
+			let fmt_layer = tracing_subscriber::fmt::layer()
+				.with_writer(IndicatifWriter)
+				.with_ansi(!no_color);
 
-	#[cfg(debug_assertions)]
-	let fmt_layer = fmt_layer.with_timer(tracing_subscriber::fmt::time::uptime());
+			#[cfg(debug_assertions)]
+			let fmt_layer = fmt_layer.with_timer(tracing_subscriber::fmt::time::uptime());
 
-	#[cfg(not(debug_assertions))]
-	let fmt_layer = fmt_layer
-		.pretty()
-		.with_timer(())
-		.with_line_number(false)
-		.with_file(false)
-		.with_target(false);
+			#[cfg(not(debug_assertions))]
+			let fmt_layer = fmt_layer
+				.pretty()
+				.with_timer(())
+				.with_line_number(false)
+				.with_file(false)
+				.with_target(false);
+
+			fmt_layer.boxed()
+		}
+		LogFormat::Json => tracing_subscriber::fmt::layer()
+			.json()
+			.with_writer(IndicatifWriter)
+			.boxed(),
+	};
 
 	tracing_subscriber::registry()
 		.with(tracing_env_filter)
@@ -199,6 +333,11 @@ async fn run() -> anyhow::Result<()> {
 
 	tracing::debug!("using cas dir in {}", cas_dir.display());
 
+	let mut tokens = get_tokens().await?;
+	tokens.extend(get_env_tokens().await?);
+
+	// proxies from the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables are
+	// picked up by reqwest
 	let reqwest = reqwest::Client::builder()
 		.user_agent(concat!(
 			env!("CARGO_PKG_NAME"),
@@ -207,18 +346,30 @@ async fn run() -> anyhow::Result<()> {
 		))
 		.build()?;
 
-	let subproject = Project::new(
+	let project = Project::new(
 		project_dir,
 		data_dir()?,
 		cas_dir,
-		AuthConfig::new().with_tokens(get_tokens().await?),
+		AuthConfig::new().with_tokens(tokens),
 		reqwest,
 	)
-	.subproject(importer);
+	.with_refresh_reporter(Arc::new(CliRefreshReporter::default()));
+	let project = with_manifest_dependencies_dir(project).await?;
+	let interrupt = project.interrupt().clone();
 
-	let cli = Cli::parse();
+	let command = cli.subcommand.run(project.subproject(importer));
+	tokio::pin!(command);
 
-	cli.subcommand.run(subproject).await
+	tokio::select! {
+		result = &mut command => result,
+		_ = tokio::signal::ctrl_c() => {
+			// give ongoing index refreshes the chance to stop and clean up after themselves
+			interrupt.store(true, Ordering::Relaxed);
+			let _ = tokio::time::timeout(Duration::from_secs(5), command).await;
+
+			anyhow::bail!("interrupted")
+		}
+	}
 }
 
 #[tokio::main]
@@ -230,3 +381,49 @@ async fn main() {
 		std::process::exit(1);
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn no_color_flag_and_env() {
+		for args in [
+			["pesde", "--no-color", "install"],
+			["pesde", "install", "--no-color"],
+		] {
+			assert!(Cli::try_parse_from(args).unwrap().no_color);
+		}
+		assert!(!Cli::try_parse_from(["pesde", "install"]).unwrap().no_color);
+
+		assert!(no_color(true, None));
+		assert!(no_color(false, Some("1".as_ref())));
+		// an empty NO_COLOR doesn't disable colors
+		assert!(!no_color(false, Some("".as_ref())));
+		assert!(!no_color(false, None));
+	}
+
+	#[tokio::test]
+	async fn linkable_dirs_cache_is_replaced() {
+		let dir = tempfile::tempdir().unwrap();
+		let cache_path = dir.path().join("data").join("linkable_dirs.json");
+		assert!(read_linkable_dirs_cache(&cache_path).await.is_empty());
+
+		for target in ["a", "b"] {
+			let cache =
+				LinkableDirsCache::from([(dir.path().join("project"), dir.path().join(target))]);
+			write_linkable_dirs_cache(&cache_path, &cache)
+				.await
+				.unwrap();
+			assert_eq!(read_linkable_dirs_cache(&cache_path).await, cache);
+		}
+
+		// the temporary file the cache is written to was moved into place
+		let mut entries = fs::read_dir(cache_path.parent().unwrap()).await.unwrap();
+		let mut names = vec![];
+		while let Some(entry) = entries.next_entry().await.unwrap() {
+			names.push(entry.file_name());
+		}
+		assert_eq!(names, ["linkable_dirs.json"]);
+	}
+}
@@ -1,14 +1,21 @@
 //! Manifest
+#![expect(deprecated)]
 use crate::GixUrl;
 use crate::ser_display_deser_fromstr;
 use crate::source::DependencySpecifiers;
 use crate::source::PackageExports;
+#[cfg(feature = "patches")]
+use crate::source::PackageRefs;
+#[cfg(feature = "patches")]
+use crate::source::PackageSources;
 use crate::source::Realm;
 #[cfg(feature = "patches")]
 use crate::source::ids::PackageId;
+use crate::source::pesde::target::TargetKind;
 #[cfg(feature = "patches")]
 use relative_path::RelativePathBuf;
 use serde::Deserialize;
+use serde::Deserializer;
 use serde::Serialize;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
@@ -27,6 +34,24 @@ pub struct ManifestIndices {
 	/// The indices to use for the package's Wally dependencies
 	#[serde(default, rename = "wally_indices")]
 	pub wally: BTreeMap<String, GixUrl>,
+	/// The names of the indices to use instead of the default index for pesde dependencies, by target
+	#[serde(default, rename = "target_default_indices")]
+	pub target_defaults: BTreeMap<TargetKind, String>,
+}
+
+impl ManifestIndices {
+	/// The name of the index a pesde dependency with the given target uses, which for the default
+	/// index is the target's default index if it has one
+	#[must_use]
+	pub fn pesde_index_name<'a>(&'a self, index: &'a str, target: TargetKind) -> &'a str {
+		if index != crate::DEFAULT_INDEX_NAME {
+			return index;
+		}
+
+		self.target_defaults
+			.get(&target)
+			.map_or(index, String::as_str)
+	}
 }
 
 /// A specifier for an override
@@ -39,22 +64,233 @@ pub enum OverrideSpecifier {
 	Alias(Alias),
 }
 
+/// The key of a patch, selecting the packages it applies to
+#[cfg(feature = "patches")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PatchKey {
+	/// A single package
+	Exact(PackageId),
+	/// Every version of a package matching the requirement
+	Range(PackageSources, PackageRefs, semver::VersionReq),
+}
+#[cfg(feature = "patches")]
+ser_display_deser_fromstr!(PatchKey);
+
+#[cfg(feature = "patches")]
+impl PatchKey {
+	/// Whether the patch applies to the package
+	#[must_use]
+	pub fn matches(&self, id: &PackageId) -> bool {
+		match self {
+			Self::Exact(exact) => exact == id,
+			Self::Range(source, pkg_ref, req) => {
+				source == id.source()
+					&& pkg_ref == id.pkg_ref()
+					&& crate::version_matches(req, id.version())
+			}
+		}
+	}
+}
+
+#[cfg(feature = "patches")]
+impl Display for PatchKey {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Exact(id) => write!(f, "{id}"),
+			Self::Range(source, pkg_ref, req) => {
+				let pkg_ref: &dyn Display = match pkg_ref {
+					PackageRefs::Pesde(pkg_ref) => pkg_ref,
+					PackageRefs::Wally(pkg_ref) => pkg_ref,
+					PackageRefs::Git(pkg_ref) => pkg_ref,
+					PackageRefs::Path(pkg_ref) => pkg_ref,
+				};
+
+				write!(f, "{source}:{pkg_ref}@{req}")
+			}
+		}
+	}
+}
+
+#[cfg(feature = "patches")]
+impl FromStr for PatchKey {
+	type Err = errors::PatchKeyFromStr;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		use crate::source::errors::PackageRefParseError;
+		use crate::source::errors::PackageSourcesFromStr;
+
+		// exact versions are also valid requirements, so anything which is a version is an exact key
+		let Some((name, req)) = s
+			.rsplit_once('@')
+			.filter(|(_, req)| req.parse::<semver::Version>().is_err())
+			.and_then(|(name, req)| Some((name, req.parse::<semver::VersionReq>().ok()?)))
+		else {
+			return Ok(Self::Exact(s.parse()?));
+		};
+
+		let (tag, name) = name
+			.split_once(':')
+			.ok_or_else(|| errors::PatchKeyFromStrKind::InvalidFormat(s.to_string()))?;
+		if !matches!(tag, "pesde" | "wally") {
+			return Err(errors::PatchKeyFromStrKind::RangeUnsupported(s.to_string()).into());
+		}
+		let (source, pkg_ref) = name
+			.rsplit_once(':')
+			.ok_or_else(|| errors::PatchKeyFromStrKind::InvalidFormat(s.to_string()))?;
+
+		let (source, pkg_ref) = if tag == "pesde" {
+			(
+				PackageSources::Pesde(source.parse().map_err(PackageSourcesFromStr::from)?),
+				PackageRefs::Pesde(pkg_ref.parse().map_err(PackageRefParseError::from)?),
+			)
+		} else {
+			(
+				PackageSources::Wally(source.parse().map_err(PackageSourcesFromStr::from)?),
+				PackageRefs::Wally(pkg_ref.parse().map_err(PackageRefParseError::from)?),
+			)
+		};
+
+		Ok(Self::Range(source, pkg_ref, req))
+	}
+}
+
+#[cfg(feature = "patches")]
+impl PartialOrd for PatchKey {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+#[cfg(feature = "patches")]
+impl Ord for PatchKey {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		match (self, other) {
+			(Self::Exact(a), Self::Exact(b)) => a.cmp(b),
+			(Self::Exact(_), Self::Range(..)) => std::cmp::Ordering::Less,
+			(Self::Range(..), Self::Exact(_)) => std::cmp::Ordering::Greater,
+			(Self::Range(a_source, a_ref, a_req), Self::Range(b_source, b_ref, b_req)) => a_source
+				.cmp(b_source)
+				.then_with(|| a_ref.cmp(b_ref))
+				.then_with(|| a_req.to_string().cmp(&b_req.to_string())),
+		}
+	}
+}
+
 /// The `workspace` field of the manifest
 #[derive(Deserialize, Debug, Clone, Default)]
 #[serde(default, deny_unknown_fields)]
 pub struct ManifestWorkspace {
 	/// A list of globs pointing to workspace members' directories
 	pub members: Vec<String>,
+	/// Whether every directory below the workspace containing a manifest is a member, if there
+	/// are no member globs
+	pub discover_members: bool,
 	/// The patches to apply to packages
 	#[cfg(feature = "patches")]
-	pub patches: BTreeMap<PackageId, RelativePathBuf>,
+	pub patches: BTreeMap<PatchKey, RelativePathBuf>,
 	/// The overrides this workspace has
 	pub overrides: BTreeMap<PackageId, OverrideSpecifier>,
+	/// Whether Wally dependencies are forbidden anywhere in the dependency graph
+	pub forbid_wally: bool,
+	/// Whether prerelease versions may satisfy the version requirements of pesde dependencies
+	pub include_prerelease: bool,
+	/// The directory to install dependencies to, relative to each workspace member's directory
+	pub dependencies_dir: Option<RelativePathBuf>,
+	/// How deeply dependencies may be nested before resolution fails
+	pub max_resolve_depth: Option<usize>,
+	/// Whether to write a JSON file listing the resolved packages next to the lockfile
+	pub sources_file: bool,
+}
+
+/// The operating systems which may be specified in [`BinPlatforms`]
+pub const KNOWN_OSES: &[&str] = &[
+	"linux", "macos", "windows", "freebsd", "netbsd", "openbsd", "android", "ios",
+];
+
+/// The architectures which may be specified in [`BinPlatforms`]
+pub const KNOWN_ARCHES: &[&str] = &["x86", "x86_64", "arm", "aarch64", "riscv64"];
+
+fn deserialize_known<'de, D: Deserializer<'de>>(
+	deserializer: D,
+	known: &[&str],
+) -> Result<Vec<String>, D::Error> {
+	let values = Vec::<String>::deserialize(deserializer)?;
+
+	if let Some(unknown) = values.iter().find(|v| !known.contains(&v.as_str())) {
+		return Err(serde::de::Error::custom(format!(
+			"unknown platform `{unknown}`, expected one of {}",
+			known.join(", ")
+		)));
+	}
+
+	Ok(values)
+}
+
+/// The platforms a bin export supports. An empty list means all are supported
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct BinPlatforms {
+	/// The supported operating systems, as in [`std::env::consts::OS`]
+	#[serde(deserialize_with = "deserialize_oses")]
+	pub os: Vec<String>,
+	/// The supported architectures, as in [`std::env::consts::ARCH`]
+	#[serde(deserialize_with = "deserialize_arches")]
+	pub arch: Vec<String>,
+}
+
+fn deserialize_oses<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+	deserialize_known(deserializer, KNOWN_OSES)
+}
+
+fn deserialize_arches<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+	deserialize_known(deserializer, KNOWN_ARCHES)
+}
+
+impl BinPlatforms {
+	/// Returns whether the current platform is supported
+	#[must_use]
+	pub fn is_current_supported(&self) -> bool {
+		(self.os.is_empty() || self.os.iter().any(|os| os == std::env::consts::OS))
+			&& (self.arch.is_empty() || self.arch.iter().any(|arch| arch == std::env::consts::ARCH))
+	}
+}
+
+impl Display for BinPlatforms {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let os = if self.os.is_empty() {
+			"any".to_string()
+		} else {
+			self.os.join(", ")
+		};
+		let arch = if self.arch.is_empty() {
+			"any".to_string()
+		} else {
+			self.arch.join(", ")
+		};
+
+		write!(f, "os: {os}; arch: {arch}")
+	}
+}
+
+/// The templates of generated linking modules, which default to
+/// [crate::linking::generator::DEFAULT_LIB_TEMPLATE] and [crate::linking::generator::DEFAULT_BIN_TEMPLATE]
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct LinkerTemplates {
+	/// The template for library linking modules. `{REQUIRE_PATH}` is replaced with the argument
+	/// to `require` the library with, and `{TYPES}` with the re-exported types of the library
+	#[serde(default)]
+	pub lib: Option<String>,
+	/// The template for binary linking modules. `{REQUIRE_PATH}` is replaced with the argument
+	/// to `require` the binary with, and `{PACKAGE_ROOT}` with the escaped path of the package
+	#[serde(default)]
+	pub bin: Option<String>,
 }
 
 /// A package manifest
 #[derive(Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
+// the derived implementation is wrapped by the `Deserialize` implementation below to validate the manifest
+#[serde(remote = "Self", deny_unknown_fields)]
 pub struct Manifest {
 	/// The description of the package
 	#[serde(default)]
@@ -68,6 +304,9 @@ pub struct Manifest {
 	/// The scripts of the package
 	#[serde(default)]
 	pub scripts: BTreeMap<String, String>,
+	/// The versions of pesde which may be used to work on this package
+	#[serde(default)]
+	pub required_pesde: Option<semver::VersionReq>,
 	/// The indices this package uses
 	#[serde(flatten)]
 	pub indices: ManifestIndices,
@@ -86,6 +325,12 @@ pub struct Manifest {
 	/// The bin export of this package
 	#[serde(default)]
 	pub bin: Option<RelativePathBuf>,
+	/// The platforms the bin export of this package supports
+	#[serde(default)]
+	pub bin_platforms: BinPlatforms,
+	/// The templates of the linking modules generated for this package's dependencies
+	#[serde(default)]
+	pub linker_templates: LinkerTemplates,
 
 	/// The standard dependencies of the package
 	#[serde(default, deserialize_with = "crate::util::deserialize_no_dup_keys")]
@@ -208,6 +453,29 @@ impl DependencyType {
 		DependencyType::Peer,
 		DependencyType::Dev,
 	];
+
+	/// The key of the manifest table containing dependencies of this type
+	#[must_use]
+	pub fn manifest_key(self) -> &'static str {
+		match self {
+			DependencyType::Standard => "dependencies",
+			DependencyType::Peer => "peer_dependencies",
+			DependencyType::Dev => "dev_dependencies",
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for Manifest {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let manifest = Manifest::deserialize(deserializer)?;
+
+		// checked eagerly so every command reading the manifest reports the conflict
+		manifest
+			.all_dependencies()
+			.map_err(serde::de::Error::custom)?;
+
+		Ok(manifest)
+	}
 }
 
 impl Manifest {
@@ -225,10 +493,13 @@ impl Manifest {
 			(&self.dev_dependencies, DependencyType::Dev),
 		] {
 			for (alias, spec) in deps {
-				if all_deps.insert(alias.clone(), (spec.clone(), ty)).is_some() {
-					return Err(
-						errors::AllDependenciesErrorKind::AliasConflict(alias.clone()).into(),
-					);
+				if let Some((_, other_ty)) = all_deps.insert(alias.clone(), (spec.clone(), ty)) {
+					return Err(errors::AllDependenciesErrorKind::AliasConflict(
+						alias.clone(),
+						other_ty.manifest_key(),
+						ty.manifest_key(),
+					)
+					.into());
 				}
 			}
 		}
@@ -242,6 +513,7 @@ impl Manifest {
 		PackageExports {
 			lib_file: self.lib.clone(),
 			bin_file: self.bin.clone(),
+			bin_platforms: self.bin_platforms.clone(),
 		}
 	}
 }
@@ -273,13 +545,189 @@ pub mod errors {
 		InvalidCharacters(String),
 	}
 
+	/// Errors that can occur when parsing a patch key from a string
+	#[cfg(feature = "patches")]
+	#[derive(Debug, Error, thiserror_ext::Box)]
+	#[thiserror_ext(newtype(name = PatchKeyFromStr))]
+	#[non_exhaustive]
+	pub enum PatchKeyFromStrKind {
+		/// Parsing the package ID failed
+		#[error("error parsing package id")]
+		PackageId(#[from] crate::source::ids::errors::PackageIdParseError),
+
+		/// The format of the patch key is invalid
+		#[error("invalid patch key format `{0}`")]
+		InvalidFormat(String),
+
+		/// Parsing the source failed
+		#[error("error parsing package source")]
+		PackageSource(#[from] crate::source::errors::PackageSourcesFromStr),
+
+		/// Parsing the package reference failed
+		#[error("error parsing package reference")]
+		PackageRef(#[from] crate::source::errors::PackageRefParseError),
+
+		/// The source of the package has no versions which a range could match
+		#[error(
+			"patch key `{0}` uses a version range, but only pesde and Wally packages support them"
+		)]
+		RangeUnsupported(String),
+	}
+
 	/// Errors that can occur when trying to get all dependencies from a manifest
 	#[derive(Debug, Error, thiserror_ext::Box)]
 	#[thiserror_ext(newtype(name = AllDependenciesError))]
 	#[non_exhaustive]
 	pub enum AllDependenciesErrorKind {
-		/// Another specifier is already using the alias
-		#[error("another specifier is already using the alias {0}")]
-		AliasConflict(Alias),
+		/// An alias is used in two dependency tables
+		#[error("alias `{0}` is used in both `{1}` and `{2}`")]
+		AliasConflict(Alias, &'static str, &'static str),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn alias_conflicts_fail_parsing() {
+		let err = toml::from_str::<Manifest>(
+			r#"
+			[dependencies]
+			foo = { path = "foo" }
+
+			[dev_dependencies]
+			bar = { path = "bar" }
+			Foo = { path = "other" }
+			"#,
+		)
+		.unwrap_err();
+
+		assert!(
+			err.message()
+				.contains("alias `Foo` is used in both `dependencies` and `dev_dependencies`"),
+			"{err}"
+		);
+	}
+
+	#[test]
+	#[cfg(feature = "patches")]
+	fn patch_key_ranges() {
+		let key = "wally:https://github.com/pesde-pkg/index:foo/bar@>=1.2, <2"
+			.parse::<PatchKey>()
+			.unwrap();
+		assert!(matches!(key, PatchKey::Range(..)));
+		assert_eq!(key.to_string().parse::<PatchKey>().unwrap(), key);
+
+		for (version, matches) in [("1.2.0", true), ("1.9.3", true), ("2.0.0", false)] {
+			let id = format!("wally:https://github.com/pesde-pkg/index:foo/bar@{version}")
+				.parse::<PackageId>()
+				.unwrap();
+			assert_eq!(key.matches(&id), matches, "{version}");
+		}
+
+		let other = "wally:https://github.com/pesde-pkg/index:foo/baz@1.2.0"
+			.parse::<PackageId>()
+			.unwrap();
+		assert!(!key.matches(&other));
+
+		let pesde = "pesde:https://github.com/pesde-pkg/index:foo/bar+lune@^1"
+			.parse::<PatchKey>()
+			.unwrap();
+		assert_eq!(
+			pesde.to_string(),
+			"pesde:https://github.com/pesde-pkg/index:foo/bar+lune@^1"
+		);
+	}
+
+	#[test]
+	#[cfg(feature = "patches")]
+	fn patch_key_exact() {
+		let serialized = "pesde:https://github.com/pesde-pkg/index:foo/bar+lune@1.2.3";
+		let key = serialized.parse::<PatchKey>().unwrap();
+		assert!(matches!(key, PatchKey::Exact(_)));
+		assert_eq!(key.to_string(), serialized);
+
+		let git = "git:https://github.com/pesde-pkg/index:abcdef#1.2.3"
+			.parse::<PatchKey>()
+			.unwrap();
+		assert!(matches!(git, PatchKey::Exact(_)));
+
+		assert!(matches!(
+			"path:/dev/null@^1".parse::<PatchKey>().unwrap_err().inner(),
+			errors::PatchKeyFromStrKind::RangeUnsupported(_)
+		));
+		assert!(matches!(
+			"foo/bar@^1".parse::<PatchKey>().unwrap_err().inner(),
+			errors::PatchKeyFromStrKind::InvalidFormat(_)
+		));
+	}
+
+	#[test]
+	fn bin_platforms() {
+		let platforms = toml::from_str::<BinPlatforms>("").unwrap();
+		assert!(platforms.is_current_supported());
+		assert_eq!(platforms.to_string(), "os: any; arch: any");
+
+		let platforms = toml::from_str::<BinPlatforms>(&format!(
+			"os = [\"{}\"]\narch = [\"{}\"]",
+			std::env::consts::OS,
+			std::env::consts::ARCH
+		))
+		.unwrap();
+		assert!(platforms.is_current_supported());
+
+		let other_os = KNOWN_OSES
+			.iter()
+			.find(|os| **os != std::env::consts::OS)
+			.unwrap();
+		let platforms = toml::from_str::<BinPlatforms>(&format!("os = [\"{other_os}\"]")).unwrap();
+		assert!(!platforms.is_current_supported());
+		assert_eq!(platforms.to_string(), format!("os: {other_os}; arch: any"));
+
+		let other_arch = KNOWN_ARCHES
+			.iter()
+			.find(|arch| **arch != std::env::consts::ARCH)
+			.unwrap();
+		let platforms =
+			toml::from_str::<BinPlatforms>(&format!("arch = [\"{other_arch}\"]")).unwrap();
+		assert!(!platforms.is_current_supported());
+
+		let err = toml::from_str::<BinPlatforms>("os = [\"plan9\"]").unwrap_err();
+		assert!(err.message().contains("unknown platform `plan9`"), "{err}");
+		assert!(toml::from_str::<BinPlatforms>("abi = [\"gnu\"]").is_err());
+	}
+
+	#[test]
+	fn target_default_indices() {
+		let indices = toml::from_str::<ManifestIndices>(
+			r#"
+			[indices]
+			default = "https://github.com/pesde-pkg/index"
+			roblox = "https://github.com/pesde-pkg/roblox-index"
+
+			[target_default_indices]
+			roblox = "roblox"
+			"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			indices.pesde_index_name(crate::DEFAULT_INDEX_NAME, TargetKind::Roblox),
+			"roblox"
+		);
+		assert_eq!(
+			indices.pesde_index_name(crate::DEFAULT_INDEX_NAME, TargetKind::Luau),
+			crate::DEFAULT_INDEX_NAME
+		);
+		assert_eq!(
+			indices.pesde_index_name("other", TargetKind::Roblox),
+			"other"
+		);
+
+		assert!(
+			toml::from_str::<ManifestIndices>("[target_default_indices]\nroblx = \"roblox\"")
+				.is_err()
+		);
 	}
 }
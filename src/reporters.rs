@@ -9,9 +9,13 @@
 
 #![allow(unused_variables)]
 
+use crate::GixUrl;
+use crate::graph::DependencyGraph;
 use async_stream::stream;
 use futures::StreamExt as _;
+use std::fmt::Debug;
 use std::sync::Arc;
+use std::sync::Once;
 use tokio::io::AsyncBufRead;
 
 /// Reports downloads.
@@ -67,16 +71,209 @@ pub trait PatchProgressReporter: Send + Sync {
 
 impl PatchProgressReporter for () {}
 
+/// Reports the progress of resolving dependencies.
+pub trait ResolveReporter: Send + Sync {
+	/// Reports the progress of the resolution.
+	///
+	/// `resolved` is the number of dependencies processed so far, and `total` is
+	/// the number of dependencies known about so far, which grows as transitive
+	/// dependencies are discovered.
+	fn report_resolve_progress(&self, resolved: usize, total: usize) {}
+}
+
+impl ResolveReporter for () {}
+
+/// Reports the progress of refreshing Git indices.
+pub trait RefreshReporter: Debug + Send + Sync {
+	/// Reports the progress of fetching the index at `url`.
+	///
+	/// `objects` is the number of objects received so far, and `bytes` is the
+	/// number of bytes received so far.
+	fn report_refresh_progress(&self, url: &GixUrl, objects: usize, bytes: usize) {}
+
+	/// Reports that fetching the index at `url` is done, whether it succeeded or not.
+	fn report_refresh_done(&self, url: &GixUrl) {}
+}
+
+impl RefreshReporter for () {}
+
+/// A stage of an install.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InstallStage {
+	/// The dependencies are being resolved
+	Resolve,
+	/// The dependencies are being downloaded and linked
+	Download,
+}
+
+/// Reports the progress of an install.
+pub trait InstallReporter: ResolveReporter + DownloadsReporter + PatchesReporter {
+	/// Reports that the install has moved on to a new stage.
+	fn report_stage(&self, stage: InstallStage) {}
+
+	/// Reports the resolved dependency graph, before any of it is downloaded.
+	fn report_resolved(&self, graph: &DependencyGraph) {}
+}
+
+impl InstallReporter for () {}
+
+impl<A: DownloadProgressReporter, B: DownloadProgressReporter> DownloadProgressReporter for (A, B) {
+	fn report_start(&self) {
+		self.0.report_start();
+		self.1.report_start();
+	}
+
+	fn report_progress(&self, total: u64, len: u64) {
+		self.0.report_progress(total, len);
+		self.1.report_progress(total, len);
+	}
+
+	fn report_done(&self) {
+		self.0.report_done();
+		self.1.report_done();
+	}
+}
+
+impl<A: PatchProgressReporter, B: PatchProgressReporter> PatchProgressReporter for (A, B) {
+	fn report_done(&self) {
+		self.0.report_done();
+		self.1.report_done();
+	}
+}
+
+/// A reporter which passes every report on to two reporters, in order.
+///
+/// More reporters can be combined by nesting [`MultiReporter`]s.
+#[derive(Debug)]
+pub struct MultiReporter<A, B> {
+	first: Arc<A>,
+	second: Arc<B>,
+}
+
+impl<A, B> MultiReporter<A, B> {
+	/// Creates a reporter which reports to both `first` and `second`.
+	#[must_use]
+	pub fn new(first: Arc<A>, second: Arc<B>) -> Self {
+		Self { first, second }
+	}
+}
+
+impl<A: DownloadsReporter, B: DownloadsReporter> DownloadsReporter for MultiReporter<A, B> {
+	type DownloadProgressReporter = (A::DownloadProgressReporter, B::DownloadProgressReporter);
+
+	fn report_download(self: Arc<Self>, name: String) -> Self::DownloadProgressReporter {
+		(
+			self.first.clone().report_download(name.clone()),
+			self.second.clone().report_download(name),
+		)
+	}
+}
+
+impl<A: PatchesReporter, B: PatchesReporter> PatchesReporter for MultiReporter<A, B> {
+	type PatchProgressReporter = (A::PatchProgressReporter, B::PatchProgressReporter);
+
+	fn report_patch(self: Arc<Self>, name: String) -> Self::PatchProgressReporter {
+		(
+			self.first.clone().report_patch(name.clone()),
+			self.second.clone().report_patch(name),
+		)
+	}
+}
+
+impl<A: ResolveReporter, B: ResolveReporter> ResolveReporter for MultiReporter<A, B> {
+	fn report_resolve_progress(&self, resolved: usize, total: usize) {
+		self.first.report_resolve_progress(resolved, total);
+		self.second.report_resolve_progress(resolved, total);
+	}
+}
+
+impl<A: InstallReporter, B: InstallReporter> InstallReporter for MultiReporter<A, B> {
+	fn report_stage(&self, stage: InstallStage) {
+		self.first.report_stage(stage);
+		self.second.report_stage(stage);
+	}
+
+	fn report_resolved(&self, graph: &DependencyGraph) {
+		self.first.report_resolved(graph);
+		self.second.report_resolved(graph);
+	}
+}
+
+/// A download exceeded the maximum size
+#[derive(Debug, thiserror::Error)]
+#[error("download exceeds the maximum size of {0} bytes")]
+pub(crate) struct DownloadTooLarge(pub(crate) u64);
+
+impl DownloadTooLarge {
+	/// Returns the maximum size if the error is caused by a download being too large, looking
+	/// through IO errors which wrap it
+	pub(crate) fn find(mut err: &(dyn std::error::Error + 'static)) -> Option<u64> {
+		loop {
+			if let Some(too_large) = err.downcast_ref::<DownloadTooLarge>() {
+				return Some(too_large.0);
+			}
+
+			// the source of an IO error is the source of the error it wraps, not that error
+			err = match err
+				.downcast_ref::<std::io::Error>()
+				.and_then(std::io::Error::get_ref)
+			{
+				Some(inner) => inner,
+				None => err.source()?,
+			};
+		}
+	}
+
+	fn check(max_size: Option<u64>, len: u64) -> Result<(), Self> {
+		match max_size {
+			Some(max_size) if len > max_size => Err(Self(max_size)),
+			_ => Ok(()),
+		}
+	}
+}
+
+/// Returns the maximum size of a download, `PESDE_MAX_DOWNLOAD_SIZE` taking precedence over the
+/// maximum of the index
+pub(crate) fn max_download_size(index_max_size: Option<u64>) -> Option<u64> {
+	static INVALID_WARNING: Once = Once::new();
+
+	let Ok(size) = std::env::var("PESDE_MAX_DOWNLOAD_SIZE") else {
+		return index_max_size;
+	};
+
+	match size.parse() {
+		Ok(size) => Some(size),
+		Err(e) => {
+			INVALID_WARNING.call_once(|| {
+				tracing::warn!(
+					"ignoring PESDE_MAX_DOWNLOAD_SIZE ({size}) as it is not a size in bytes: {e}"
+				);
+			});
+			index_max_size
+		}
+	}
+}
+
 pub(crate) fn response_to_async_buf_read<R: DownloadProgressReporter>(
 	response: reqwest::Response,
 	reporter: Arc<R>,
+	max_size: Option<u64>,
 ) -> impl AsyncBufRead {
-	let total_len = response.content_length().unwrap_or(0);
+	let content_length = response.content_length();
+	let total_len = content_length.unwrap_or(0);
 	reporter.report_progress(total_len, 0);
 
 	let mut bytes_downloaded = 0;
 	let mut stream = response.bytes_stream();
 	let bytes = stream!({
+		if let Some(content_length) = content_length
+			&& let Err(e) = DownloadTooLarge::check(max_size, content_length)
+		{
+			yield Err(std::io::Error::other(e));
+			return;
+		}
+
 		while let Some(chunk) = stream.next().await {
 			let chunk = match chunk {
 				Ok(chunk) => chunk,
@@ -86,6 +283,13 @@ pub(crate) fn response_to_async_buf_read<R: DownloadProgressReporter>(
 				}
 			};
 			bytes_downloaded += chunk.len() as u64;
+
+			// the content length may be missing or wrong, so the downloaded bytes are checked too
+			if let Err(e) = DownloadTooLarge::check(max_size, bytes_downloaded) {
+				yield Err(std::io::Error::other(e));
+				return;
+			}
+
 			reporter.report_progress(total_len, bytes_downloaded);
 			yield Ok(chunk);
 		}
@@ -95,3 +299,105 @@ pub(crate) fn response_to_async_buf_read<R: DownloadProgressReporter>(
 
 	tokio_util::io::StreamReader::new(bytes)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::Mutex;
+
+	#[derive(Default)]
+	struct CollectingReporter(Arc<Mutex<Vec<String>>>);
+
+	struct CollectingProgressReporter(Arc<Mutex<Vec<String>>>, String);
+
+	impl DownloadsReporter for CollectingReporter {
+		type DownloadProgressReporter = CollectingProgressReporter;
+
+		fn report_download(self: Arc<Self>, name: String) -> Self::DownloadProgressReporter {
+			self.0.lock().unwrap().push(format!("download {name}"));
+			CollectingProgressReporter(self.0.clone(), name)
+		}
+	}
+
+	impl DownloadProgressReporter for CollectingProgressReporter {
+		fn report_progress(&self, total: u64, len: u64) {
+			self.0
+				.lock()
+				.unwrap()
+				.push(format!("progress {} {len}/{total}", self.1));
+		}
+
+		fn report_done(&self) {
+			self.0.lock().unwrap().push(format!("done {}", self.1));
+		}
+	}
+
+	impl ResolveReporter for CollectingReporter {
+		fn report_resolve_progress(&self, resolved: usize, total: usize) {
+			self.0
+				.lock()
+				.unwrap()
+				.push(format!("resolve {resolved}/{total}"));
+		}
+	}
+
+	#[test]
+	fn multi_reporter_reports_to_both() {
+		let first = Arc::new(CollectingReporter::default());
+		let second = Arc::new(CollectingReporter::default());
+		let reporter = Arc::new(MultiReporter::new(first.clone(), second.clone()));
+
+		reporter.report_resolve_progress(1, 2);
+		let download = reporter.report_download("foo".to_string());
+		download.report_progress(10, 5);
+		download.report_done();
+
+		let expected = [
+			"resolve 1/2",
+			"download foo",
+			"progress foo 5/10",
+			"done foo",
+		];
+		assert_eq!(*first.0.lock().unwrap(), expected);
+		assert_eq!(*second.0.lock().unwrap(), expected);
+	}
+
+	#[tokio::test]
+	async fn download_too_large() {
+		use tokio::io::AsyncReadExt as _;
+
+		// serves 64 bytes, with or without a content length depending on the request path
+		let addr = crate::tests::mock_http_server(|request| {
+			let header = if request.starts_with("GET /sized ") {
+				"content-length: 64\r\n"
+			} else {
+				""
+			};
+			format!(
+				"HTTP/1.1 200 OK\r\n{header}connection: close\r\n\r\n{}",
+				"a".repeat(64)
+			)
+		})
+		.await;
+
+		let read = async |path: &str, max_size: Option<u64>| {
+			let response = reqwest::get(format!("http://{addr}/{path}")).await.unwrap();
+			let bytes = response_to_async_buf_read(response, Arc::new(()), max_size);
+			tokio::pin!(bytes);
+
+			let mut contents = vec![];
+			bytes.read_to_end(&mut contents).await.map(|_| contents)
+		};
+
+		for path in ["sized", "unsized"] {
+			assert_eq!(read(path, None).await.unwrap().len(), 64, "{path}");
+			assert_eq!(read(path, Some(64)).await.unwrap().len(), 64, "{path}");
+
+			let err = read(path, Some(10)).await.unwrap_err();
+			assert_eq!(DownloadTooLarge::find(&err), Some(10), "{path}");
+		}
+
+		let err = std::io::Error::other("unrelated");
+		assert_eq!(DownloadTooLarge::find(&err), None);
+	}
+}
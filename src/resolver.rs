@@ -7,11 +7,12 @@ use crate::graph::DependencyGraph;
 use crate::graph::DependencyGraphImporter;
 use crate::graph::DependencyGraphNode;
 use crate::graph::DependencyGraphNodeDependency;
+use crate::lockfile::Lockfile;
 use crate::manifest::Alias;
 use crate::manifest::DependencyType;
 use crate::manifest::ManifestIndices;
 use crate::manifest::OverrideSpecifier;
-use crate::matching_globs;
+use crate::reporters::ResolveReporter;
 use crate::source::DependencySpecifier as _;
 use crate::source::DependencySpecifiers;
 use crate::source::PackageSource as _;
@@ -21,6 +22,7 @@ use crate::source::StructureKind;
 use crate::source::ids::PackageId;
 #[expect(deprecated)]
 use crate::source::pesde::PesdePackageSource;
+use crate::workspace_members;
 use itertools::Itertools as _;
 use relative_path::RelativePathBuf;
 use std::collections::BTreeMap;
@@ -33,16 +35,19 @@ use tracing::instrument;
 fn specifier_to_source(
 	indices: Option<&ManifestIndices>,
 	specifier: &DependencySpecifiers,
+	forbid_wally: bool,
 ) -> Result<PackageSources, errors::DependencyGraphError> {
 	let source = match &specifier {
 		#[expect(deprecated)]
 		DependencySpecifiers::Pesde(specifier) => {
 			let index_url = if let Some(indices) = indices {
+				let index = indices.pesde_index_name(&specifier.index, specifier.target);
+
 				indices
 					.pesde
-					.get(&specifier.index)
+					.get(index)
 					.ok_or_else(|| {
-						errors::DependencyGraphErrorKind::IndexNotFound(specifier.index.clone())
+						errors::DependencyGraphErrorKind::IndexNotFound(index.to_string())
 					})?
 					.clone()
 			} else {
@@ -56,6 +61,9 @@ fn specifier_to_source(
 
 			PackageSources::Pesde(PesdePackageSource::from_url(index_url))
 		}
+		DependencySpecifiers::Wally(_) if forbid_wally => {
+			return Err(errors::DependencyGraphErrorKind::WallyForbidden(specifier.clone()).into());
+		}
 		DependencySpecifiers::Wally(specifier) => {
 			let index_url = if let Some(indices) = indices {
 				indices
@@ -130,11 +138,7 @@ async fn prepare_queue(
 		})
 		.collect::<Result<_, errors::DependencyGraphError>>()?;
 
-	let members = matching_globs(
-		project.dir(),
-		root_manifest.workspace.members.iter().map(String::as_str),
-	)
-	.await?;
+	let members = workspace_members(project.dir(), &root_manifest.workspace).await?;
 
 	let mut members = members
 		.into_iter()
@@ -267,6 +271,7 @@ async fn resolve_version(
 	refreshed_sources: &RefreshedSources,
 	pass_indices: bool,
 	specifier: &DependencySpecifiers,
+	forbid_wally: bool,
 ) -> Result<ResolveVersionData, errors::DependencyGraphError> {
 	let mut manifest = None;
 
@@ -274,20 +279,38 @@ async fn resolve_version(
 		if pass_indices && manifest.is_none() {
 			manifest = Some(subproject.deser_manifest().await?);
 		}
-		let source = specifier_to_source(manifest.as_ref().map(|m| &m.indices), specifier)?;
+		let source = specifier_to_source(
+			manifest.as_ref().map(|m| &m.indices),
+			specifier,
+			forbid_wally,
+		)?;
 
 		refreshed_sources
 			.refresh(&source, subproject.project())
 			.await?;
 
+		let mut result = source
+			.resolve(&subproject, specifier, refreshed_sources)
+			.await;
+		// a local index which wasn't refreshed may not have the matching versions yet
+		if !result
+			.as_ref()
+			.is_ok_and(|result| !result.versions.is_empty())
+			&& refreshed_sources
+				.refresh_offline(&source, subproject.project())
+				.await?
+		{
+			result = source
+				.resolve(&subproject, specifier, refreshed_sources)
+				.await;
+		}
+
 		let ResolveResult {
 			source,
 			pkg_ref,
 			structure_kind,
 			mut versions,
-		} = source
-			.resolve(&subproject, specifier, refreshed_sources)
-			.await?;
+		} = result?;
 
 		let Some((package_id, dependencies)) = graph
 			.nodes
@@ -324,10 +347,13 @@ async fn resolve_version(
 	Ok((id, structure_kind, deps))
 }
 
+/// The maximum depth of the dependency graph if `workspace.max_resolve_depth` isn't set
+pub const DEFAULT_MAX_RESOLVE_DEPTH: usize = 256;
+
 impl Project {
 	/// Create a dependency graph from the project's manifest
 	#[instrument(
-		skip(self, previous_graph, refreshed_sources),
+		skip(self, previous_graph, refreshed_sources, reporter),
 		ret(level = "trace"),
 		level = "debug"
 	)]
@@ -337,6 +363,7 @@ impl Project {
 		refreshed_sources: &RefreshedSources,
 		// used by `x` command - if true, specifier indices are expected to be URLs
 		is_published_package: bool,
+		reporter: &impl ResolveReporter,
 	) -> Result<(DependencyGraph, bool), errors::DependencyGraphError> {
 		let mut graph = DependencyGraph {
 			importers: Default::default(),
@@ -344,17 +371,60 @@ impl Project {
 			nodes: Default::default(),
 		};
 
+		let (forbid_wally, max_depth, include_prerelease) = {
+			let manifest = self
+				.clone()
+				.subproject(Importer::root())
+				.deser_manifest()
+				.await?;
+
+			(
+				manifest.workspace.forbid_wally,
+				manifest
+					.workspace
+					.max_resolve_depth
+					.unwrap_or(DEFAULT_MAX_RESOLVE_DEPTH),
+				manifest.workspace.include_prerelease,
+			)
+		};
+		// the workspace setting applies on top of what the caller asked for
+		let refreshed_sources = &refreshed_sources
+			.clone()
+			.include_prerelease(refreshed_sources.includes_prerelease() || include_prerelease);
+
 		let mut queue = prepare_queue(self, &mut graph, previous_graph).await?;
+
+		// packages kept from the previous graph aren't resolved again, so they have to be checked here
+		if forbid_wally
+			&& let Some(id) = graph
+				.nodes
+				.keys()
+				.find(|id| matches!(id.source(), PackageSources::Wally(_)))
+		{
+			return Err(errors::DependencyGraphErrorKind::WallyPackageForbidden(id.clone()).into());
+		}
+
 		if queue.is_empty() {
 			tracing::debug!("dependency graph is up to date");
 			return Ok((graph, false));
 		}
 
+		let mut resolved = 0;
+		reporter.report_resolve_progress(resolved, queue.len());
+
 		while let Some(entry) = queue.pop_front() {
 			async {
 				let alias = entry.path.last().unwrap();
 				let depth = entry.path.len() - 1;
 
+				if depth > max_depth {
+					return Err(errors::DependencyGraphErrorKind::MaxDepthExceeded(
+						max_depth,
+						entry.path.iter().map(Alias::as_str).join(">"),
+					)
+					.into());
+				}
+
 				tracing::debug!("resolving {} ({:?})", entry.specifier, entry.ty);
 
 				let (package_id, structure_kind, dependencies) = resolve_version(
@@ -363,6 +433,7 @@ impl Project {
 					refreshed_sources,
 					!is_published_package && depth == 0,
 					&entry.specifier,
+					forbid_wally,
 				)
 				.await?;
 
@@ -439,10 +510,35 @@ impl Project {
 				path = entry.path.iter().map(Alias::as_str).join(">")
 			))
 			.await?;
+
+			resolved += 1;
+			reporter.report_resolve_progress(resolved, resolved + queue.len());
 		}
 
 		Ok((graph, true))
 	}
+
+	/// Resolves the project's dependencies into a lockfile without writing it to disk.
+	/// If `use_lockfile` is set, the existing lockfile is used to avoid re-resolving unchanged
+	/// dependencies, otherwise everything is resolved from scratch
+	#[instrument(skip(self, refreshed_sources), level = "debug")]
+	pub async fn resolve_lockfile(
+		&self,
+		use_lockfile: bool,
+		refreshed_sources: &RefreshedSources,
+	) -> Result<Lockfile, errors::ResolveLockfileError> {
+		let previous_graph = if use_lockfile {
+			self.graph_from_lockfile().await?
+		} else {
+			None
+		};
+
+		let (graph, _) = self
+			.dependency_graph(previous_graph.as_ref(), refreshed_sources, false, &())
+			.await?;
+
+		Ok(Lockfile { graph })
+	}
 }
 
 /// Errors that can occur when resolving dependencies
@@ -450,6 +546,7 @@ pub mod errors {
 	use crate::errors::MatchingGlobsError;
 	use crate::manifest::Alias;
 	use crate::source::DependencySpecifiers;
+	use crate::source::ids::PackageId;
 	use thiserror::Error;
 
 	/// Errors that can occur when creating a dependency graph
@@ -489,8 +586,330 @@ pub mod errors {
 		#[error("no matching version found for {0}")]
 		NoMatchingVersion(DependencySpecifiers),
 
+		/// A Wally dependency was found while they are forbidden by the manifest
+		#[error("wally dependency {0} is forbidden by `workspace.forbid_wally`")]
+		WallyForbidden(DependencySpecifiers),
+
+		/// A Wally package was found in the previous graph while they are forbidden by the manifest
+		#[error("wally package {0} is forbidden by `workspace.forbid_wally`")]
+		WallyPackageForbidden(PackageId),
+
 		/// An alias for an override was not found in the manifest
 		#[error("alias `{0}` not found in manifest")]
 		AliasNotFound(Alias),
+
+		/// A dependency was nested deeper than the maximum resolution depth
+		#[error("dependency {1} is nested deeper than the maximum depth of {0}")]
+		MaxDepthExceeded(usize, String),
+	}
+
+	/// Errors that can occur when resolving a lockfile
+	#[derive(Debug, Error, thiserror_ext::Box)]
+	#[thiserror_ext(newtype(name = ResolveLockfileError))]
+	#[non_exhaustive]
+	pub enum ResolveLockfileErrorKind {
+		/// Reading the existing lockfile failed
+		#[error("error reading lockfile")]
+		LockfileRead(#[from] crate::errors::LockfileReadError),
+
+		/// Creating the dependency graph failed
+		#[error("error creating dependency graph")]
+		DependencyGraph(#[from] DependencyGraphError),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::path_dependency_project;
+	use std::sync::Mutex;
+
+	#[derive(Default)]
+	struct CollectingResolveReporter(Mutex<Vec<(usize, usize)>>);
+
+	impl ResolveReporter for CollectingResolveReporter {
+		fn report_resolve_progress(&self, resolved: usize, total: usize) {
+			self.0.lock().unwrap().push((resolved, total));
+		}
+	}
+
+	/// Creates a project depending on `foo`, which in turn depends on `bar`, both by path
+	async fn nested_path_dependency_project(
+		project_manifest: &str,
+	) -> (tempfile::TempDir, Project) {
+		let (dir, project) = path_dependency_project(
+			project_manifest,
+			"[dependencies]\nbar = { path = \"../bar\" }\n",
+		)
+		.await;
+		tokio::fs::create_dir_all(dir.path().join("bar"))
+			.await
+			.unwrap();
+		tokio::fs::write(dir.path().join("bar").join(crate::MANIFEST_FILE_NAME), "")
+			.await
+			.unwrap();
+
+		(dir, project)
+	}
+
+	#[tokio::test]
+	async fn reports_resolve_progress() {
+		let (_dir, project) = nested_path_dependency_project("").await;
+
+		let reporter = CollectingResolveReporter::default();
+		let (graph, changed) = project
+			.dependency_graph(None, &RefreshedSources::new(), false, &reporter)
+			.await
+			.unwrap();
+		assert!(changed);
+		assert_eq!(graph.nodes.len(), 2);
+
+		// `bar` is only discovered once `foo` has been resolved
+		assert_eq!(*reporter.0.lock().unwrap(), [(0, 1), (1, 2), (2, 2)]);
+	}
+
+	#[tokio::test]
+	async fn max_resolve_depth() {
+		let (_dir, project) =
+			nested_path_dependency_project("[workspace]\nmax_resolve_depth = 0\n").await;
+
+		let err = project
+			.dependency_graph(None, &RefreshedSources::new(), false, &())
+			.await
+			.unwrap_err();
+		assert!(matches!(
+			err.inner(),
+			errors::DependencyGraphErrorKind::MaxDepthExceeded(0, path) if path == "foo>bar"
+		));
+
+		let (_dir, project) =
+			nested_path_dependency_project("[workspace]\nmax_resolve_depth = 1\n").await;
+		assert!(
+			project
+				.dependency_graph(None, &RefreshedSources::new(), false, &())
+				.await
+				.is_ok()
+		);
+	}
+
+	#[tokio::test]
+	async fn forbid_wally() {
+		let (_dir, project) = path_dependency_project(
+			"[workspace]\nforbid_wally = true\n",
+			"[wally_indices]\ndefault = \"https://github.com/UpliftGames/wally-index\"\n\n[dependencies]\nbar = { wally = \"acme/bar\", version = \"^1.0.0\", realm = \"shared\" }\n",
+		)
+		.await;
+
+		// the transitive Wally dependency is rejected before its index is ever fetched
+		let err = project
+			.dependency_graph(None, &RefreshedSources::new(), false, &())
+			.await
+			.unwrap_err();
+		assert!(matches!(
+			err.inner(),
+			errors::DependencyGraphErrorKind::WallyForbidden(DependencySpecifiers::Wally(specifier))
+				if specifier.name.to_string() == "acme/bar"
+		));
+	}
+
+	#[tokio::test]
+	async fn previous_graph_is_reused_without_sources() {
+		use crate::source::local_index::LocalIndex;
+
+		let dir = tempfile::tempdir().unwrap();
+		let mut index = LocalIndex::new(&dir.path().join("index"));
+		index
+			.publish(
+				"acme/lib",
+				"1.0.0",
+				"{ environment = \"luau\", lib = \"init.luau\" }",
+				&[("init.luau", "return nil\n")],
+			)
+			.await;
+		let project_dir = dir.path().join("project");
+		tokio::fs::create_dir_all(&project_dir).await.unwrap();
+		tokio::fs::write(
+			project_dir.join(crate::MANIFEST_FILE_NAME),
+			format!(
+				"[indices]\ndefault = \"{}\"\n\n[dependencies]\nlib = {{ name = \"acme/lib\", version = \"^1.0.0\", target = \"luau\" }}\n",
+				index.url()
+			),
+		)
+		.await
+		.unwrap();
+		let project = crate::tests::test_project(dir.path(), &project_dir);
+
+		let (graph, _) = project
+			.dependency_graph(None, &RefreshedSources::new(), false, &())
+			.await
+			.unwrap();
+
+		// neither the index nor its local copy exist anymore, so resolving can't read any
+		// version from them
+		tokio::fs::remove_dir_all(dir.path().join("index"))
+			.await
+			.unwrap();
+		tokio::fs::remove_dir_all(dir.path().join("data"))
+			.await
+			.unwrap();
+		let (reused, _) = project
+			.dependency_graph(Some(&graph), &RefreshedSources::new(), false, &())
+			.await
+			.unwrap();
+		assert_eq!(reused.diff(&graph), crate::graph::GraphDiff::default());
+
+		assert!(
+			project
+				.dependency_graph(None, &RefreshedSources::new(), false, &())
+				.await
+				.is_err()
+		);
+	}
+
+	#[tokio::test]
+	async fn prefer_offline_refreshes_without_matching_version() {
+		use crate::source::local_index::LocalIndex;
+
+		let dir = tempfile::tempdir().unwrap();
+		let mut index = LocalIndex::new(&dir.path().join("index"));
+		let publish = async |index: &mut LocalIndex, version: &str| {
+			index
+				.publish(
+					"acme/lib",
+					version,
+					"{ environment = \"luau\", lib = \"init.luau\" }",
+					&[("init.luau", "return nil\n")],
+				)
+				.await;
+		};
+		// the projects share their data directory, and therefore the local copy of the index
+		let resolve = async |index: &LocalIndex, name: &str, req: &str| {
+			let project_dir = dir.path().join(name);
+			tokio::fs::create_dir_all(&project_dir).await.unwrap();
+			tokio::fs::write(
+				project_dir.join(crate::MANIFEST_FILE_NAME),
+				format!(
+					"[indices]\ndefault = \"{}\"\n\n[dependencies]\nlib = {{ name = \"acme/lib\", version = \"{req}\", target = \"luau\" }}\n",
+					index.url()
+				),
+			)
+			.await
+			.unwrap();
+			let project = crate::tests::test_project(dir.path(), &project_dir);
+
+			let (graph, _) = project
+				.dependency_graph(
+					None,
+					&RefreshedSources::new().prefer_offline(true),
+					false,
+					&(),
+				)
+				.await
+				.unwrap();
+			graph.importers[&Importer::root()].dependencies[&"lib".parse().unwrap()]
+				.0
+				.version()
+				.to_string()
+		};
+
+		publish(&mut index, "1.0.0").await;
+		assert_eq!(resolve(&index, "a", "^1.0.0").await, "1.0.0");
+
+		// a matching version is found in the local index, so it isn't refreshed
+		publish(&mut index, "1.1.0").await;
+		assert_eq!(resolve(&index, "b", "^1.0.0").await, "1.0.0");
+
+		// without a matching version in the local index, it is refreshed and resolution retried
+		publish(&mut index, "2.0.0").await;
+		assert_eq!(resolve(&index, "c", "^2.0.0").await, "2.0.0");
+		assert_eq!(resolve(&index, "d", "^1.0.0").await, "1.1.0");
+	}
+
+	#[tokio::test]
+	#[expect(deprecated)]
+	async fn include_prerelease() {
+		use crate::source::PackageSource as _;
+		use crate::source::local_index::LocalIndex;
+		use crate::source::pesde::specifier::PesdeDependencySpecifier;
+		use crate::source::pesde::target::TargetKind;
+
+		let dir = tempfile::tempdir().unwrap();
+		let mut index = LocalIndex::new(&dir.path().join("index"));
+		for version in ["1.0.0", "1.1.0-beta.1"] {
+			index
+				.publish(
+					"acme/lib",
+					version,
+					"{ environment = \"luau\", lib = \"init.luau\" }",
+					&[("init.luau", "return nil\n")],
+				)
+				.await;
+		}
+		let project = |name: &str| crate::tests::test_project(dir.path(), dir.path().join(name));
+		let resolve = async |name: &str, workspace: &str, include_prerelease: bool| {
+			let project = project(name);
+			tokio::fs::create_dir_all(project.dir()).await.unwrap();
+			tokio::fs::write(
+				project.dir().join(crate::MANIFEST_FILE_NAME),
+				format!(
+					"[workspace]\n{workspace}\n\n[indices]\ndefault = \"{}\"\n\n[dependencies]\nlib = {{ name = \"acme/lib\", version = \"^1.0.0\", target = \"luau\" }}\n",
+					index.url()
+				),
+			)
+			.await
+			.unwrap();
+
+			let (graph, _) = project
+				.dependency_graph(
+					None,
+					&RefreshedSources::new().include_prerelease(include_prerelease),
+					false,
+					&(),
+				)
+				.await
+				.unwrap();
+			graph.importers[&Importer::root()].dependencies[&"lib".parse().unwrap()]
+				.0
+				.version()
+				.to_string()
+		};
+
+		assert_eq!(resolve("a", "", false).await, "1.0.0");
+		assert_eq!(resolve("b", "", true).await, "1.1.0-beta.1");
+		assert_eq!(
+			resolve("c", "include_prerelease = true", false).await,
+			"1.1.0-beta.1"
+		);
+
+		// resolving doesn't need a project manifest, like when executing a package with `pesde x`
+		let source = PesdePackageSource::from_url(index.url().parse().unwrap());
+		let specifier = DependencySpecifiers::Pesde(PesdeDependencySpecifier {
+			name: "acme/lib".parse().unwrap(),
+			version: "^1.0.0".parse().unwrap(),
+			index: index.url(),
+			target: TargetKind::Luau,
+		});
+		let subproject = project("missing").subproject(Importer::root());
+		let refreshed_sources = RefreshedSources::new();
+		let source = PackageSources::Pesde(source);
+		refreshed_sources
+			.refresh(&source, subproject.project())
+			.await
+			.unwrap();
+		let versions = async |refreshed_sources: &RefreshedSources| {
+			source
+				.resolve(&subproject, &specifier, refreshed_sources)
+				.await
+				.unwrap()
+				.versions
+				.into_keys()
+				.map(|version| version.to_string())
+				.collect::<Vec<_>>()
+		};
+		assert_eq!(versions(&refreshed_sources).await, ["1.0.0"]);
+		assert_eq!(
+			versions(&refreshed_sources.clone().include_prerelease(true)).await,
+			["1.0.0", "1.1.0-beta.1"]
+		);
 	}
 }
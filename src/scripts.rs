@@ -4,6 +4,7 @@ use croshet::ShellPipeWriter;
 use std::convert::Infallible;
 use std::error::Error;
 use std::future;
+use std::path::Path;
 use tracing::instrument;
 
 use crate::Subproject;
@@ -40,19 +41,34 @@ pub async fn execute_script<H: ExecuteScriptHooks>(
 	script: &str,
 	hooks: &mut H,
 	args: Vec<std::ffi::OsString>,
+) -> Result<i32, errors::ExecuteScriptError<H>> {
+	execute_script_in(&subproject.dir(), script, hooks, args).await
+}
+
+/// Executes a script in the given directory
+#[instrument(skip(hooks), level = "debug")]
+pub async fn execute_script_in<H: ExecuteScriptHooks>(
+	dir: &Path,
+	script: &str,
+	hooks: &mut H,
+	args: Vec<std::ffi::OsString>,
 ) -> Result<i32, errors::ExecuteScriptError<H>> {
 	let parsed_script = croshet::parser::parse(script)?;
 
 	let (stdout, stderr, stdin) = hooks.stdio();
+	// croshet clears the environment of spawned processes, so forward ours to keep `PATH`,
+	// terminal settings and the like available to interactive scripts
+	let env_vars = std::env::vars_os().collect::<Vec<_>>();
 
 	let (code, stdio_result) = tokio::join!(
 		croshet::execute(
 			parsed_script,
 			croshet::ExecuteOptionsBuilder::new()
-				.cwd(subproject.dir())
+				.cwd(dir.to_path_buf())
 				.stdout(stdout)
 				.stderr(stderr)
 				.stdin(stdin)
+				.env_vars(&env_vars)
 				.args(args)
 				.build()
 				.unwrap(),
@@ -86,3 +102,55 @@ pub mod errors {
 		Hooks(Hooks::Error),
 	}
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+	use super::*;
+
+	#[derive(Debug)]
+	struct PipedStdin {
+		stdout: Option<ShellPipeWriter>,
+		stdin: Option<ShellPipeReader>,
+	}
+
+	impl ExecuteScriptHooks for PipedStdin {
+		type Error = Infallible;
+
+		fn stdio(&mut self) -> (ShellPipeWriter, ShellPipeWriter, ShellPipeReader) {
+			(
+				self.stdout.take().unwrap(),
+				ShellPipeWriter::Stderr,
+				self.stdin.take().unwrap(),
+			)
+		}
+	}
+
+	#[tokio::test]
+	async fn child_receives_stdin() {
+		let (stdout_reader, stdout) = croshet::pipe();
+		let (stdin, mut stdin_writer) = croshet::pipe();
+		stdin_writer.write_all(b"pesde\n").unwrap();
+		drop(stdin_writer);
+
+		let mut hooks = PipedStdin {
+			stdout: Some(stdout),
+			stdin: Some(stdin),
+		};
+
+		let code = execute_script_in(
+			&std::env::temp_dir(),
+			r#"sh -c 'read name; echo "hello $name"; exit 3'"#,
+			&mut hooks,
+			vec![],
+		)
+		.await
+		.unwrap();
+		drop(hooks);
+
+		let mut output = vec![];
+		stdout_reader.pipe_to(&mut output).unwrap();
+
+		assert_eq!(code, 3i32);
+		assert_eq!(String::from_utf8(output).unwrap().trim(), "hello pesde");
+	}
+}
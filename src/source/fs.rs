@@ -1,11 +1,11 @@
 //! Packages' filesystems
 use crate::hash::Hash;
 use crate::hash::HashAlgorithm;
-use crate::source::ADDITIONAL_FORBIDDEN_FILES;
-use crate::source::IGNORED_DIRS;
-use crate::source::IGNORED_FILES;
 use crate::source::Realm;
 use crate::source::RealmExt;
+use crate::source::is_additional_forbidden_file;
+use crate::source::is_ignored_dir;
+use crate::source::is_ignored_file;
 use crate::util;
 use fs_err::tokio as fs;
 use relative_path::RelativePath;
@@ -63,7 +63,61 @@ async fn set_readonly(path: &Path, readonly: bool) -> std::io::Result<()> {
 	fs::set_permissions(path, permissions).await
 }
 
-fn cas_path(hash: &Hash, cas_dir: &Path) -> PathBuf {
+/// Adds the executable bits to the permissions of the file, doing nothing on platforms without them
+pub(crate) async fn make_executable(path: &Path) -> std::io::Result<()> {
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt as _;
+
+		let mut permissions = fs::metadata(path).await?.permissions();
+		permissions.set_mode(permissions.mode() | 0o111);
+		fs::set_permissions(path, permissions).await?;
+	}
+
+	#[cfg(not(unix))]
+	let _ = path;
+
+	Ok(())
+}
+
+/// Hard links `src` to `dst`, copying it instead if they are on different filesystems, which
+/// happens when dependencies are installed under a prefix on another filesystem than the CAS
+pub(crate) async fn hard_link_or_copy(src: &Path, dst: &Path) -> std::io::Result<()> {
+	match fs::hard_link(src, dst).await {
+		Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+			fs::copy(src, dst).await?;
+			set_readonly(dst, false).await
+		}
+		result => result,
+	}
+}
+
+/// Replaces the file with an executable copy of itself, so that its other hard links (such as the
+/// file in the CAS) keep their permissions. Does nothing on platforms without executable bits
+pub(crate) async fn make_executable_copy(path: &Path) -> std::io::Result<()> {
+	#[cfg(unix)]
+	{
+		let parent = path.parent().unwrap().to_path_buf();
+		let temp_path = spawn_blocking(move || Builder::new().make_in(&parent, |_| Ok(())))
+			.await
+			.unwrap()?
+			.into_temp_path();
+
+		fs::copy(path, &temp_path).await?;
+		set_readonly(&temp_path, false).await?;
+		make_executable(&temp_path).await?;
+		temp_path.persist(path).map_err(|e| e.error)?;
+	}
+
+	#[cfg(not(unix))]
+	let _ = path;
+
+	Ok(())
+}
+
+/// Returns the path of the file with the given hash in the CAS
+#[must_use]
+pub fn cas_path(hash: &Hash, cas_dir: &Path) -> PathBuf {
 	let hex = hex::encode(hash.hash());
 	let (prefix, rest) = hex.split_at(hash.algorithm().optimal_prefix_length());
 	cas_dir
@@ -72,7 +126,8 @@ fn cas_path(hash: &Hash, cas_dir: &Path) -> PathBuf {
 		.join(rest)
 }
 
-pub(crate) async fn store_in_cas<R: AsyncBufRead + Unpin>(
+/// Stores the contents in the CAS, returning the path of the file and its hash
+pub async fn store_in_cas<R: AsyncBufRead + Unpin>(
 	cas_dir: impl AsRef<Path>,
 	mut contents: R,
 ) -> std::io::Result<(PathBuf, Hash)> {
@@ -147,7 +202,7 @@ async fn package_fs_cas(
 						Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
 						Err(e) => return Err(e),
 					}
-					fs::hard_link(cas_file_path, path).await?;
+					hard_link_or_copy(&cas_file_path, &path).await?;
 				} else {
 					fs::copy(cas_file_path, &path).await?;
 					set_readonly(&path, false).await?;
@@ -180,7 +235,7 @@ async fn package_fs_copy(src: &Path, destination: &Path) -> std::io::Result<()>
 		})?;
 
 		if entry.file_type().await?.is_dir() {
-			if IGNORED_DIRS.contains(&file_name) {
+			if is_ignored_dir(file_name) {
 				continue;
 			}
 
@@ -202,7 +257,7 @@ async fn package_fs_copy(src: &Path, destination: &Path) -> std::io::Result<()>
 			continue;
 		}
 
-		if IGNORED_FILES.contains(&file_name) || ADDITIONAL_FORBIDDEN_FILES.contains(&file_name) {
+		if is_ignored_file(file_name) || is_additional_forbidden_file(file_name) {
 			continue;
 		}
 
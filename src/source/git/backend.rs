@@ -121,7 +121,7 @@ impl GitPackageSourceBackend for GixPackageSourceBackend {
 
 	#[instrument(skip_all, level = "debug")]
 	async fn refresh(&self, project: &Project) -> Result<(), Self::RefreshError> {
-		refresh_git_repo(self.repo_path(project), self.repo_url.clone()).await
+		refresh_git_repo(self.repo_path(project), self.repo_url.clone(), project).await
 	}
 
 	async fn resolve_rev(
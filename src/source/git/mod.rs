@@ -11,10 +11,7 @@ use crate::manifest::DependencyType;
 use crate::manifest::Manifest;
 use crate::reporters::DownloadProgressReporter;
 use crate::ser_display_deser_fromstr;
-use crate::source::ADDITIONAL_FORBIDDEN_FILES;
 use crate::source::DependencySpecifiers;
-use crate::source::IGNORED_DIRS;
-use crate::source::IGNORED_FILES;
 use crate::source::PackageExports;
 use crate::source::PackageRefs;
 use crate::source::PackageSource;
@@ -28,6 +25,9 @@ use crate::source::git::backend::GitPackageBackends;
 use crate::source::git::backend::GitPackageSourceBackend as _;
 use crate::source::git::backend::GixPackageSourceBackend;
 use crate::source::git::pkg_ref::GitPackageRef;
+use crate::source::is_additional_forbidden_file;
+use crate::source::is_ignored_dir;
+use crate::source::is_ignored_file;
 use crate::source::path::RelativeOrAbsolutePath;
 use crate::source::pesde::PesdeVersionedManifest;
 use crate::source::wally::compat_util::WALLY_MANIFEST_FILE_NAME;
@@ -244,7 +244,7 @@ impl PackageSource for GitPackageSource {
 			};
 
 			if entry.is_dir {
-				if IGNORED_DIRS.contains(&name) {
+				if is_ignored_dir(name) {
 					continue;
 				}
 
@@ -252,11 +252,11 @@ impl PackageSource for GitPackageSource {
 				continue;
 			}
 
-			if IGNORED_FILES.contains(&name) {
+			if is_ignored_file(name) {
 				continue;
 			}
 
-			if !is_wally && ADDITIONAL_FORBIDDEN_FILES.contains(&name) {
+			if !is_wally && is_additional_forbidden_file(name) {
 				tracing::debug!(
 					"removing {name} from {}#{} at {} - using new structure",
 					self.repo,
@@ -13,7 +13,15 @@ pub struct GitPackageRef {
 }
 ser_display_deser_fromstr!(GitPackageRef);
 
-impl PackageRef for GitPackageRef {}
+impl PackageRef for GitPackageRef {
+	fn is_local(&self) -> bool {
+		false
+	}
+
+	fn is_wally_package(&self) -> bool {
+		false
+	}
+}
 
 impl std::fmt::Display for GitPackageRef {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
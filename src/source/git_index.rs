@@ -2,77 +2,303 @@
 #![allow(async_fn_in_trait)]
 
 use crate::GixUrl;
+use crate::Project;
+use crate::reporters::RefreshReporter;
 use fs_err::tokio as fs;
+use gix::progress::Count;
+use gix::progress::Id;
+use gix::progress::MessageLevel;
+use gix::progress::NestedProgress;
+use gix::progress::Progress;
+use gix::progress::Step;
+use gix::progress::StepShared;
+use gix::progress::Unit;
 use gix::remote::Direction;
 use std::fmt::Debug;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use tokio::task::spawn_blocking;
 use tracing::instrument;
 
+/// The objects and bytes received while fetching an index, shared between all progress children
+#[derive(Debug)]
+struct FetchProgressShared {
+	url: GixUrl,
+	reporter: Arc<dyn RefreshReporter>,
+	objects: AtomicUsize,
+	bytes: AtomicUsize,
+}
+
+/// Forwards the progress of fetching an index to a [`RefreshReporter`]
+struct FetchProgress {
+	id: Id,
+	name: Option<String>,
+	max: Option<Step>,
+	unit: Option<Unit>,
+	step: StepShared,
+	shared: Arc<FetchProgressShared>,
+}
+
+impl FetchProgress {
+	fn new(url: GixUrl, reporter: Arc<dyn RefreshReporter>) -> Self {
+		FetchProgress {
+			id: gix::progress::UNKNOWN,
+			name: None,
+			max: None,
+			unit: None,
+			step: Default::default(),
+			shared: Arc::new(FetchProgressShared {
+				url,
+				reporter,
+				objects: AtomicUsize::new(0),
+				bytes: AtomicUsize::new(0),
+			}),
+		}
+	}
+
+	fn report(&self) {
+		let step = self.step.load(Ordering::Relaxed);
+		let shared = &self.shared;
+
+		if self.id == Id::from(gix::odb::pack::bundle::write::ProgressId::ReadPackBytes) {
+			shared.bytes.store(step, Ordering::Relaxed);
+		} else if self.id == Id::from(gix::odb::pack::index::write::ProgressId::IndexObjects) {
+			shared.objects.store(step, Ordering::Relaxed);
+		} else {
+			return;
+		}
+
+		shared.reporter.report_refresh_progress(
+			&shared.url,
+			shared.objects.load(Ordering::Relaxed),
+			shared.bytes.load(Ordering::Relaxed),
+		);
+	}
+}
+
+impl Count for FetchProgress {
+	fn set(&self, step: Step) {
+		self.step.store(step, Ordering::Relaxed);
+		self.report();
+	}
+
+	fn step(&self) -> Step {
+		self.step.load(Ordering::Relaxed)
+	}
+
+	fn inc_by(&self, step: Step) {
+		self.step.fetch_add(step, Ordering::Relaxed);
+		self.report();
+	}
+
+	fn counter(&self) -> StepShared {
+		self.step.clone()
+	}
+}
+
+impl Progress for FetchProgress {
+	fn init(&mut self, max: Option<Step>, unit: Option<Unit>) {
+		self.max = max;
+		self.unit = unit;
+	}
+
+	fn unit(&self) -> Option<Unit> {
+		self.unit.clone()
+	}
+
+	fn max(&self) -> Option<Step> {
+		self.max
+	}
+
+	fn set_max(&mut self, max: Option<Step>) -> Option<Step> {
+		std::mem::replace(&mut self.max, max)
+	}
+
+	fn set_name(&mut self, name: String) {
+		self.name = Some(name);
+	}
+
+	fn name(&self) -> Option<String> {
+		self.name.clone()
+	}
+
+	fn id(&self) -> Id {
+		self.id
+	}
+
+	fn message(&self, _level: MessageLevel, message: String) {
+		tracing::trace!("{}: {message}", self.shared.url);
+	}
+}
+
+impl NestedProgress for FetchProgress {
+	type SubProgress = Self;
+
+	fn add_child(&mut self, name: impl Into<String>) -> Self::SubProgress {
+		self.add_child_with_id(name, gix::progress::UNKNOWN)
+	}
+
+	fn add_child_with_id(&mut self, name: impl Into<String>, id: Id) -> Self::SubProgress {
+		FetchProgress {
+			id,
+			name: Some(name.into()),
+			max: None,
+			unit: None,
+			step: Default::default(),
+			shared: self.shared.clone(),
+		}
+	}
+}
+
+/// Opens the repository at `path` if it is usable for fetching. It may not be if a previous
+/// clone was interrupted before the remote was configured, or if the directory is corrupted
+fn open_existing(path: &Path) -> Option<gix::Repository> {
+	let repo = match gix::open_opts(path, gix::open::Options::isolated()) {
+		Ok(repo) => repo,
+		Err(e) => {
+			tracing::warn!("failed to open repository at {}: {e}", path.display());
+			return None;
+		}
+	};
+
+	match repo.find_default_remote(Direction::Fetch) {
+		Some(Ok(_)) => Some(repo),
+		Some(Err(e)) => {
+			tracing::warn!(
+				"failed to get default remote of repository at {}: {e}",
+				path.display()
+			);
+			None
+		}
+		None => {
+			tracing::warn!(
+				"no default remote found in repository at {}",
+				path.display()
+			);
+			None
+		}
+	}
+}
+
+/// Fetches the repository at `path` from `repo_url`, cloning it if needed.
+///
+/// The proxy of the project's [`crate::AuthConfig`] isn't used here: gix's reqwest transport builds
+/// its own client and ignores the `http.proxy` option, so only the proxy environment variables
+/// (which reqwest reads itself) apply to Git indices
 #[instrument(skip_all, level = "debug")]
 pub(crate) async fn refresh_git_repo(
 	path: PathBuf,
 	repo_url: GixUrl,
+	project: &Project,
 ) -> Result<(), errors::RefreshError> {
-	if fs::metadata(&path).await.is_ok() {
-		spawn_blocking(move || {
-			let repo = match gix::open_opts(&path, gix::open::Options::isolated()) {
-				Ok(repo) => repo,
-				Err(e) => return Err(errors::RefreshErrorKind::Open(path, e).into()),
-			};
-			let remote = match repo.find_default_remote(Direction::Fetch) {
-				Some(Ok(remote)) => remote,
-				Some(Err(e)) => {
-					return Err(errors::RefreshErrorKind::GetDefaultRemote(path, e).into());
-				}
-				None => {
-					return Err(errors::RefreshErrorKind::NoDefaultRemote(path).into());
-				}
-			};
+	let interrupt = project.interrupt().clone();
+	let reporter = project.refresh_reporter().clone();
 
-			let connection = remote
-				.connect(Direction::Fetch)
-				.map_err(|e| errors::RefreshErrorKind::Connect(repo_url.clone(), e))?;
+	let result = fetch_or_clone(path, repo_url.clone(), interrupt.clone(), reporter.clone()).await;
+	reporter.report_refresh_done(&repo_url);
 
-			let fetch = match connection.prepare_fetch(gix::progress::Discard, Default::default()) {
-				Ok(fetch) => fetch,
-				Err(e) => {
-					return Err(errors::RefreshErrorKind::PrepareFetch(repo_url.clone(), e).into());
-				}
-			};
+	if result.is_err() && interrupt.load(Ordering::Relaxed) {
+		return Err(errors::RefreshErrorKind::Interrupted(repo_url).into());
+	}
+
+	result
+}
 
-			match fetch.receive(gix::progress::Discard, &false.into()) {
-				Ok(_) => Ok::<_, errors::RefreshError>(()),
-				Err(e) => Err(errors::RefreshErrorKind::Read(repo_url.clone(), e).into()),
+async fn fetch_or_clone(
+	path: PathBuf,
+	repo_url: GixUrl,
+	interrupt: Arc<AtomicBool>,
+	reporter: Arc<dyn RefreshReporter>,
+) -> Result<(), errors::RefreshError> {
+	if fs::metadata(&path).await.is_ok() {
+		let fetched = spawn_blocking({
+			let path = path.clone();
+			let repo_url = repo_url.clone();
+			let interrupt = interrupt.clone();
+			let reporter = reporter.clone();
+
+			move || {
+				let Some(repo) = open_existing(&path) else {
+					return Ok(false);
+				};
+				let remote = match repo.find_default_remote(Direction::Fetch) {
+					Some(Ok(remote)) => remote,
+					Some(Err(e)) => {
+						return Err(errors::RefreshErrorKind::GetDefaultRemote(path, e).into());
+					}
+					None => {
+						return Err(errors::RefreshErrorKind::NoDefaultRemote(path).into());
+					}
+				};
+
+				let connection = remote
+					.connect(Direction::Fetch)
+					.map_err(|e| errors::RefreshErrorKind::Connect(repo_url.clone(), e))?;
+
+				let fetch = match connection
+					.prepare_fetch(gix::progress::Discard, Default::default())
+				{
+					Ok(fetch) => fetch,
+					Err(e) => {
+						return Err(
+							errors::RefreshErrorKind::PrepareFetch(repo_url.clone(), e).into()
+						);
+					}
+				};
+
+				// fetching only transfers what is missing, which continues a previously interrupted fetch
+				let progress = FetchProgress::new(repo_url.clone(), reporter);
+				match fetch.receive(progress, &interrupt) {
+					Ok(_) => Ok::<_, errors::RefreshError>(true),
+					Err(e) => Err(errors::RefreshErrorKind::Read(repo_url.clone(), e).into()),
+				}
 			}
 		})
 		.await
 		.unwrap()?;
 
-		return Ok(());
+		if fetched {
+			return Ok(());
+		}
+
+		tracing::warn!("re-cloning {repo_url} into {} from scratch", path.display());
+		fs::remove_dir_all(&path).await?;
 	}
 
 	fs::create_dir_all(&path).await?;
 
 	spawn_blocking(move || {
-		gix::clone::PrepareFetch::new(
+		let mut prepare = gix::clone::PrepareFetch::new(
 			repo_url.as_url().clone(),
-			path,
+			&path,
 			gix::create::Kind::Bare,
 			gix::create::Options::default(),
 			gix::open::Options::isolated(),
 		)
-		.map_err(|e| {
-			errors::RefreshError::from(errors::RefreshErrorKind::Clone(repo_url.clone(), e))
-		})?
-		.fetch_only(gix::progress::Discard, &false.into())
-		.map_err(|e| {
-			errors::RefreshError::from(errors::RefreshErrorKind::Fetch(repo_url.clone(), e))
-		})
+		.map_err(|e| errors::RefreshErrorKind::Clone(repo_url.clone(), e))?;
+
+		match prepare.fetch_only(FetchProgress::new(repo_url.clone(), reporter), &interrupt) {
+			Ok(_) => Ok(()),
+			Err(e) => {
+				// keep the partial clone so that the next refresh resumes it with a fetch, unless
+				// it failed before the remote was configured, in which case nothing was received
+				prepare.persist();
+				if open_existing(&path).is_none()
+					&& let Err(e) = std::fs::remove_dir_all(&path)
+				{
+					tracing::warn!("failed to remove partial clone at {}: {e}", path.display());
+				}
+
+				Err(errors::RefreshErrorKind::Fetch(repo_url, e).into())
+			}
+		}
 	})
 	.await
 	.unwrap()
-	.map(|_| ())
 }
 
 /// Reads a file from a tree
@@ -204,6 +430,10 @@ pub mod errors {
 		/// Error fetching repository
 		#[error("error fetching repository from {0}")]
 		Fetch(GixUrl, #[source] gix::clone::fetch::Error),
+
+		/// The refresh was interrupted
+		#[error("refreshing repository from {0} was interrupted")]
+		Interrupted(GixUrl),
 	}
 
 	/// Errors that can occur when reading a git-based package source's tree
@@ -262,3 +492,133 @@ pub mod errors {
 		Utf8(String, #[source] std::string::FromUtf8Error),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::source::local_index::LocalIndex;
+	use std::sync::Mutex;
+
+	/// Records the reported progress, and optionally interrupts the refresh on the first report
+	#[derive(Debug, Default)]
+	struct CollectingRefreshReporter {
+		interrupt: Option<Arc<AtomicBool>>,
+		progress: Mutex<Vec<(usize, usize)>>,
+		done: AtomicBool,
+	}
+
+	impl RefreshReporter for CollectingRefreshReporter {
+		fn report_refresh_progress(&self, _url: &GixUrl, objects: usize, bytes: usize) {
+			self.progress.lock().unwrap().push((objects, bytes));
+			if let Some(interrupt) = &self.interrupt {
+				interrupt.store(true, Ordering::Relaxed);
+			}
+		}
+
+		fn report_refresh_done(&self, _url: &GixUrl) {
+			self.done.store(true, Ordering::Relaxed);
+		}
+	}
+
+	async fn local_index(dir: &Path) -> GixUrl {
+		let mut index = LocalIndex::new(&dir.join("index"));
+		for version in ["1.0.0", "1.1.0", "2.0.0"] {
+			index
+				.publish(
+					"acme/lib",
+					version,
+					"{ environment = \"luau\", lib = \"init.luau\" }",
+					&[("init.luau", "return nil\n")],
+				)
+				.await;
+		}
+
+		index.url().parse().unwrap()
+	}
+
+	#[tokio::test]
+	async fn refresh_reports_progress() {
+		let dir = tempfile::tempdir().unwrap();
+		let url = local_index(dir.path()).await;
+		let reporter = Arc::new(CollectingRefreshReporter::default());
+		let project = crate::tests::test_project(dir.path(), dir.path())
+			.with_refresh_reporter(reporter.clone());
+		let path = dir.path().join("clone");
+
+		refresh_git_repo(path.clone(), url.clone(), &project)
+			.await
+			.unwrap();
+		assert!(reporter.done.load(Ordering::Relaxed));
+		let (objects, bytes) = *reporter.progress.lock().unwrap().last().unwrap();
+		assert!(objects > 0, "{objects}");
+		assert!(bytes > 0, "{bytes}");
+
+		// fetching into the existing clone reports through the same reporter
+		reporter.done.store(false, Ordering::Relaxed);
+		refresh_git_repo(path, url, &project).await.unwrap();
+		assert!(reporter.done.load(Ordering::Relaxed));
+	}
+
+	#[tokio::test]
+	async fn interrupted_refresh() {
+		let dir = tempfile::tempdir().unwrap();
+		let url = local_index(dir.path()).await;
+		let project = crate::tests::test_project(dir.path(), dir.path());
+		let reporter = Arc::new(CollectingRefreshReporter {
+			interrupt: Some(project.interrupt().clone()),
+			..Default::default()
+		});
+		let project = project.with_refresh_reporter(reporter.clone());
+		let path = dir.path().join("clone");
+
+		let err = refresh_git_repo(path.clone(), url.clone(), &project)
+			.await
+			.unwrap_err();
+		assert!(
+			matches!(err.inner(), errors::RefreshErrorKind::Interrupted(..)),
+			"{err:?}"
+		);
+		assert!(reporter.done.load(Ordering::Relaxed));
+		// the partial clone is kept to be resumed
+		assert!(open_existing(&path).is_some());
+		let marker = path.join("pesde-marker");
+		fs::write(&marker, "").await.unwrap();
+
+		project.interrupt().store(false, Ordering::Relaxed);
+		let reporter = Arc::new(CollectingRefreshReporter::default());
+		let project = project.with_refresh_reporter(reporter.clone());
+		refresh_git_repo(path.clone(), url, &project).await.unwrap();
+
+		// the partial clone was fetched into rather than cloned again from scratch
+		assert!(fs::metadata(&marker).await.is_ok());
+		let repo = open_existing(&path).unwrap();
+		assert!(repo.find_reference("refs/remotes/origin/main").is_ok());
+		assert!(root_tree(&repo).is_ok());
+	}
+
+	#[test]
+	fn open_existing_detects_unusable_repos() {
+		let dir = tempfile::tempdir().unwrap();
+
+		let path = dir.path().join("missing");
+		assert!(open_existing(&path).is_none());
+
+		let path = dir.path().join("corrupted");
+		std::fs::create_dir_all(&path).unwrap();
+		std::fs::write(path.join("HEAD"), "garbage").unwrap();
+		assert!(open_existing(&path).is_none());
+
+		// a clone interrupted before the remote was configured
+		let path = dir.path().join("interrupted");
+		gix::init_bare(&path).unwrap();
+		assert!(open_existing(&path).is_none());
+
+		// resuming the clone only requires the remote to be configured
+		let mut config = std::fs::read_to_string(path.join("config")).unwrap();
+		config.push_str(
+			"[remote \"origin\"]\n\turl = https://github.com/pesde-pkg/index\n\tfetch = +refs/heads/*:refs/remotes/origin/*\n",
+		);
+		std::fs::write(path.join("config"), config).unwrap();
+		assert!(open_existing(&path).is_some());
+	}
+}
@@ -165,4 +165,50 @@ mod tests {
 			assert_eq!(id.to_string(), serialized);
 		}
 	}
+
+	const VERSIONS: &[&str] = &[
+		"0.0.0",
+		"1.2.3",
+		"1.2.3-beta.1",
+		"1.2.3+build.5",
+		"1.2.3-rc.1+build.5",
+		"10.20.30-alpha-1.0+sha.abc-def",
+	];
+
+	#[test]
+	fn package_ids_round_trip() {
+		let bases = [
+			"pesde:https://github.com/pesde-pkg/index:foo/bar+roblox@",
+			"pesde:https://github.com/pesde-pkg/index:foo/bar+roblox_server@",
+			"pesde:https://github.com/pesde-pkg/index:foo/bar+lune@",
+			"pesde:https://github.com/pesde-pkg/index:foo/bar+luau@",
+			"wally:https://github.com/pesde-pkg/index:foo/bar@",
+			"git:https://github.com/pesde-pkg/index:abcdef#",
+		];
+
+		for base in bases {
+			for version in VERSIONS {
+				let serialized = format!("{base}{version}");
+				let id: PackageId = serialized.parse().unwrap();
+				assert_eq!(id.to_string(), serialized);
+				assert_eq!(id.version().to_string(), *version);
+				assert_eq!(id.to_string().parse::<PackageId>().unwrap(), id);
+			}
+		}
+	}
+
+	#[test]
+	#[expect(deprecated)]
+	fn version_ids_round_trip() {
+		use crate::source::pesde::backend::VersionId;
+
+		for target in ["roblox", "roblox_server", "lune", "luau"] {
+			for version in VERSIONS {
+				let serialized = format!("{version}:{target}");
+				let id: VersionId = serialized.parse().unwrap();
+				assert_eq!(id.to_string(), serialized);
+				assert_eq!(id.to_string().parse::<VersionId>().unwrap(), id);
+			}
+		}
+	}
 }
@@ -4,6 +4,7 @@ use crate::Project;
 use crate::RefreshedSources;
 use crate::Subproject;
 use crate::manifest::Alias;
+use crate::manifest::BinPlatforms;
 use crate::manifest::DependencyType;
 use crate::reporters::DownloadProgressReporter;
 use crate::ser_display_deser_fromstr;
@@ -39,6 +40,26 @@ pub const ADDITIONAL_FORBIDDEN_FILES: &[&str] = &["default.project.json"];
 /// Directories that will not be stored when downloading a package. These are only directories which break pesde's functionality, or are meaningless and possibly heavy
 pub const IGNORED_DIRS: &[&str] = &[".git"];
 
+/// Returns whether the file name is in [IGNORED_FILES], ignoring case
+#[must_use]
+pub fn is_ignored_file(name: &str) -> bool {
+	IGNORED_FILES.iter().any(|f| f.eq_ignore_ascii_case(name))
+}
+
+/// Returns whether the file name is in [ADDITIONAL_FORBIDDEN_FILES], ignoring case
+#[must_use]
+pub fn is_additional_forbidden_file(name: &str) -> bool {
+	ADDITIONAL_FORBIDDEN_FILES
+		.iter()
+		.any(|f| f.eq_ignore_ascii_case(name))
+}
+
+/// Returns whether the directory name is in [IGNORED_DIRS], ignoring case
+#[must_use]
+pub fn is_ignored_dir(name: &str) -> bool {
+	IGNORED_DIRS.iter().any(|d| d.eq_ignore_ascii_case(name))
+}
+
 /// A specifier for a dependency
 pub trait DependencySpecifier: Debug + Display {
 	/// The realm this dependency is for, if any
@@ -46,7 +67,13 @@ pub trait DependencySpecifier: Debug + Display {
 }
 
 /// A reference to a package
-pub trait PackageRef: Debug {}
+pub trait PackageRef: Debug {
+	/// Whether the package is read from the local filesystem instead of being downloaded
+	fn is_local(&self) -> bool;
+
+	/// Whether the package comes from a Wally index
+	fn is_wally_package(&self) -> bool;
+}
 
 /// The exports of a package
 #[derive(Debug, Clone)]
@@ -55,6 +82,8 @@ pub struct PackageExports {
 	pub lib_file: Option<RelativePathBuf>,
 	/// The path to the bin export file
 	pub bin_file: Option<RelativePathBuf>,
+	/// The platforms the bin export supports
+	pub bin_platforms: BinPlatforms,
 }
 
 /// A resolved package
@@ -235,6 +264,24 @@ impl Display for PackageSources {
 	}
 }
 
+impl PackageSources {
+	/// Whether a local copy of this source's index exists, which can be used without refreshing it
+	pub async fn has_local_index(&self, project: &Project) -> bool {
+		let path = match self {
+			Self::Pesde(source) => match source.repo() {
+				pesde::backend::PesdePackageBackends::Git(repo) => repo.path(project),
+				pesde::backend::PesdePackageBackends::Api(_) => return false,
+			},
+			Self::Wally(source) => match source.repo() {
+				wally::backend::WallyPackageBackends::Git(repo) => repo.path(project),
+			},
+			Self::Git(_) | Self::Path(_) => return false,
+		};
+
+		fs_err::tokio::metadata(path).await.is_ok()
+	}
+}
+
 impl FromStr for PackageSources {
 	type Err = errors::PackageSourcesFromStr;
 
@@ -339,7 +386,23 @@ macro_rules! impls {
 			}
 			ser_display_deser_fromstr!(PackageRefs);
 
-			impl PackageRef for PackageRefs {}
+			impl PackageRef for PackageRefs {
+				fn is_local(&self) -> bool {
+					match self {
+						$(
+							Self::$source(pkg_ref) => pkg_ref.is_local()
+						),+
+					}
+				}
+
+				fn is_wally_package(&self) -> bool {
+					match self {
+						$(
+							Self::$source(pkg_ref) => pkg_ref.is_wally_package()
+						),+
+					}
+				}
+			}
 
 			impl Display for PackageRefs {
 				fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -583,13 +646,23 @@ impl DependencySpecifiers {
 }
 
 impl PackageRefs {
-	/// Returns whether this package reference is local
+	/// Returns whether this package reference is local. Kept as an inherent method so that it can
+	/// be called without importing [PackageRef]
 	#[must_use]
 	pub fn is_local(&self) -> bool {
-		matches!(self, PackageRefs::Path(_))
+		PackageRef::is_local(self)
 	}
 }
 
+// the tests of the pesde source live next to it, but outside of its module as it is deprecated
+#[cfg(test)]
+#[path = "pesde/tests.rs"]
+mod pesde_tests;
+
+#[cfg(test)]
+#[path = "pesde/local_index.rs"]
+pub(crate) mod local_index;
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -648,4 +721,32 @@ mod tests {
 			assert_eq!(PackageRefs::from_str(serialized).unwrap(), pkg_ref);
 		}
 	}
+
+	#[test]
+	fn package_ref_kinds() {
+		let refs = [
+			(
+				PackageRefs::Pesde("foo/bar+lune".parse().unwrap()),
+				false,
+				false,
+			),
+			(PackageRefs::Wally("foo/bar".parse().unwrap()), false, true),
+			(PackageRefs::Git("abcdef".parse().unwrap()), false, false),
+			(PackageRefs::Path("/dev/null".parse().unwrap()), true, false),
+		];
+
+		for (pkg_ref, is_local, is_wally_package) in refs {
+			assert_eq!(pkg_ref.is_local(), is_local, "{pkg_ref}");
+			assert_eq!(pkg_ref.is_wally_package(), is_wally_package, "{pkg_ref}");
+		}
+	}
+
+	#[test]
+	fn ignored_files_case_insensitive() {
+		assert!(is_ignored_file("Foreman.toml"));
+		assert!(is_ignored_file(".DS_STORE"));
+		assert!(is_additional_forbidden_file("Default.Project.json"));
+		assert!(is_ignored_dir(".GIT"));
+		assert!(!is_ignored_file("init.luau"));
+	}
 }
@@ -27,6 +27,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::collections::BTreeMap;
 use std::fmt::Display;
+use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -46,6 +47,29 @@ pub(crate) fn local_version() -> Version {
 	}
 }
 
+/// Removes `.` and `..` components from the path without touching the filesystem, so that the
+/// same package referenced through different relative paths gets the same ID
+fn normalize_path(path: &Path) -> PathBuf {
+	let mut normalized = PathBuf::new();
+
+	for component in path.components() {
+		match component {
+			Component::CurDir => {}
+			Component::ParentDir => match normalized.components().next_back() {
+				Some(Component::Normal(_)) => {
+					normalized.pop();
+				}
+				// `..` can't go above the root, but leading `..`s of relative paths are kept
+				Some(Component::RootDir | Component::Prefix(_)) => {}
+				_ => normalized.push(component),
+			},
+			component => normalized.push(component),
+		}
+	}
+
+	normalized
+}
+
 /// The path for a path dependency specifier
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum RelativeOrAbsolutePath {
@@ -97,10 +121,13 @@ impl PackageSource for PathPackageSource {
 			unreachable!("invalid specifier type for path package source");
 		};
 
-		let path = match &specifier.path {
+		let path = normalize_path(&match &specifier.path {
+			RelativeOrAbsolutePath::Relative(rel_path) if specifier.workspace => {
+				rel_path.to_path(subproject.project().dir())
+			}
 			RelativeOrAbsolutePath::Relative(rel_path) => rel_path.to_path(subproject.dir()),
 			RelativeOrAbsolutePath::Absolute(abs_path) => abs_path.clone(),
-		};
+		});
 
 		let manifest = fs::read_to_string(path.join(MANIFEST_FILE_NAME))
 			.await
@@ -147,6 +174,19 @@ impl PackageSource for PathPackageSource {
 							.to_string();
 					}
 					DependencySpecifiers::Git(_) => {}
+					// the path is relative to this package, but will be resolved relative to the subproject
+					DependencySpecifiers::Path(spec) if !spec.workspace => {
+						if let RelativeOrAbsolutePath::Relative(rel_path) = &spec.path {
+							let dep_path = normalize_path(&rel_path.to_path(&path));
+
+							spec.path = pathdiff::diff_paths(&dep_path, subproject.dir())
+								.and_then(|diff| RelativePathBuf::from_path(diff).ok())
+								.map_or(
+									RelativeOrAbsolutePath::Absolute(dep_path),
+									RelativeOrAbsolutePath::Relative,
+								);
+						}
+					}
 					DependencySpecifiers::Path(_) => {}
 				}
 
@@ -255,3 +295,62 @@ pub mod errors {
 		ManifestRead(#[from] crate::errors::ManifestReadError),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn normalizes_paths() {
+		assert_eq!(
+			normalize_path(Path::new("/ws/pkgs/x/./../y")),
+			PathBuf::from("/ws/pkgs/y")
+		);
+		assert_eq!(
+			normalize_path(Path::new("../../a/b/..")),
+			PathBuf::from("../../a")
+		);
+		assert_eq!(normalize_path(Path::new("/../a")), PathBuf::from("/a"));
+	}
+
+	#[tokio::test]
+	async fn workspace_sibling_dependency() {
+		use crate::Importer;
+		use crate::RefreshedSources;
+		use crate::find_roots;
+
+		let dir = tempfile::tempdir().unwrap();
+		let workspace_dir = dir.path().join("workspace");
+		let write_manifest = async |member: &str, manifest: &str| {
+			let member_dir = workspace_dir.join(member);
+			fs::create_dir_all(&member_dir).await.unwrap();
+			fs::write(member_dir.join(MANIFEST_FILE_NAME), manifest)
+				.await
+				.unwrap();
+		};
+		write_manifest("", "[workspace]\nmembers = [\"packages/*\"]\n").await;
+		write_manifest(
+			"packages/a",
+			"[dependencies]\nb = { path = \"packages/b\", workspace = true }\n",
+		)
+		.await;
+		write_manifest("packages/b", "").await;
+
+		// run from within a member, the project is still the whole workspace
+		let (root, importer) = find_roots(workspace_dir.join("packages/a")).await.unwrap();
+		assert_eq!(root, workspace_dir);
+		assert_eq!(importer, Importer::new(RelativePathBuf::from("packages/a")));
+
+		let project = crate::tests::test_project(dir.path(), root);
+		let lockfile = project
+			.resolve_lockfile(false, &RefreshedSources::new())
+			.await
+			.unwrap();
+
+		let (id, _, _) = &lockfile.graph.importers[&importer].dependencies[&"b".parse().unwrap()];
+		let PackageRefs::Path(pkg_ref) = id.pkg_ref() else {
+			panic!("expected a path package, got {id}");
+		};
+		assert_eq!(pkg_ref.path, Path::new("packages/b"));
+	}
+}
@@ -12,7 +12,15 @@ pub struct PathPackageRef {
 }
 ser_display_deser_fromstr!(PathPackageRef);
 
-impl PackageRef for PathPackageRef {}
+impl PackageRef for PathPackageRef {
+	fn is_local(&self) -> bool {
+		true
+	}
+
+	fn is_wally_package(&self) -> bool {
+		false
+	}
+}
 
 impl std::fmt::Display for PathPackageRef {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
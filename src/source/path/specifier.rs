@@ -14,6 +14,9 @@ pub const DISCRIMINATOR_FIELD: &str = "path";
 pub struct PathDependencySpecifier {
 	/// The path to the package
 	pub path: RelativeOrAbsolutePath,
+	/// Whether a relative path is relative to the workspace root instead of the manifest's directory
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub workspace: bool,
 	/// The realm of the package
 	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub realm: Option<Realm>,
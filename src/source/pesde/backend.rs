@@ -5,7 +5,9 @@ use crate::GixUrl;
 use crate::Project;
 use crate::manifest::Alias;
 use crate::manifest::DependencyType;
+use crate::names::Name;
 use crate::names::PackageName;
+use crate::names::Scope;
 use crate::reporters::DownloadProgressReporter;
 use crate::source::git::specifier::IndexGitDependencySpecifier;
 use crate::source::git_index::read_file;
@@ -15,9 +17,12 @@ use crate::source::pesde::target::Target;
 use crate::source::pesde::target::TargetKind;
 use crate::source::wally::specifier::IndexWallyDependencySpecifier;
 use crate::util::ToEscaped as _;
+use crate::util::archive_entry_path;
 use async_stream::try_stream;
+use fs_err::tokio as fs;
 use futures::Stream;
 use futures::StreamExt as _;
+use relative_path::RelativePath;
 use relative_path::RelativePathBuf;
 use reqwest::header::ACCEPT;
 use reqwest::header::AUTHORIZATION;
@@ -29,18 +34,48 @@ use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::path::Path;
 use std::path::PathBuf;
+use std::pin::Pin;
 use std::str::FromStr;
 use std::sync::Arc;
+use tokio::io::AsyncBufRead;
 use tokio::io::AsyncReadExt as _;
 use tokio::task::spawn_blocking;
 use tracing::instrument;
 use urlencoding::encode;
 
+/// The name of the file containing a scope's information in a pesde index
+pub const SCOPE_INFO_FILE: &str = "scope.toml";
+
+/// The index files of every package in an index, with errors reported per package
+pub type AllIndexFiles<E> = BTreeMap<PackageName, Result<IndexFile, E>>;
+
 fn default_archive_size() -> usize {
 	4 * 1024 * 1024
 }
 
+/// Returns the path to read an archive from if the download URL points to the local filesystem.
+/// Only indices which are themselves on the local filesystem may point to local archives, and
+/// relative paths are resolved against the index's directory
+pub(crate) fn local_download_path(url: &str, index_dir: Option<&Path>) -> Option<PathBuf> {
+	let index_dir = index_dir?;
+
+	match url::Url::parse(url) {
+		Ok(url) if url.scheme() == "file" => url.to_file_path().ok(),
+		// the path is decoded the same way `to_file_path` decodes `file://` URLs
+		Err(url::ParseError::RelativeUrlWithoutBase) => {
+			let path = urlencoding::decode(url).ok()?;
+			Some(index_dir.join(path.as_ref()))
+		}
+		_ if Path::new(url).is_absolute() => {
+			let path = urlencoding::decode(url).ok()?;
+			Some(PathBuf::from(path.as_ref()))
+		}
+		_ => None,
+	}
+}
+
 /// The configuration for the pesde index
 #[derive(Deserialize, Debug, Clone)]
 pub struct IndexConfig {
@@ -57,6 +92,12 @@ pub struct IndexConfig {
 	/// The packages to display in the CLI for default script implementations
 	#[serde(default)]
 	pub scripts_packages: Vec<PackageName>,
+	/// Files to not store when downloading packages, in addition to [crate::source::IGNORED_FILES]
+	#[serde(default)]
+	pub ignored_files: Vec<String>,
+	/// Directories to not store when downloading packages, in addition to [crate::source::IGNORED_DIRS]
+	#[serde(default)]
+	pub ignored_dirs: Vec<String>,
 }
 
 impl IndexConfig {
@@ -74,6 +115,28 @@ impl IndexConfig {
 			.unwrap_or("{API_URL}/v1/packages/{PACKAGE}/{PACKAGE_VERSION}/{PACKAGE_TARGET}/archive")
 			.replace("{API_URL}", self.api())
 	}
+
+	/// Returns whether an entry of a package is ignored by this index's configuration, ignoring case
+	#[must_use]
+	pub fn is_ignored(&self, path: &RelativePath, is_dir: bool) -> bool {
+		let dirs = if is_dir { Some(path) } else { path.parent() };
+		if dirs.is_some_and(|dirs| {
+			dirs.iter().any(|dir| {
+				self.ignored_dirs
+					.iter()
+					.any(|d| d.eq_ignore_ascii_case(dir))
+			})
+		}) {
+			return true;
+		}
+
+		!is_dir
+			&& path.file_name().is_some_and(|name| {
+				self.ignored_files
+					.iter()
+					.any(|f| f.eq_ignore_ascii_case(name))
+			})
+	}
 }
 
 /// An entry in a package's documentation
@@ -285,6 +348,13 @@ pub trait PesdePackageSourceBackend: Debug + Display + Send + Sync {
 		name: PackageName,
 	) -> impl Future<Output = Result<Option<IndexFile>, Self::ReadIndexFileError>> + Send;
 
+	/// Reads the index files of every package in the index
+	fn all_index_files(
+		&self,
+		project: &Project,
+	) -> impl Future<Output = Result<AllIndexFiles<Self::ReadIndexFileError>, Self::ReadIndexFileError>>
+	+ Send;
+
 	/// Downloads entries for a package version
 	fn download_entries<R: DownloadProgressReporter + 'static>(
 		&self,
@@ -324,7 +394,7 @@ impl GitPesdePackageSourceBackend {
 		Self { repo_url }
 	}
 
-	fn path(&self, project: &Project) -> PathBuf {
+	pub(crate) fn path(&self, project: &Project) -> PathBuf {
 		project
 			.data_dir()
 			.join("git_repos")
@@ -341,7 +411,12 @@ impl PesdePackageSourceBackend for GitPesdePackageSourceBackend {
 
 	#[instrument(skip_all, level = "debug")]
 	async fn refresh(&self, project: &Project) -> Result<(), Self::RefreshError> {
-		crate::source::git_index::refresh_git_repo(self.path(project), self.repo_url.clone()).await
+		crate::source::git_index::refresh_git_repo(
+			self.path(project),
+			self.repo_url.clone(),
+			project,
+		)
+		.await
 	}
 
 	#[instrument(skip_all, ret(level = "trace"), level = "debug")]
@@ -387,6 +462,57 @@ impl PesdePackageSourceBackend for GitPesdePackageSourceBackend {
 		.unwrap()
 	}
 
+	async fn all_index_files(
+		&self,
+		project: &Project,
+	) -> Result<AllIndexFiles<Self::ReadIndexFileError>, Self::ReadIndexFileError> {
+		let path = self.path(project);
+
+		spawn_blocking(move || {
+			let repo = gix::open(&path)?;
+			let tree = root_tree(&repo)?;
+			let mut packages = BTreeMap::new();
+
+			for scope_entry in tree.decode()?.entries {
+				if !scope_entry.mode.is_tree() {
+					continue;
+				}
+
+				let Ok(scope) = scope_entry.filename.to_string().parse::<Scope>() else {
+					tracing::debug!("skipping non-scope directory {}", scope_entry.filename);
+					continue;
+				};
+
+				let scope_tree = repo.find_tree(scope_entry.oid)?;
+				for name_entry in scope_tree.decode()?.entries {
+					if !name_entry.mode.is_blob() || name_entry.filename == SCOPE_INFO_FILE {
+						continue;
+					}
+
+					let Ok(name) = name_entry.filename.to_string().parse::<Name>() else {
+						tracing::debug!(
+							"skipping non-package file {scope}/{}",
+							name_entry.filename
+						);
+						continue;
+					};
+
+					let file = match read_file(&scope_tree, [name.as_str()]) {
+						Ok(Some(s)) => toml::from_str(&s).map_err(Into::into),
+						Ok(None) => continue,
+						Err(e) => Err(errors::GitReadIndexFileErrorKind::ReadFile(e).into()),
+					};
+
+					packages.insert(PackageName::new(scope.clone(), name), file);
+				}
+			}
+
+			Ok(packages)
+		})
+		.await
+		.unwrap()
+	}
+
 	fn download_entries<R: DownloadProgressReporter + 'static>(
 		&self,
 		project: &Project,
@@ -397,59 +523,291 @@ impl PesdePackageSourceBackend for GitPesdePackageSourceBackend {
 	{
 		try_stream!({
 			let config = self.config(project).await?;
-			let url = config
-				.download()
-				.replace("{PACKAGE}", &encode(&package.to_string()))
-				.replace("{PACKAGE_VERSION}", &encode(&version_id.0.to_string()))
-				.replace("{PACKAGE_TARGET}", &encode(&version_id.1.to_string()));
-
-			let mut request = project
-				.reqwest()
-				.get(&url)
-				.header(ACCEPT, "application/octet-stream");
-
-			if let Some(token) = project.auth_config().tokens().get(&self.repo_url) {
+			let token = project.auth_config().tokens().get(&self.repo_url).cloned();
+			if token.is_some() {
 				tracing::debug!("using token for {}", self.repo_url);
-				request = request.header(AUTHORIZATION, token);
 			}
 
-			let response = request.send().await?.error_for_status()?;
-
-			let bytes = crate::reporters::response_to_async_buf_read(response, reporter.clone());
-			tokio::pin!(bytes);
-
-			let decoder = async_compression::tokio::bufread::GzipDecoder::new(
-				tokio::io::BufReader::new(bytes),
+			// only indices on the local filesystem may have their archives read from it
+			let index_dir = (self.repo_url.as_url().scheme == gix::url::Scheme::File)
+				.then(|| PathBuf::from(self.repo_url.as_url().path.to_string()));
+
+			let entries = download_archive_entries(
+				project,
+				config,
+				package,
+				version_id,
+				token,
+				index_dir.as_deref(),
+				reporter,
 			);
-			let archive = async_tar::Archive::new(decoder);
-			let mut entries_stream = archive
-				.entries()
-				.map_err(errors::GitDownloadErrorKind::OpenArchive)?;
-
-			while let Some(entry_result) = entries_stream.next().await {
-				let mut entry = entry_result.map_err(errors::GitDownloadErrorKind::ReadEntry)?;
-
-				let path = entry
-					.path()
-					.map_err(errors::GitDownloadErrorKind::ReadEntry)?;
-				let path_str = path
-					.to_str()
-					.ok_or_else(|| errors::GitDownloadErrorKind::InvalidPath)?;
-				let rel_path = RelativePathBuf::from_path(path_str)
-					.map_err(|_e| errors::GitDownloadErrorKind::InvalidPath)?;
-
-				if entry.header().entry_type().is_dir() {
-					yield (rel_path, None);
-					continue;
-				}
+			futures::pin_mut!(entries);
+			while let Some(entry) = entries.next().await {
+				yield entry?;
+			}
+		})
+	}
+}
 
-				let mut contents = Vec::new();
-				entry
-					.read_to_end(&mut contents)
+/// Downloads a package's archive as described by the index config, and yields its entries
+fn download_archive_entries<R: DownloadProgressReporter + 'static>(
+	project: &Project,
+	config: IndexConfig,
+	package: &PackageName,
+	version_id: &VersionId,
+	token: Option<String>,
+	index_dir: Option<&Path>,
+	reporter: Arc<R>,
+) -> impl Stream<Item = Result<(RelativePathBuf, Option<Vec<u8>>), errors::ArchiveDownloadError>> + Send
+{
+	let url = config
+		.download()
+		.replace("{PACKAGE}", &encode(&package.to_string()))
+		.replace("{PACKAGE_VERSION}", &encode(&version_id.0.to_string()))
+		.replace("{PACKAGE_TARGET}", &encode(&version_id.1.to_string()));
+
+	try_stream!({
+		let bytes: Pin<Box<dyn AsyncBufRead + Send>> =
+			if let Some(path) = local_download_path(&url, index_dir) {
+				tracing::debug!("reading archive from {}", path.display());
+
+				let file = fs::File::open(&path)
+					.await
+					.map_err(errors::ArchiveDownloadErrorKind::ReadLocal)?;
+				let len = file
+					.metadata()
 					.await
-					.map_err(errors::GitDownloadErrorKind::ReadEntry)?;
+					.map_err(errors::ArchiveDownloadErrorKind::ReadLocal)?
+					.len();
+				reporter.report_progress(len, len);
+				reporter.report_done();
+
+				Box::pin(tokio::io::BufReader::new(file))
+			} else {
+				let mut request = project
+					.reqwest()
+					.get(&url)
+					.header(ACCEPT, "application/octet-stream");
+
+				if let Some(token) = token {
+					request = request.header(AUTHORIZATION, token);
+				}
+
+				let response = request.send().await?.error_for_status()?;
+
+				Box::pin(crate::reporters::response_to_async_buf_read(
+					response,
+					reporter.clone(),
+					crate::reporters::max_download_size(Some(config.max_archive_size as u64)),
+				))
+			};
+
+		let decoder =
+			async_compression::tokio::bufread::GzipDecoder::new(tokio::io::BufReader::new(bytes));
+		let archive = async_tar::Archive::new(decoder);
+		let mut entries_stream = archive
+			.entries()
+			.map_err(errors::ArchiveDownloadErrorKind::OpenArchive)?;
+
+		let read_entry_error =
+			|e: std::io::Error| match crate::reporters::DownloadTooLarge::find(&e) {
+				Some(max_size) => errors::ArchiveDownloadErrorKind::TooLarge(max_size),
+				None => errors::ArchiveDownloadErrorKind::ReadEntry(e),
+			};
+
+		while let Some(entry_result) = entries_stream.next().await {
+			let mut entry = entry_result.map_err(read_entry_error)?;
+
+			let path = entry
+				.path()
+				.map_err(errors::ArchiveDownloadErrorKind::ReadEntry)?;
+			let path_str = path
+				.to_str()
+				.ok_or_else(|| errors::ArchiveDownloadErrorKind::InvalidPath)?;
+			let rel_path = archive_entry_path(path_str).ok_or_else(|| {
+				errors::ArchiveDownloadErrorKind::UnsafePath(path_str.to_string())
+			})?;
+
+			let is_dir = entry.header().entry_type().is_dir();
+			if config.is_ignored(&rel_path, is_dir) {
+				continue;
+			}
 
-				yield (rel_path, Some(contents));
+			if is_dir {
+				yield (rel_path, None);
+				continue;
+			}
+
+			let mut contents = Vec::new();
+			entry
+				.read_to_end(&mut contents)
+				.await
+				.map_err(read_entry_error)?;
+
+			yield (rel_path, Some(contents));
+		}
+	})
+}
+
+/// The prefix of URLs which point to an index served over the registry's HTTP API
+pub const API_URL_PREFIX: &str = "api+";
+
+fn is_api_url(url: &GixUrl) -> bool {
+	matches!(&url.as_url().scheme, gix::url::Scheme::Ext(scheme) if scheme.starts_with(API_URL_PREFIX))
+}
+
+/// A pesde package source backend which reads index data as JSON from the registry's HTTP API,
+/// without cloning the Git index
+#[derive(Debug, Clone)]
+pub struct ApiPesdePackageSourceBackend {
+	url: GixUrl,
+	/// The index config, fetched once per source
+	config: Arc<tokio::sync::OnceCell<IndexConfig>>,
+}
+
+impl PartialEq for ApiPesdePackageSourceBackend {
+	fn eq(&self, other: &Self) -> bool {
+		self.url == other.url
+	}
+}
+
+impl Eq for ApiPesdePackageSourceBackend {}
+
+impl std::hash::Hash for ApiPesdePackageSourceBackend {
+	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		self.url.hash(state);
+	}
+}
+
+impl PartialOrd for ApiPesdePackageSourceBackend {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for ApiPesdePackageSourceBackend {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		self.url.cmp(&other.url)
+	}
+}
+
+impl Display for ApiPesdePackageSourceBackend {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.url)
+	}
+}
+
+impl ApiPesdePackageSourceBackend {
+	/// Creates a new HTTP API pesde package source backend from an `api+` prefixed URL
+	#[must_use]
+	pub fn new(url: GixUrl) -> Self {
+		Self {
+			url,
+			config: Arc::default(),
+		}
+	}
+
+	/// The URL of the API, without the `api+` prefix
+	#[must_use]
+	pub fn api_url(&self) -> String {
+		let url = self.url.to_string();
+		url.strip_prefix(API_URL_PREFIX)
+			.unwrap_or(&url)
+			.trim_end_matches('/')
+			.to_string()
+	}
+
+	fn get(&self, project: &Project, path: &str) -> reqwest::RequestBuilder {
+		let mut request = project
+			.reqwest()
+			.get(format!("{}/v1/index/{path}", self.api_url()))
+			.header(ACCEPT, "application/json");
+
+		if let Some(token) = project.auth_config().tokens().get(&self.url) {
+			tracing::debug!("using token for {}", self.url);
+			request = request.header(AUTHORIZATION, token);
+		}
+
+		request
+	}
+}
+
+impl PesdePackageSourceBackend for ApiPesdePackageSourceBackend {
+	type RefreshError = std::convert::Infallible;
+	type ConfigError = errors::ApiConfigError;
+	type ReadIndexFileError = errors::ApiReadIndexFileError;
+	type DownloadError = errors::ApiDownloadError;
+
+	async fn refresh(&self, _project: &Project) -> Result<(), Self::RefreshError> {
+		// index files are fetched on demand, so there is nothing to refresh
+		Ok(())
+	}
+
+	#[instrument(skip_all, ret(level = "trace"), level = "debug")]
+	async fn config(&self, project: &Project) -> Result<IndexConfig, Self::ConfigError> {
+		self.config
+			.get_or_try_init(|| async {
+				Ok(self
+					.get(project, "config")
+					.send()
+					.await?
+					.error_for_status()?
+					.json()
+					.await?)
+			})
+			.await
+			.cloned()
+	}
+
+	async fn read_index_file(
+		&self,
+		project: &Project,
+		name: PackageName,
+	) -> Result<Option<IndexFile>, Self::ReadIndexFileError> {
+		let response = self
+			.get(
+				project,
+				&format!(
+					"{}/{}",
+					encode(name.scope().as_str()),
+					encode(name.name().as_str())
+				),
+			)
+			.send()
+			.await?;
+
+		if response.status() == reqwest::StatusCode::NOT_FOUND {
+			return Ok(None);
+		}
+
+		Ok(Some(response.error_for_status()?.json().await?))
+	}
+
+	async fn all_index_files(
+		&self,
+		_project: &Project,
+	) -> Result<AllIndexFiles<Self::ReadIndexFileError>, Self::ReadIndexFileError> {
+		Err(errors::ApiReadIndexFileErrorKind::ListingUnsupported(self.url.clone()).into())
+	}
+
+	fn download_entries<R: DownloadProgressReporter + 'static>(
+		&self,
+		project: &Project,
+		package: &PackageName,
+		version_id: &VersionId,
+		reporter: Arc<R>,
+	) -> impl Stream<Item = Result<(RelativePathBuf, Option<Vec<u8>>), Self::DownloadError>> + Send
+	{
+		try_stream!({
+			let config = self.config(project).await?;
+			let token = project.auth_config().tokens().get(&self.url).cloned();
+
+			let entries = download_archive_entries(
+				project, config, package, version_id, token, None, reporter,
+			);
+			futures::pin_mut!(entries);
+			while let Some(entry) = entries.next().await {
+				yield entry?;
 			}
 		})
 	}
@@ -461,12 +819,28 @@ impl PesdePackageSourceBackend for GitPesdePackageSourceBackend {
 pub enum PesdePackageBackends {
 	/// A Git-based pesde package source backend
 	Git(GitPesdePackageSourceBackend),
+	/// An HTTP API-based pesde package source backend
+	Api(ApiPesdePackageSourceBackend),
+}
+
+impl PesdePackageBackends {
+	/// Creates the backend for an index URL. URLs prefixed with `api+` are read through the
+	/// registry's HTTP API, and all others are treated as Git repositories
+	#[must_use]
+	pub fn from_url(url: GixUrl) -> Self {
+		if is_api_url(&url) {
+			Self::Api(ApiPesdePackageSourceBackend::new(url))
+		} else {
+			Self::Git(GitPesdePackageSourceBackend::new(url))
+		}
+	}
 }
 
 impl Display for PesdePackageBackends {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 		match self {
 			PesdePackageBackends::Git(repo) => write!(f, "{repo}"),
+			PesdePackageBackends::Api(api) => write!(f, "{api}"),
 		}
 	}
 }
@@ -475,12 +849,10 @@ impl FromStr for PesdePackageBackends {
 	type Err = errors::ParseBackendError;
 
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		let git_err = match s.parse::<GitPesdePackageSourceBackend>() {
-			Ok(repo) => return Ok(PesdePackageBackends::Git(repo)),
-			Err(e) => e,
-		};
-
-		Err(errors::ParseBackendErrorKind::NoMatch(s.to_string(), git_err).into())
+		match s.parse::<GixUrl>() {
+			Ok(url) => Ok(Self::from_url(url)),
+			Err(e) => Err(errors::ParseBackendErrorKind::NoMatch(s.to_string(), e).into()),
+		}
 	}
 }
 
@@ -495,12 +867,14 @@ impl PesdePackageSourceBackend for PesdePackageBackends {
 	async fn refresh(&self, project: &Project) -> Result<(), Self::RefreshError> {
 		match self {
 			PesdePackageBackends::Git(repo) => repo.refresh(project).await.map_err(Into::into),
+			PesdePackageBackends::Api(api) => api.refresh(project).await.map_err(|e| match e {}),
 		}
 	}
 
 	async fn config(&self, project: &Project) -> Result<IndexConfig, Self::ConfigError> {
 		match self {
 			PesdePackageBackends::Git(repo) => repo.config(project).await.map_err(Into::into),
+			PesdePackageBackends::Api(api) => api.config(project).await.map_err(Into::into),
 		}
 	}
 
@@ -514,6 +888,29 @@ impl PesdePackageSourceBackend for PesdePackageBackends {
 				.read_index_file(project, name)
 				.await
 				.map_err(Into::into),
+			PesdePackageBackends::Api(api) => {
+				api.read_index_file(project, name).await.map_err(Into::into)
+			}
+		}
+	}
+
+	async fn all_index_files(
+		&self,
+		project: &Project,
+	) -> Result<AllIndexFiles<Self::ReadIndexFileError>, Self::ReadIndexFileError> {
+		match self {
+			PesdePackageBackends::Git(repo) => Ok(repo
+				.all_index_files(project)
+				.await?
+				.into_iter()
+				.map(|(name, file)| (name, file.map_err(Into::into)))
+				.collect()),
+			PesdePackageBackends::Api(api) => Ok(api
+				.all_index_files(project)
+				.await?
+				.into_iter()
+				.map(|(name, file)| (name, file.map_err(Into::into)))
+				.collect()),
 		}
 	}
 
@@ -528,7 +925,12 @@ impl PesdePackageSourceBackend for PesdePackageBackends {
 		match self {
 			PesdePackageBackends::Git(repo) => repo
 				.download_entries(project, package, version_id, reporter)
-				.map(|r| r.map_err(|e| errors::DownloadErrorKind::Git(e).into())),
+				.map(|r| r.map_err(|e| errors::DownloadErrorKind::Git(e).into()))
+				.left_stream(),
+			PesdePackageBackends::Api(api) => api
+				.download_entries(project, package, version_id, reporter)
+				.map(|r| r.map_err(|e| errors::DownloadErrorKind::Api(e).into()))
+				.right_stream(),
 		}
 	}
 }
@@ -568,6 +970,10 @@ pub mod errors {
 		/// An error occurred from the Git backend
 		#[error("error from git backend")]
 		Git(#[from] GitConfigError),
+
+		/// An error occurred from the HTTP API backend
+		#[error("error from http api backend")]
+		Api(#[from] ApiConfigError),
 	}
 
 	/// Errors that can occur when reading an index file for a pesde package source
@@ -578,6 +984,10 @@ pub mod errors {
 		/// An error occurred from the Git backend
 		#[error("error from git backend")]
 		Git(#[from] GitReadIndexFileError),
+
+		/// An error occurred from the HTTP API backend
+		#[error("error from http api backend")]
+		Api(#[from] ApiReadIndexFileError),
 	}
 
 	/// Errors that can occur when downloading a package from a pesde package source
@@ -588,6 +998,10 @@ pub mod errors {
 		/// An error occurred from the Git backend
 		#[error("error from git backend")]
 		Git(#[from] GitDownloadError),
+
+		/// An error occurred from the HTTP API backend
+		#[error("error from http api backend")]
+		Api(#[from] ApiDownloadError),
 	}
 
 	/// Errors that can occur when downloading a package from a Git-based pesde package source
@@ -599,10 +1013,24 @@ pub mod errors {
 		#[error("error reading config")]
 		Config(#[from] GitConfigError),
 
+		/// An error occurred reading the archive
+		#[error("error reading archive")]
+		Archive(#[from] ArchiveDownloadError),
+	}
+
+	/// Errors that can occur when downloading a package's archive from a pesde package source
+	#[derive(Debug, Error, thiserror_ext::Box)]
+	#[thiserror_ext(newtype(name = ArchiveDownloadError))]
+	#[non_exhaustive]
+	pub enum ArchiveDownloadErrorKind {
 		/// An error occurred downloading the archive
 		#[error("error downloading archive")]
 		Download(#[from] reqwest::Error),
 
+		/// An error occurred reading the archive from the local filesystem
+		#[error("error reading local archive")]
+		ReadLocal(#[source] std::io::Error),
+
 		/// An error occurred opening the archive
 		#[error("error opening archive")]
 		OpenArchive(#[source] std::io::Error),
@@ -614,6 +1042,14 @@ pub mod errors {
 		/// An invalid path was encountered in the archive
 		#[error("invalid path in archive")]
 		InvalidPath,
+
+		/// A path in the archive is absolute or escapes the package directory
+		#[error("unsafe path `{0}` in archive")]
+		UnsafePath(String),
+
+		/// The package is larger than the maximum download size
+		#[error("package exceeds the maximum download size of {0} bytes")]
+		TooLarge(u64),
 	}
 
 	/// Errors that can occur when reading the config file from a Git-based pesde package source
@@ -662,6 +1098,52 @@ pub mod errors {
 		/// An error occurred parsing the file
 		#[error("error parsing file")]
 		Parse(#[from] toml::de::Error),
+
+		/// An error occurred decoding a tree
+		#[error("error decoding tree")]
+		Decode(#[from] gix::objs::decode::Error),
+
+		/// An error occurred finding a tree
+		#[error("error finding tree")]
+		FindTree(#[from] gix::object::find::existing::with_conversion::Error),
+	}
+
+	/// Errors that can occur when reading the config from an HTTP API-based pesde package source
+	#[derive(Debug, Error, thiserror_ext::Box)]
+	#[thiserror_ext(newtype(name = ApiConfigError))]
+	#[non_exhaustive]
+	pub enum ApiConfigErrorKind {
+		/// An error occurred requesting the config
+		#[error("error requesting config")]
+		Request(#[from] reqwest::Error),
+	}
+
+	/// Errors that can occur when reading an index file from an HTTP API-based pesde package source
+	#[derive(Debug, Error, thiserror_ext::Box)]
+	#[thiserror_ext(newtype(name = ApiReadIndexFileError))]
+	#[non_exhaustive]
+	pub enum ApiReadIndexFileErrorKind {
+		/// An error occurred requesting the index file
+		#[error("error requesting index file")]
+		Request(#[from] reqwest::Error),
+
+		/// The API does not support listing every package
+		#[error("listing all packages is not supported by the api index at {0}")]
+		ListingUnsupported(GixUrl),
+	}
+
+	/// Errors that can occur when downloading a package from an HTTP API-based pesde package source
+	#[derive(Debug, Error, thiserror_ext::Box)]
+	#[thiserror_ext(newtype(name = ApiDownloadError))]
+	#[non_exhaustive]
+	pub enum ApiDownloadErrorKind {
+		/// An error occurred reading the config
+		#[error("error reading config")]
+		Config(#[from] ApiConfigError),
+
+		/// An error occurred reading the archive
+		#[error("error reading archive")]
+		Archive(#[from] ArchiveDownloadError),
 	}
 
 	/// Errors that can occur when parsing a version ID
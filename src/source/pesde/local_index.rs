@@ -0,0 +1,169 @@
+//! A pesde index on the local filesystem for tests. It doesn't use anything from the library, so
+//! that the CLI's tests can include it too
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// A pesde index on the local filesystem, whose directory also holds the archives of the
+/// packages published to it. Using it doesn't need network access
+pub struct LocalIndex {
+	dir: PathBuf,
+	repo: gix::Repository,
+	/// The contents of the index files, by scope and name
+	index_files: BTreeMap<String, BTreeMap<String, String>>,
+	head: Option<gix::ObjectId>,
+}
+
+impl LocalIndex {
+	pub fn new(dir: &Path) -> Self {
+		std::fs::create_dir_all(dir).unwrap();
+		let mut index = LocalIndex {
+			dir: dir.to_path_buf(),
+			repo: gix::init_bare(dir).unwrap(),
+			index_files: BTreeMap::new(),
+			head: None,
+		};
+		index.commit();
+		index
+	}
+
+	/// The URL of the index, for use in manifests
+	pub fn url(&self) -> String {
+		format!("file://{}", self.dir.display())
+	}
+
+	/// Publishes a package version containing `files`. `target` is an inline TOML table
+	pub async fn publish(
+		&mut self,
+		name: &str,
+		version: &str,
+		target: &str,
+		files: &[(&str, &str)],
+	) {
+		use std::fmt::Write as _;
+		use tokio::io::AsyncWriteExt as _;
+
+		let kind = toml::from_str::<toml::Table>(&format!("target = {target}")).unwrap()["target"]
+			["environment"]
+			.as_str()
+			.unwrap()
+			.to_string();
+		let (scope, package) = name.split_once('/').unwrap();
+		let index_file = self
+			.index_files
+			.entry(scope.to_string())
+			.or_default()
+			.entry(package.to_string())
+			.or_default();
+		writeln!(
+			index_file,
+			"[\"{version} {kind}\"]\npublished_at = \"2024-01-01T00:00:00Z\"\ntarget = {target}"
+		)
+		.unwrap();
+
+		let archive_path = self
+			.dir
+			.join("archives")
+			.join(name)
+			.join(format!("{version}+{kind}.tar.gz"));
+		fs_err::tokio::create_dir_all(archive_path.parent().unwrap())
+			.await
+			.unwrap();
+		let mut archive =
+			async_tar::Builder::new(async_compression::tokio::write::GzipEncoder::new(
+				fs_err::tokio::File::create(&archive_path).await.unwrap(),
+			));
+		for (path, contents) in files {
+			let mut header = async_tar::Header::new_gnu();
+			header.set_size(contents.len() as u64);
+			header.set_mode(0o644);
+			header.set_cksum();
+			archive
+				.append_data(&mut header, path, contents.as_bytes())
+				.await
+				.unwrap();
+		}
+		archive
+			.into_inner()
+			.await
+			.unwrap()
+			.shutdown()
+			.await
+			.unwrap();
+
+		self.commit();
+	}
+
+	fn commit(&mut self) {
+		let blob = |contents: &str| self.repo.write_blob(contents).unwrap().detach();
+
+		let mut root = vec![(
+			"config.toml",
+			blob(
+				"api = \"https://registry.invalid\"\ndownload = \"archives/{PACKAGE}/{PACKAGE_VERSION}+{PACKAGE_TARGET}.tar.gz\"\n",
+			),
+			false,
+		)];
+		for (scope, packages) in &self.index_files {
+			let entries = packages
+				.iter()
+				.map(|(name, contents)| (name.as_str(), blob(contents), false))
+				.collect();
+			root.push((scope.as_str(), write_tree(&self.repo, entries), true));
+		}
+		let tree = write_tree(&self.repo, root);
+
+		let signature = gix::actor::Signature {
+			name: "pesde".into(),
+			email: "pesde@localhost".into(),
+			time: gix::date::Time::new(0, 0),
+		};
+		let commit = self
+			.repo
+			.write_object(gix::objs::Commit {
+				tree,
+				parents: self.head.into_iter().collect(),
+				author: signature.clone(),
+				committer: signature,
+				encoding: None,
+				message: "publish".into(),
+				extra_headers: vec![],
+			})
+			.unwrap()
+			.detach();
+		self.repo
+			.reference(
+				"refs/heads/main",
+				commit,
+				gix::refs::transaction::PreviousValue::Any,
+				"publish",
+			)
+			.unwrap();
+		self.head = Some(commit);
+	}
+}
+
+pub fn write_tree(
+	repo: &gix::Repository,
+	entries: Vec<(&str, gix::ObjectId, bool)>,
+) -> gix::ObjectId {
+	let mut entries = entries
+		.into_iter()
+		.map(|(name, oid, is_tree)| gix::objs::tree::Entry {
+			mode: if is_tree {
+				gix::objs::tree::EntryKind::Tree
+			} else {
+				gix::objs::tree::EntryKind::Blob
+			}
+			.into(),
+			filename: name.into(),
+			oid,
+		})
+		.collect::<Vec<_>>();
+	entries.sort();
+
+	repo.write_object(gix::objs::Tree { entries })
+		.unwrap()
+		.detach()
+}
@@ -10,12 +10,13 @@ use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use backend::GitPesdePackageSourceBackend;
+use backend::AllIndexFiles;
 use backend::IndexDependencySpecifiers;
 use backend::IndexFile;
 use backend::PesdePackageBackends;
 use backend::PesdePackageSourceBackend as _;
 use backend::VersionId;
+use backend::errors::ReadIndexFileError;
 use futures::StreamExt as _;
 use pkg_ref::PesdePackageRef;
 use serde::Deserialize;
@@ -29,8 +30,6 @@ use crate::manifest::Manifest;
 use crate::reporters::DownloadProgressReporter;
 use crate::ser_display_deser_fromstr;
 use crate::source::DependencySpecifiers;
-use crate::source::IGNORED_DIRS;
-use crate::source::IGNORED_FILES;
 use crate::source::PackageExports;
 use crate::source::PackageRefs;
 use crate::source::PackageSource;
@@ -42,10 +41,13 @@ use crate::source::StructureKind;
 use crate::source::fs::PackageFs;
 use crate::source::fs::store_in_cas;
 use crate::source::git::specifier::GitDependencySpecifier;
+use crate::source::is_ignored_dir;
+use crate::source::is_ignored_file;
 use crate::source::pesde::target::Target;
 use crate::source::wally::specifier::WallyDependencySpecifier;
 use crate::util::ToEscaped as _;
 use crate::version_matches;
+use crate::version_matches_including_prerelease;
 use fs_err::tokio as fs;
 use semver::Version;
 use tracing::instrument;
@@ -84,12 +86,10 @@ impl PesdePackageSource {
 		Self { repo }
 	}
 
-	/// Creates a pesde package source from a URL
+	/// Creates a pesde package source from a URL, see [`PesdePackageBackends::from_url`]
 	#[must_use]
 	pub fn from_url(repo_url: GixUrl) -> Self {
-		Self::new(PesdePackageBackends::Git(
-			GitPesdePackageSourceBackend::new(repo_url),
-		))
+		Self::new(PesdePackageBackends::from_url(repo_url))
 	}
 
 	/// Gets the repository backend
@@ -97,6 +97,17 @@ impl PesdePackageSource {
 	pub fn repo(&self) -> &PesdePackageBackends {
 		&self.repo
 	}
+
+	/// Reads the index files of every package in the index.
+	/// Errors reading or parsing an individual package's index file are returned in its entry
+	/// instead of aborting the whole walk
+	#[instrument(skip_all, level = "debug")]
+	pub async fn all_packages(
+		&self,
+		project: &Project,
+	) -> Result<AllIndexFiles<ReadIndexFileError>, ReadIndexFileError> {
+		self.repo.all_index_files(project).await
+	}
 }
 
 impl PackageSource for PesdePackageSource {
@@ -115,12 +126,18 @@ impl PackageSource for PesdePackageSource {
 		&self,
 		subproject: &Subproject,
 		specifier: &DependencySpecifiers,
-		_refreshed_sources: &RefreshedSources,
+		refreshed_sources: &RefreshedSources,
 	) -> Result<ResolveResult, Self::ResolveError> {
 		let DependencySpecifiers::Pesde(specifier) = specifier else {
 			unreachable!("invalid specifier type for pesde package source");
 		};
 
+		let version_matches = if refreshed_sources.includes_prerelease() {
+			version_matches_including_prerelease
+		} else {
+			version_matches
+		};
+
 		let Some(IndexFile { entries, .. }) = self
 			.repo
 			.read_index_file(subproject.project(), specifier.name.clone())
@@ -262,14 +279,14 @@ impl PackageSource for PesdePackageSource {
 			};
 
 			let Some(contents) = contents else {
-				if IGNORED_DIRS.contains(&name) {
+				if is_ignored_dir(name) {
 					continue;
 				}
 				entries.insert(path, None);
 				continue;
 			};
 
-			if IGNORED_FILES.contains(&name) {
+			if is_ignored_file(name) {
 				continue;
 			}
 
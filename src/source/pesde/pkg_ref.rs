@@ -17,7 +17,15 @@ pub struct PesdePackageRef {
 }
 ser_display_deser_fromstr!(PesdePackageRef);
 
-impl PackageRef for PesdePackageRef {}
+impl PackageRef for PesdePackageRef {
+	fn is_local(&self) -> bool {
+		false
+	}
+
+	fn is_wally_package(&self) -> bool {
+		false
+	}
+}
 
 impl Display for PesdePackageRef {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
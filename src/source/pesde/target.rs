@@ -1,3 +1,4 @@
+use crate::manifest::BinPlatforms;
 use crate::ser_display_deser_fromstr;
 use crate::source::PackageExports;
 use relative_path::RelativePathBuf;
@@ -142,10 +143,12 @@ impl Target {
 			Self::Roblox { lib } | Self::RobloxServer { lib } => PackageExports {
 				lib_file: lib,
 				bin_file: None,
+				bin_platforms: BinPlatforms::default(),
 			},
 			Self::Lune { lib, bin } | Self::Luau { lib, bin } => PackageExports {
 				lib_file: lib,
 				bin_file: bin,
+				bin_platforms: BinPlatforms::default(),
 			},
 		}
 	}
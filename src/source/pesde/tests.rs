@@ -0,0 +1,296 @@
+//! Tests of the pesde source
+
+use crate::RefreshedSources;
+use crate::source::DependencySpecifiers;
+use crate::source::PackageSource as _;
+use crate::source::PackageSources;
+use crate::source::local_index::write_tree;
+use relative_path::RelativePathBuf;
+
+#[test]
+#[expect(deprecated)]
+fn index_config_ignored_entries() {
+	use relative_path::RelativePath;
+
+	let config: crate::source::pesde::backend::IndexConfig = toml::from_str(
+		r#"
+		api = "https://registry.pesde.dev"
+		ignored_files = [".env"]
+		ignored_dirs = ["secrets"]
+		"#,
+	)
+	.unwrap();
+
+	assert!(config.is_ignored(RelativePath::new("src/.ENV"), false));
+	assert!(config.is_ignored(RelativePath::new("secrets"), true));
+	assert!(config.is_ignored(RelativePath::new("secrets/key.txt"), false));
+	assert!(!config.is_ignored(RelativePath::new("src/init.luau"), false));
+	assert!(!config.is_ignored(RelativePath::new(".env"), true));
+}
+
+/// Creates the local clone of an index as a refresh would leave it, without any network access
+fn init_index_clone(path: &std::path::Path, url: &str) -> gix::Repository {
+	std::fs::create_dir_all(path).unwrap();
+	gix::init_bare(path).unwrap();
+	let config = std::fs::read_to_string(path.join("config")).unwrap();
+	std::fs::write(
+		path.join("config"),
+		format!(
+			"{config}[remote \"origin\"]\n\turl = {url}\n\tfetch = +refs/heads/*:refs/remotes/origin/*\n"
+		),
+	)
+	.unwrap();
+
+	gix::open(path).unwrap()
+}
+
+fn set_index_root(repo: &gix::Repository, root: gix::ObjectId) {
+	repo.reference(
+		"refs/remotes/origin/main",
+		root,
+		gix::refs::transaction::PreviousValue::Any,
+		"test",
+	)
+	.unwrap();
+}
+
+#[tokio::test]
+#[expect(deprecated)]
+async fn pesde_all_packages() {
+	use crate::source::pesde::PesdePackageSource;
+	use crate::source::pesde::backend::PesdePackageBackends;
+	use crate::source::pesde::backend::SCOPE_INFO_FILE;
+
+	let dir = tempfile::tempdir().unwrap();
+	let project = crate::tests::test_project(dir.path(), dir.path().join("project"));
+	let source =
+		PesdePackageSource::from_url("https://github.com/pesde-pkg/index".parse().unwrap());
+	let PesdePackageBackends::Git(backend) = source.repo() else {
+		unreachable!("index url is not an api url");
+	};
+
+	let repo = init_index_clone(
+		&backend.path(&project),
+		"https://github.com/pesde-pkg/index",
+	);
+
+	let blob = |contents: &str| repo.write_blob(contents).unwrap().detach();
+	let valid = blob(
+		"[\"1.0.0 luau\"]\npublished_at = \"2024-01-01T00:00:00Z\"\ntarget = { environment = \"luau\" }\n",
+	);
+	let invalid = blob("this is not toml");
+	let scope_info = blob("owners = []\n");
+	let config = blob("api = \"https://registry.pesde.dev\"\n");
+
+	let scope = write_tree(
+		&repo,
+		vec![
+			("hello", valid, false),
+			("broken", invalid, false),
+			(SCOPE_INFO_FILE, scope_info, false),
+		],
+	);
+	let root = write_tree(
+		&repo,
+		vec![("config.toml", config, false), ("pesde", scope, true)],
+	);
+	set_index_root(&repo, root);
+
+	let packages = source.all_packages(&project).await.unwrap();
+	assert_eq!(packages.len(), 2);
+
+	let hello = packages[&"pesde/hello".parse().unwrap()].as_ref().unwrap();
+	assert_eq!(
+		hello
+			.entries
+			.keys()
+			.map(ToString::to_string)
+			.collect::<Vec<_>>(),
+		["1.0.0:luau"]
+	);
+	assert!(packages[&"pesde/broken".parse().unwrap()].is_err());
+}
+
+#[tokio::test]
+#[expect(deprecated)]
+async fn pesde_api_resolve() {
+	use crate::source::pesde::PesdePackageSource;
+	use crate::source::pesde::backend::PesdePackageBackends;
+	use crate::source::pesde::backend::PesdePackageSourceBackend as _;
+	use crate::source::pesde::specifier::PesdeDependencySpecifier;
+	use crate::source::pesde::target::TargetKind;
+	use std::sync::Arc;
+	use std::sync::atomic::AtomicUsize;
+	use std::sync::atomic::Ordering;
+
+	let config_requests = Arc::new(AtomicUsize::new(0));
+	let server_config_requests = config_requests.clone();
+	let addr = crate::tests::mock_http_server(move |request| {
+		if request.starts_with("GET /v1/index/pesde/hello ") {
+			crate::tests::json_response(
+				"200 OK",
+				r#"{"1.0.0 luau":{"published_at":"2024-01-01T00:00:00Z","target":{"environment":"luau"}},"2.0.0 luau":{"published_at":"2024-01-01T00:00:00Z","target":{"environment":"luau"}}}"#,
+			)
+		} else if request.starts_with("GET /v1/index/config ") {
+			server_config_requests.fetch_add(1, Ordering::SeqCst);
+			crate::tests::json_response("200 OK", r#"{"api":"http://localhost"}"#)
+		} else {
+			crate::tests::json_response("404 Not Found", "")
+		}
+	})
+	.await;
+
+	let dir = tempfile::tempdir().unwrap();
+	tokio::fs::write(dir.path().join(crate::MANIFEST_FILE_NAME), "")
+		.await
+		.unwrap();
+	let project = crate::tests::test_project(dir.path(), dir.path());
+	let subproject = project.clone().subproject(crate::Importer::root());
+
+	let source = PesdePackageSource::from_url(format!("api+http://{addr}").parse().unwrap());
+	assert!(matches!(source.repo(), PesdePackageBackends::Api(_)));
+	assert_eq!(source.to_string(), format!("api+http://{addr}"));
+	assert!(
+		!PackageSources::Pesde(source.clone())
+			.has_local_index(&project)
+			.await
+	);
+
+	let specifier = |name: &str| {
+		DependencySpecifiers::Pesde(PesdeDependencySpecifier {
+			name: name.parse().unwrap(),
+			version: "^1.0.0".parse().unwrap(),
+			index: crate::DEFAULT_INDEX_NAME.to_string(),
+			target: TargetKind::Luau,
+		})
+	};
+
+	let result = source
+		.resolve(
+			&subproject,
+			&specifier("pesde/hello"),
+			&RefreshedSources::new(),
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		result.versions.into_keys().collect::<Vec<_>>(),
+		[semver::Version::new(1, 0, 0)]
+	);
+
+	assert!(
+		source
+			.resolve(
+				&subproject,
+				&specifier("pesde/missing"),
+				&RefreshedSources::new()
+			)
+			.await
+			.is_err()
+	);
+
+	// the config is only fetched once, even across clones of the source
+	let config = source.repo().config(&project).await.unwrap();
+	assert_eq!(config.api(), "http://localhost");
+	source.clone().repo().config(&project).await.unwrap();
+	assert_eq!(config_requests.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+#[expect(deprecated)]
+fn local_download_paths() {
+	use crate::source::pesde::backend::local_download_path;
+	use std::path::Path;
+
+	let index_dir = Path::new("/srv/index");
+
+	// remote indices may never point at the local filesystem
+	assert_eq!(local_download_path("file:///etc/passwd", None), None);
+	assert_eq!(local_download_path("/etc/passwd", None), None);
+	assert_eq!(local_download_path("archives/foo.tar.gz", None), None);
+
+	assert_eq!(
+		local_download_path("https://registry.pesde.dev/archive", Some(index_dir)),
+		None
+	);
+	assert_eq!(
+		local_download_path("archives/pesde%2Fhello.tar.gz", Some(index_dir)).unwrap(),
+		Path::new("/srv/index/archives/pesde/hello.tar.gz")
+	);
+
+	#[cfg(unix)]
+	assert_eq!(
+		local_download_path("file:///srv/archives/pesde%2Fhello.tar.gz", Some(index_dir)),
+		local_download_path("/srv/archives/pesde%2Fhello.tar.gz", Some(index_dir)),
+	);
+}
+
+#[tokio::test]
+#[expect(deprecated)]
+async fn pesde_local_archive_download() {
+	use crate::source::pesde::PesdePackageSource;
+	use crate::source::pesde::backend::PesdePackageBackends;
+	use crate::source::pesde::backend::PesdePackageSourceBackend as _;
+	use futures::StreamExt as _;
+	use tokio::io::AsyncWriteExt as _;
+
+	let dir = tempfile::tempdir().unwrap();
+	let project = crate::tests::test_project(dir.path(), dir.path().join("project"));
+
+	let index_dir = dir.path().join("index");
+	let source =
+		PesdePackageSource::from_url(format!("file://{}", index_dir.display()).parse().unwrap());
+	let PesdePackageBackends::Git(backend) = source.repo() else {
+		unreachable!("index url is not an api url");
+	};
+
+	let repo = init_index_clone(&backend.path(&project), &source.to_string());
+	let config = repo
+		.write_blob(
+			"api = \"https://registry.invalid\"\ndownload = \"archives/{PACKAGE}/{PACKAGE_VERSION}.tar.gz\"\n",
+		)
+		.unwrap()
+		.detach();
+	set_index_root(
+		&repo,
+		write_tree(&repo, vec![("config.toml", config, false)]),
+	);
+
+	let archive_path = index_dir.join("archives/pesde/hello/1.0.0.tar.gz");
+	fs_err::tokio::create_dir_all(archive_path.parent().unwrap())
+		.await
+		.unwrap();
+	let mut archive = async_tar::Builder::new(async_compression::tokio::write::GzipEncoder::new(
+		fs_err::tokio::File::create(&archive_path).await.unwrap(),
+	));
+	let mut header = async_tar::Header::new_gnu();
+	header.set_size(6);
+	header.set_mode(0o644);
+	header.set_cksum();
+	archive
+		.append_data(&mut header, "init.luau", &b"return"[..])
+		.await
+		.unwrap();
+	archive
+		.into_inner()
+		.await
+		.unwrap()
+		.shutdown()
+		.await
+		.unwrap();
+
+	let entries = backend
+		.download_entries(
+			&project,
+			&"pesde/hello".parse().unwrap(),
+			&"1.0.0 luau".parse().unwrap(),
+			std::sync::Arc::new(()),
+		)
+		.map(Result::unwrap)
+		.collect::<Vec<_>>()
+		.await;
+	assert_eq!(
+		entries,
+		[(RelativePathBuf::from("init.luau"), Some(b"return".to_vec()))]
+	);
+}
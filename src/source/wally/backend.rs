@@ -8,6 +8,7 @@ use crate::reporters::DownloadProgressReporter;
 use crate::source::git_index::read_file;
 use crate::source::git_index::root_tree;
 use crate::util::ToEscaped as _;
+use crate::util::archive_entry_path;
 use async_stream::try_stream;
 use futures::AsyncReadExt as _;
 use futures::Stream;
@@ -105,7 +106,7 @@ impl GitWallyPackageSourceBackend {
 		Self { repo_url }
 	}
 
-	fn path(&self, project: &Project) -> PathBuf {
+	pub(crate) fn path(&self, project: &Project) -> PathBuf {
 		project
 			.data_dir()
 			.join("git_repos")
@@ -128,7 +129,12 @@ impl WallyPackageSourceBackend for GitWallyPackageSourceBackend {
 
 	#[instrument(skip_all, level = "debug")]
 	async fn refresh(&self, project: &Project) -> Result<(), Self::RefreshError> {
-		crate::source::git_index::refresh_git_repo(self.path(project), self.repo_url.clone()).await
+		crate::source::git_index::refresh_git_repo(
+			self.path(project),
+			self.repo_url.clone(),
+			project,
+		)
+		.await
 	}
 
 	#[instrument(skip_all, ret(level = "trace"), level = "debug")]
@@ -208,15 +214,23 @@ impl WallyPackageSourceBackend for GitWallyPackageSourceBackend {
 
 			let response = request.send().await?.error_for_status()?;
 
-			let total_len = response.content_length().unwrap_or(0);
-			let bytes = crate::reporters::response_to_async_buf_read(response, reporter.clone());
+			let max_size = crate::reporters::max_download_size(None);
+			// the content length is only checked against the limit once reading starts, so it's
+			// only trusted up to the limit, and not at all without one
+			let capacity = max_size.map_or(0, |max_size| {
+				response.content_length().unwrap_or(0).min(max_size)
+			});
+			let bytes =
+				crate::reporters::response_to_async_buf_read(response, reporter.clone(), max_size);
 			tokio::pin!(bytes);
 
-			let mut archive_bytes = Vec::with_capacity(total_len as usize);
-			bytes
-				.read_to_end(&mut archive_bytes)
-				.await
-				.map_err(errors::GitDownloadErrorKind::ReadEntryContents)?;
+			let mut archive_bytes = Vec::with_capacity(usize::try_from(capacity).unwrap_or(0));
+			bytes.read_to_end(&mut archive_bytes).await.map_err(|e| {
+				match crate::reporters::DownloadTooLarge::find(&e) {
+					Some(max_size) => errors::GitDownloadErrorKind::TooLarge(max_size),
+					None => errors::GitDownloadErrorKind::ReadEntryContents(e),
+				}
+			})?;
 
 			let zip_file = BufReader::new(std::io::Cursor::new(archive_bytes));
 
@@ -227,7 +241,9 @@ impl WallyPackageSourceBackend for GitWallyPackageSourceBackend {
 				let entry = archive.file().entries().get(index).unwrap();
 				let entry_name = entry.filename().as_str()?;
 
-				let path = RelativePathBuf::from_path(entry_name)?;
+				let path = archive_entry_path(entry_name).ok_or_else(|| {
+					errors::GitDownloadErrorKind::UnsafePath(entry_name.to_string())
+				})?;
 
 				let is_dir = entry.dir()?;
 
@@ -477,8 +493,12 @@ pub mod errors {
 		#[error("error reading entry contents from archive")]
 		ReadEntryContents(#[source] std::io::Error),
 
-		/// An error occurred parsing an entry path
-		#[error("error parsing entry path")]
-		InvalidPath(#[from] relative_path::FromPathError),
+		/// A path in the archive is absolute or escapes the package directory
+		#[error("unsafe path `{0}` in archive")]
+		UnsafePath(String),
+
+		/// The package is larger than the maximum download size
+		#[error("package exceeds the maximum download size of {0} bytes")]
+		TooLarge(u64),
 	}
 }
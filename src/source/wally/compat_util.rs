@@ -10,6 +10,7 @@ use serde::Deserialize;
 use crate::Importer;
 use crate::LINK_LIB_NO_FILE_FOUND;
 use crate::Project;
+use crate::manifest::BinPlatforms;
 use crate::scripts::ExecuteScriptHooks;
 use crate::scripts::execute_script;
 use crate::source::PackageExports;
@@ -134,6 +135,7 @@ pub(crate) async fn get_exports(
 	Ok(PackageExports {
 		lib_file,
 		bin_file: None,
+		bin_platforms: BinPlatforms::default(),
 	})
 }
 
@@ -78,23 +78,36 @@ impl WallyManifest {
 	#[instrument(skip(self), ret(level = "trace"), level = "debug")]
 	pub(crate) fn into_resolve_entry(self) -> Result<ResolveEntry, errors::AllDependenciesError> {
 		let mut all_deps = BTreeMap::new();
+		let mut tables = BTreeMap::new();
 
-		for (mut deps, ty) in [
-			(self.dependencies, DependencyType::Standard),
-			(self.server_dependencies, DependencyType::Standard),
-			(self.dev_dependencies, DependencyType::Dev),
+		for (mut deps, ty, table) in [
+			(self.dependencies, DependencyType::Standard, "dependencies"),
+			(
+				self.server_dependencies,
+				DependencyType::Standard,
+				"server-dependencies",
+			),
+			(
+				self.dev_dependencies,
+				DependencyType::Dev,
+				"dev-dependencies",
+			),
 		] {
 			while let Some((alias, mut spec)) = deps.pop_first() {
 				spec.index = self.package.registry.to_string();
 
 				// TODO: update realm based on the package's canonical value (WallyPackage.realm)
 
-				if all_deps
-					.insert(alias.clone(), (DependencySpecifiers::Wally(spec), ty))
-					.is_some()
-				{
-					return Err(errors::AllDependenciesErrorKind::AliasConflict(alias).into());
+				if let Some(other_table) = tables.insert(alias.clone(), table) {
+					return Err(errors::AllDependenciesErrorKind::AliasConflict(
+						alias,
+						other_table,
+						table,
+					)
+					.into());
 				}
+
+				all_deps.insert(alias, (DependencySpecifiers::Wally(spec), ty));
 			}
 		}
 
@@ -6,8 +6,6 @@ use crate::Subproject;
 use crate::reporters::DownloadProgressReporter;
 use crate::ser_display_deser_fromstr;
 use crate::source::DependencySpecifiers;
-use crate::source::IGNORED_DIRS;
-use crate::source::IGNORED_FILES;
 use crate::source::PackageExports;
 use crate::source::PackageRefs;
 use crate::source::PackageSource;
@@ -17,6 +15,8 @@ use crate::source::ResolvedPackage;
 use crate::source::StructureKind;
 use crate::source::fs::PackageFs;
 use crate::source::fs::store_in_cas;
+use crate::source::is_ignored_dir;
+use crate::source::is_ignored_file;
 use crate::source::wally::backend::GitWallyPackageSourceBackend;
 use crate::source::wally::backend::WallyPackageBackends;
 use crate::source::wally::backend::WallyPackageSourceBackend as _;
@@ -243,14 +243,14 @@ impl PackageSource for WallyPackageSource {
 			};
 
 			let Some(contents) = contents else {
-				if IGNORED_DIRS.contains(&name) {
+				if is_ignored_dir(name) {
 					continue;
 				}
 				entries.insert(path, None);
 				continue;
 			};
 
-			if IGNORED_FILES.contains(&name) {
+			if is_ignored_file(name) {
 				continue;
 			}
 
@@ -14,7 +14,15 @@ pub struct WallyPackageRef {
 }
 ser_display_deser_fromstr!(WallyPackageRef);
 
-impl PackageRef for WallyPackageRef {}
+impl PackageRef for WallyPackageRef {
+	fn is_local(&self) -> bool {
+		false
+	}
+
+	fn is_wally_package(&self) -> bool {
+		true
+	}
+}
 
 impl Display for WallyPackageRef {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
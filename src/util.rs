@@ -1,6 +1,7 @@
 //! Code that we want to share between the lib and bin without exposing it in the public API
 use fs_err::tokio as fs;
 use relative_path::RelativePath;
+use relative_path::RelativePathBuf;
 use serde::Deserialize;
 use serde::Deserializer;
 use serde::de::MapAccess;
@@ -141,6 +142,29 @@ pub fn relative_path_is_empty(path: &RelativePath) -> bool {
 	path.as_str().is_empty()
 }
 
+/// Converts the path of an archive entry into a relative path, returning `None` if it is absolute
+/// or contains `..`, which could make extracting it escape the destination directory
+#[must_use]
+pub fn archive_entry_path(path: &str) -> Option<RelativePathBuf> {
+	if path.starts_with(['/', '\\']) {
+		return None;
+	}
+
+	let mut entry_path = RelativePathBuf::new();
+
+	for (i, component) in path.split(['/', '\\']).enumerate() {
+		match component {
+			"" | "." => {}
+			".." => return None,
+			// drive prefixes, such as `C:`
+			_ if i == 0 && component.ends_with(':') => return None,
+			_ => entry_path.push(component),
+		}
+	}
+
+	Some(entry_path)
+}
+
 #[must_use]
 pub fn relative_path_level(path: &RelativePath) -> isize {
 	use relative_path::Component;
@@ -170,3 +194,33 @@ impl ToEscaped for String {
 			.collect()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn archive_entry_paths() {
+		for (path, expected) in [
+			("init.luau", "init.luau"),
+			("src/init.luau", "src/init.luau"),
+			("./src//init.luau", "src/init.luau"),
+			("src\\init.luau", "src/init.luau"),
+			("src/", "src"),
+		] {
+			assert_eq!(archive_entry_path(path).unwrap(), expected);
+		}
+
+		for path in [
+			"../init.luau",
+			"src/../../init.luau",
+			"src\\..\\..\\init.luau",
+			"/etc/passwd",
+			"\\Windows\\System32",
+			"C:\\Windows",
+			"C:/Windows",
+		] {
+			assert!(archive_entry_path(path).is_none(), "{path} was accepted");
+		}
+	}
+}